@@ -38,8 +38,9 @@ enum HeaderValue {
         #[inline]
         fn from(value: CowSlice) -> Self {
             match value {
-                CowSlice::Own(o) => Self::Bytes(o.into()),
-                CowSlice::Ref(r) => Self::Slice(r),
+                CowSlice::Own(o)    => Self::Bytes(o.into()),
+                CowSlice::Ref(r)    => Self::Slice(r),
+                CowSlice::Shared(s) => Self::Bytes(s.to_vec()),
             }
         }
     }
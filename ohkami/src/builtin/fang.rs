@@ -11,3 +11,13 @@ pub use basicauth::BasicAuth;
 pub(crate) mod timeout;
 #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
 pub use timeout::Timeout;
+
+#[cfg(any(feature="gzip",feature="deflate",feature="br",feature="zstd"))]
+pub(crate) mod compress;
+#[cfg(any(feature="gzip",feature="deflate",feature="br",feature="zstd"))]
+pub use compress::{Compress, Encoding};
+
+#[cfg(any(feature="gzip",feature="deflate",feature="br",feature="zstd"))]
+pub(crate) mod decompress;
+#[cfg(any(feature="gzip",feature="deflate",feature="br",feature="zstd"))]
+pub use decompress::Decompress;
@@ -0,0 +1,476 @@
+use crate::{Fang, FangProc, Request, Response};
+use crate::header::append;
+use crate::response::Content;
+use std::{io::Write, sync::Arc};
+
+
+/// # Builtin fang for response compression
+///
+/// <br>
+///
+/// Compress response bodies by the encoding negotiated from `Accept-Encoding`
+/// with its q-values, and set `Content-Encoding` and `Vary: Accept-Encoding`.
+///
+/// Each encoding is available with its feature :
+///
+/// | encoding  | feature     |
+/// |:---------:|:-----------:|
+/// | `zstd`    | `"zstd"`    |
+/// | `br`      | `"br"`      |
+/// | `gzip`    | `"gzip"`    |
+/// | `deflate` | `"deflate"` |
+///
+/// <br>
+///
+/// A response is compressed when
+///
+/// - its body is larger than the threshold (default: 1024 bytes), or is a stream
+/// - its `Content-Type` is not already-compressed one like `image/png`, `video/*` or `application/zip`
+/// - it doesn't have `Content-Encoding` or `Content-Range`
+///
+/// A stream is compressed chunk by chunk, each flushed to be sent without delay.
+///
+/// **Note** : on `rt_worker`, streams ( like SSE ) are sent as they are,
+/// without `Content-Encoding` nor `Vary` set by this fang.
+/// Compression of them is left to Cloudflare.
+///
+/// <br>
+///
+/// ---
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::builtin::fang::Compress;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     Ohkami::with(Compress::new().threshold(512), (
+///         "/".GET(|| async {"Hello, compression!"}),
+///     )).howl("localhost:3000").await
+/// }
+/// ```
+/// ---
+#[derive(Clone)]
+pub struct Compress {
+    threshold: usize,
+    encodings: Arc<[Encoding]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    #[cfg(feature="zstd")]
+    Zstd,
+    #[cfg(feature="br")]
+    Br,
+    #[cfg(feature="gzip")]
+    Gzip,
+    #[cfg(feature="deflate")]
+    Deflate,
+}
+impl Encoding {
+    /// All the available ones in the default preference order
+    pub const ALL: &'static [Self] = &[
+        #[cfg(feature="zstd")]
+        Self::Zstd,
+        #[cfg(feature="br")]
+        Self::Br,
+        #[cfg(feature="gzip")]
+        Self::Gzip,
+        #[cfg(feature="deflate")]
+        Self::Deflate,
+    ];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            #[cfg(feature="zstd")]
+            Self::Zstd    => "zstd",
+            #[cfg(feature="br")]
+            Self::Br      => "br",
+            #[cfg(feature="gzip")]
+            Self::Gzip    => "gzip",
+            #[cfg(feature="deflate")]
+            Self::Deflate => "deflate",
+        }
+    }
+
+    pub(super) fn matches(&self, coding: &str) -> bool {
+        coding.eq_ignore_ascii_case(self.as_str()) || {
+            #[cfg(feature="gzip")] {*self == Self::Gzip && coding.eq_ignore_ascii_case("x-gzip")}
+            #[cfg(not(feature="gzip"))] {false}
+        }
+    }
+}
+
+impl Compress {
+    pub fn new() -> Self {
+        Self {
+            threshold: 1024,
+            encodings: Encoding::ALL.into(),
+        }
+    }
+
+    /// Compress bodies of at least `bytes` bytes. Default: 1024
+    pub fn threshold(mut self, bytes: usize) -> Self {
+        self.threshold = bytes;
+        self
+    }
+
+    /// Use only `encodings`, preferred in this order when `Accept-Encoding`
+    /// gives them the same q-value. Default: `Encoding::ALL`
+    pub fn encodings<const N: usize>(mut self, encodings: [Encoding; N]) -> Self {
+        self.encodings = encodings.into();
+        self
+    }
+}
+impl Default for Compress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const _: () = {
+    impl<Inner: FangProc> Fang<Inner> for Compress {
+        type Proc = CompressProc<Inner>;
+        fn chain(&self, inner: Inner) -> Self::Proc {
+            CompressProc { compress: self.clone(), inner }
+        }
+    }
+
+    pub struct CompressProc<Inner: FangProc> {
+        compress: Compress,
+        inner:    Inner,
+    }
+    impl<Inner: FangProc> FangProc for CompressProc<Inner> {
+        async fn bite<'b>(&'b self, req: &'b mut Request) -> Response {
+            let encoding = req.headers.AcceptEncoding()
+                .and_then(|accept_encoding| negotiate(accept_encoding, &self.compress.encodings));
+            let mut res = self.inner.bite(req).await;
+            self.compress.apply(&mut res, encoding);
+            res
+        }
+    }
+};
+
+impl Compress {
+    fn apply(&self, res: &mut Response, encoding: Option<Encoding>) {
+        if res.headers.ContentEncoding().is_some() || res.headers.ContentRange().is_some() {
+            return
+        }
+        if !res.headers.ContentType().is_some_and(is_compressible) {
+            return
+        }
+        match &res.content {
+            Content::Payload(bytes) if bytes.len() >= self.threshold => (),
+            #[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+            Content::Stream(_) => (),
+            _ => return
+        }
+
+        /* the body depends on `Accept-Encoding` from here, even if not compressed for this request */
+        if !res.headers.Vary().is_some_and(|vary| vary.split(',').any(|v|
+            v.trim() == "*" || v.trim().eq_ignore_ascii_case("Accept-Encoding")
+        )) {
+            res.headers.set().Vary(append("Accept-Encoding"));
+        }
+
+        let Some(encoding) = encoding else {return};
+        match &mut res.content {
+            Content::Payload(bytes) => {
+                let compressed = Encoder::compress(encoding, bytes);
+                if compressed.len() >= bytes.len() {
+                    return
+                }
+                *bytes = compressed.into();
+            }
+            #[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+            Content::Stream(_) => {
+                res.stream_encoder = Some(Box::new(Encoder::new(encoding)));
+            }
+            _ => return
+        }
+        res.headers.set().ContentEncoding(encoding.as_str());
+
+        /* the representation is changed, so a strong ETag is no longer valid for it */
+        if let Some(etag) = res.headers.ETag().filter(|etag| !etag.starts_with("W/")) {
+            let weak = format!("W/{etag}");
+            res.headers.set().ETag(weak);
+        }
+    }
+}
+
+/// The most preferred one of `encodings` by `Accept-Encoding`, or `None` for identity
+fn negotiate(accept_encoding: &str, encodings: &[Encoding]) -> Option<Encoding> {
+    let codings = accept_encoding.split(',').filter_map(|coding| {
+        let mut params = coding.split(';').map(str::trim);
+        let name = params.next().filter(|name| !name.is_empty())?;
+        let q = params
+            .find_map(|p| p.split_once('=').filter(|(k, _)| k.trim().eq_ignore_ascii_case("q")))
+            .map_or(Some(1.), |(_, q)| q.trim().parse::<f32>().ok())?;
+        Some((name, q))
+    }).collect::<Vec<_>>();
+
+    let q_of = |matches: &dyn Fn(&str) -> bool| codings.iter()
+        .find(|(name, _)| matches(name))
+        .or_else(|| codings.iter().find(|(name, _)| *name == "*"))
+        .map(|(_, q)| *q);
+
+    /* identity is preferred only when explicitly given a higher q-value */
+    let identity = codings.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("identity"))
+        .map_or(0., |(_, q)| *q);
+
+    let mut best: Option<(Encoding, f32)> = None;
+    for encoding in encodings {
+        let q = q_of(&|name| encoding.matches(name)).unwrap_or(0.);
+        if q > 0. && q >= identity && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((*encoding, q))
+        }
+    }
+    best.map(|(encoding, _)| encoding)
+}
+
+/// Whether the media type of `content_type` is not already compressed
+fn is_compressible(content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    let Some((main, sub)) = media_type.split_once('/') else {return false};
+
+    let is = |t: &str, candidates: &[&str]| candidates.iter().any(|c| t.eq_ignore_ascii_case(c));
+    if is(main, &["image"]) {
+        is(sub, &["svg+xml", "bmp", "x-icon", "vnd.microsoft.icon"])
+    } else if is(main, &["video", "audio"]) {
+        false
+    } else if is(main, &["font"]) {
+        !is(sub, &["woff", "woff2"])
+    } else if is(main, &["application"]) {
+        !is(sub, &[
+            "zip", "gzip", "x-gzip", "zstd", "x-bzip2", "x-xz", "x-7z-compressed",
+            "vnd.rar", "x-rar-compressed", "octet-stream", "pdf",
+        ])
+    } else {
+        true
+    }
+}
+
+pub(crate) enum Encoder {
+    #[cfg(feature="zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+    #[cfg(feature="br")]
+    Br(Box<brotli::CompressorWriter<Vec<u8>>>),
+    #[cfg(feature="gzip")]
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    /// `deflate` of HTTP is zlib format
+    #[cfg(feature="deflate")]
+    Deflate(flate2::write::ZlibEncoder<Vec<u8>>),
+}
+impl Encoder {
+    fn new(encoding: Encoding) -> Self {
+        match encoding {
+            #[cfg(feature="zstd")]
+            Encoding::Zstd    => Self::Zstd(zstd::stream::write::Encoder::new(Vec::new(), 3).expect("Failed to init zstd encoder")),
+            #[cfg(feature="br")]
+            Encoding::Br      => Self::Br(Box::new(brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22))),
+            #[cfg(feature="gzip")]
+            Encoding::Gzip    => Self::Gzip(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default())),
+            #[cfg(feature="deflate")]
+            Encoding::Deflate => Self::Deflate(flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default())),
+        }
+    }
+
+    pub(super) fn compress(encoding: Encoding, bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = Self::new(encoding);
+        encoder.writer().write_all(bytes).expect("Failed to compress");
+        encoder.finish()
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            #[cfg(feature="zstd")]
+            Self::Zstd(e)    => e,
+            #[cfg(feature="br")]
+            Self::Br(e)      => &mut **e,
+            #[cfg(feature="gzip")]
+            Self::Gzip(e)    => e,
+            #[cfg(feature="deflate")]
+            Self::Deflate(e) => e,
+        }
+    }
+
+    /// Take the bytes compressed so far
+    #[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+    fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(match self {
+            #[cfg(feature="zstd")]
+            Self::Zstd(e)    => e.get_mut(),
+            #[cfg(feature="br")]
+            Self::Br(e)      => e.get_mut(),
+            #[cfg(feature="gzip")]
+            Self::Gzip(e)    => e.get_mut(),
+            #[cfg(feature="deflate")]
+            Self::Deflate(e) => e.get_mut(),
+        })
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            #[cfg(feature="zstd")]
+            Self::Zstd(e)    => e.finish().expect("Failed to compress"),
+            #[cfg(feature="br")]
+            Self::Br(e)      => e.into_inner(),
+            #[cfg(feature="gzip")]
+            Self::Gzip(e)    => e.finish().expect("Failed to compress"),
+            #[cfg(feature="deflate")]
+            Self::Deflate(e) => e.finish().expect("Failed to compress"),
+        }
+    }
+}
+
+#[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+impl crate::response::StreamEncoder for Encoder {
+    fn encode(&mut self, chunk: &[u8]) -> Vec<u8> {
+        let writer = self.writer();
+        writer.write_all(chunk).and_then(|_| writer.flush()).expect("Failed to compress");
+        self.take_output()
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        (*self).finish()
+    }
+}
+
+
+#[cfg(all(test, feature="zstd", feature="br", feature="gzip", feature="deflate"))]
+#[test] fn test_negotiate() {
+    for (accept_encoding, expected) in [
+        ("", None),
+        ("identity", None),
+        ("gzip", Some("gzip")),
+        ("x-gzip", Some("gzip")),
+        ("GZIP, deflate", Some("gzip")),
+        ("deflate, gzip;q=0.5", Some("deflate")),
+        ("gzip;q=0, deflate;q=0", None),
+        ("gzip;q=0.5, identity", None),
+        ("gzip;q=1.0, identity;q=0.5", Some("gzip")),
+        ("*", Some("zstd")),
+        ("br;q=0.9, *;q=0.1", Some("br")),
+        ("zstd, br, gzip, deflate", Some("zstd")),
+        ("gzip;q=invalid, deflate;q=0.1", Some("deflate")),
+    ] {
+        let encodings = [Encoding::Zstd, Encoding::Br, Encoding::Gzip, Encoding::Deflate];
+        assert_eq!(negotiate(accept_encoding, &encodings).map(|e| e.as_str()), expected, "{accept_encoding}");
+    }
+
+    assert_eq!(negotiate("gzip, deflate, br", &[Encoding::Deflate, Encoding::Gzip]), Some(Encoding::Deflate));
+    assert_eq!(negotiate("br", &[Encoding::Gzip]), None);
+}
+
+#[cfg(test)]
+#[test] fn test_is_compressible() {
+    for compressible in ["text/plain; charset=UTF-8", "text/html", "application/json", "image/svg+xml", "text/event-stream", "application/wasm"] {
+        assert!(is_compressible(compressible), "{compressible}")
+    }
+    for compressed in ["image/png", "video/mp4", "audio/ogg", "application/zip", "font/woff2", "application/octet-stream", "invalid"] {
+        assert!(!is_compressible(compressed), "{compressed}")
+    }
+}
+
+#[cfg(all(test, feature="testing", feature="zstd", feature="br", feature="gzip", feature="deflate"))]
+#[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+#[crate::__rt__::test] async fn test_compress() {
+    use crate::prelude::*;
+    use crate::testing::*;
+    use std::io::Read;
+
+    fn decompress(encoding: &str, bytes: &[u8]) -> String {
+        let mut decompressed = String::new();
+        match encoding {
+            "zstd"    => {zstd::stream::read::Decoder::new(bytes).unwrap().read_to_string(&mut decompressed).unwrap();}
+            "br"      => {brotli::Decompressor::new(bytes, 4096).read_to_string(&mut decompressed).unwrap();}
+            "gzip"    => {flate2::read::GzDecoder::new(bytes).read_to_string(&mut decompressed).unwrap();}
+            "deflate" => {flate2::read::ZlibDecoder::new(bytes).read_to_string(&mut decompressed).unwrap();}
+            _ => unreachable!()
+        }
+        decompressed
+    }
+
+    const LARGE: &str = "Hello, compression! ";
+
+    let t = Ohkami::with(Compress::new().threshold(100), (
+        "/large".GET(|| async {LARGE.repeat(10)}),
+        "/small".GET(|| async {"Hello!"}),
+        "/png".GET(|| async {Response::OK().with_payload("image/png", LARGE.repeat(10).into_bytes())}),
+        "/encoded".GET(|| async {Response::OK()
+            .with_text(LARGE.repeat(10))
+            .with_headers(|h| h.ContentEncoding("identity"))
+        }),
+        "/etag".GET(|| async {Response::OK()
+            .with_text(LARGE.repeat(10))
+            .with_headers(|h| h.ETag("\"abc\""))
+        }),
+    )).test();
+
+    for (accept_encoding, expected) in [
+        ("gzip, deflate, br, zstd", "zstd"),
+        ("br;q=1.0, gzip;q=0.8", "br"),
+        ("gzip", "gzip"),
+        ("deflate, identity;q=0.5", "deflate"),
+    ] {
+        let req = TestRequest::GET("/large").header("Accept-Encoding", accept_encoding);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.header("Content-Encoding"), Some(expected));
+        assert_eq!(res.header("Vary"), Some("Accept-Encoding"));
+        assert!(res.content().unwrap().len() < LARGE.len() * 10);
+        assert_eq!(decompress(expected, res.content().unwrap()), LARGE.repeat(10));
+    }
+
+    {
+        let res = t.oneshot(TestRequest::GET("/large")).await;
+        assert_eq!(res.header("Content-Encoding"), None);
+        assert_eq!(res.header("Vary"), Some("Accept-Encoding"));
+        assert_eq!(res.text(), Some(&*LARGE.repeat(10)));
+    }
+    for path in ["/small", "/png", "/encoded"] {
+        let req = TestRequest::GET(path).header("Accept-Encoding", "gzip");
+        let res = t.oneshot(req).await;
+        assert_ne!(res.header("Content-Encoding"), Some("gzip"));
+        assert_eq!(res.header("Vary"), None);
+    }
+    {
+        let req = TestRequest::GET("/etag").header("Accept-Encoding", "gzip");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.header("Content-Encoding"), Some("gzip"));
+        assert_eq!(res.header("ETag"), Some("W/\"abc\""));
+    }
+}
+
+#[cfg(all(test, feature="sse", feature="gzip", feature="rt_tokio"))]
+#[crate::__rt__::test] async fn test_compress_stream() {
+    use crate::utils::{stream, StreamExt};
+    use std::io::Read;
+
+    let mut res = Response::OK().with_stream(
+        stream::once(Ok::<_, std::io::Error>("Hello")).chain(stream::once(Ok("compression!")))
+    );
+    Compress::new().apply(&mut res, Some(Encoding::Gzip));
+    assert_eq!(res.headers.ContentEncoding(), Some("gzip"));
+
+    let mut bytes = Vec::new();
+    res.send(&mut bytes).await;
+
+    /* concat the chunks */
+    let (_, mut body) = bytes.split_at(bytes.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4);
+    let mut compressed = Vec::new();
+    let mut n_chunks = 0;
+    loop {
+        let (size, rest) = body.split_at(body.windows(2).position(|w| w == b"\r\n").unwrap());
+        let size = usize::from_str_radix(std::str::from_utf8(size).unwrap(), 16).unwrap();
+        if size == 0 {break}
+        compressed.extend_from_slice(&rest[2..2+size]);
+        body = &rest[2+size+2..];
+        n_chunks += 1;
+    }
+    assert!(n_chunks >= 3, "each message and the end should be sent as chunks");
+
+    let mut decompressed = String::new();
+    flate2::read::GzDecoder::new(&*compressed).read_to_string(&mut decompressed).unwrap();
+    assert_eq!(decompressed, "data: Hello\n\ndata: compression!\n\n");
+}
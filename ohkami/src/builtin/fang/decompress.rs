@@ -0,0 +1,228 @@
+use crate::{Fang, FangProc, Request, Response};
+use super::compress::Encoding;
+use std::io::Read;
+
+
+/// # Builtin fang for request body decompression
+///
+/// <br>
+///
+/// Decompress request bodies by their `Content-Encoding` before extraction,
+/// so that `Payload`s and `req.payload()` see the original bytes.
+///
+/// Each encoding is available with its feature as `Compress`.
+/// Stacked encodings like `Content-Encoding: gzip, br` are decoded in reverse order.
+///
+/// <br>
+///
+/// Responds with
+///
+/// - `415 Unsupported Media Type` and `Accept-Encoding` of available ones for unsupported encodings
+/// - `413 Payload Too Large` when the decompressed body exceeds the limit (default: 10 MiB)
+/// - `400 Bad Request` for bodies that fail to be decoded
+///
+/// <br>
+///
+/// ---
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::builtin::fang::Decompress;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     Ohkami::with(Decompress::new().limit(1024 * 1024), (
+///         "/logs".POST(post_logs),
+///     )).howl("localhost:3000").await
+/// }
+///
+/// async fn post_logs(logs: &str) -> Status {
+///     println!("{logs}");
+///     Status::NoContent
+/// }
+/// ```
+/// ---
+#[derive(Clone, Copy)]
+pub struct Decompress {
+    limit: usize,
+}
+impl Decompress {
+    pub fn new() -> Self {
+        Self { limit: 10 * 1024 * 1024 }
+    }
+
+    /// Reject bodies decompressed into more than `bytes` bytes. Default: 10 MiB
+    pub fn limit(mut self, bytes: usize) -> Self {
+        self.limit = bytes;
+        self
+    }
+}
+impl Default for Decompress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const _: () = {
+    impl<Inner: FangProc> Fang<Inner> for Decompress {
+        type Proc = DecompressProc<Inner>;
+        fn chain(&self, inner: Inner) -> Self::Proc {
+            DecompressProc { decompress: *self, inner }
+        }
+    }
+
+    pub struct DecompressProc<Inner: FangProc> {
+        decompress: Decompress,
+        inner:      Inner,
+    }
+    impl<Inner: FangProc> FangProc for DecompressProc<Inner> {
+        async fn bite<'b>(&'b self, req: &'b mut Request) -> Response {
+            if let Some(rejection) = self.decompress.apply(req) {
+                return rejection
+            }
+            self.inner.bite(req).await
+        }
+    }
+};
+
+impl Decompress {
+    /// Decompress the payload of `req` in place, or return the response rejecting it
+    fn apply(&self, req: &mut Request) -> Option<Response> {
+        let (Some(content_encoding), Some(payload)) = (req.headers.ContentEncoding(), &req.payload) else {
+            return None
+        };
+
+        let mut encodings = Vec::new();
+        for coding in content_encoding.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            if coding.eq_ignore_ascii_case("identity") {continue}
+            match Encoding::ALL.iter().find(|e| e.matches(coding)) {
+                Some(encoding) => encodings.push(*encoding),
+                None => return Some(Response::UnsupportedMediaType()
+                    .with_headers(|h| h.AcceptEncoding(accept_encoding()))
+                )
+            }
+        }
+
+        let mut body = payload.to_vec();
+        for encoding in encodings.into_iter().rev() {
+            body = match decode(encoding, &body, self.limit) {
+                Ok(decoded) => decoded,
+                Err(DecodeError::Invalid)  => return Some(Response::BadRequest().with_text("Failed to decode payload")),
+                Err(DecodeError::TooLarge) => return Some(Response::PayloadTooLarge()),
+            };
+        }
+
+        req.headers.set()
+            .ContentEncoding(None)
+            .ContentLength(body.len().to_string());
+        req.payload = Some(body.into());
+        None
+    }
+}
+
+fn accept_encoding() -> String {
+    Encoding::ALL.iter().map(Encoding::as_str).collect::<Vec<_>>().join(", ")
+}
+
+enum DecodeError {
+    Invalid,
+    TooLarge,
+}
+
+fn decode(encoding: Encoding, bytes: &[u8], limit: usize) -> Result<Vec<u8>, DecodeError> {
+    fn read_limited(decoder: impl Read, limit: usize) -> Result<Vec<u8>, DecodeError> {
+        let mut decoded = Vec::new();
+        decoder.take(limit as u64 + 1).read_to_end(&mut decoded)
+            .map_err(|_| DecodeError::Invalid)?;
+        if decoded.len() > limit {
+            return Err(DecodeError::TooLarge)
+        }
+        Ok(decoded)
+    }
+
+    match encoding {
+        #[cfg(feature="zstd")]
+        Encoding::Zstd => read_limited(
+            zstd::stream::read::Decoder::new(bytes).map_err(|_| DecodeError::Invalid)?,
+            limit
+        ),
+        #[cfg(feature="br")]
+        Encoding::Br      => read_limited(brotli::Decompressor::new(bytes, 4096), limit),
+        #[cfg(feature="gzip")]
+        Encoding::Gzip    => read_limited(flate2::read::MultiGzDecoder::new(bytes), limit),
+        #[cfg(feature="deflate")]
+        Encoding::Deflate => read_limited(flate2::read::ZlibDecoder::new(bytes), limit),
+    }
+}
+
+
+#[cfg(all(test, feature="testing", feature="zstd", feature="br", feature="gzip", feature="deflate"))]
+#[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+#[crate::__rt__::test] async fn test_decompress() {
+    use crate::prelude::*;
+    use crate::testing::*;
+    use crate::typed::status;
+    use super::compress::Encoder;
+
+    use crate::typed::Payload;
+    use crate::builtin::payload::JSON;
+    use ::serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Log<'req> {
+        message: &'req str,
+    } impl Payload for Log<'_> {
+        type Type = JSON;
+    }
+
+    async fn post_log(log: Log<'_>) -> status::Created<String> {
+        status::Created(log.message.to_string())
+    }
+
+    let t = Ohkami::with(Decompress::new().limit(1024), (
+        "/log".POST(post_log),
+    )).test();
+
+    const LOG: &str = r#"{"message":"Hello, decompression!"}"#;
+
+    for encoding in Encoding::ALL {
+        let req = TestRequest::POST("/log")
+            .header("Content-Encoding", encoding.as_str())
+            .content("application/json", Encoder::compress(*encoding, LOG.as_bytes()));
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::Created);
+        assert_eq!(res.text(), Some("Hello, decompression!"));
+    }
+
+    {
+        let req = TestRequest::POST("/log")
+            .header("Content-Encoding", "gzip, br")
+            .content("application/json", Encoder::compress(Encoding::Br, &Encoder::compress(Encoding::Gzip, LOG.as_bytes())));
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::Created);
+        assert_eq!(res.text(), Some("Hello, decompression!"));
+    }
+    {
+        let req = TestRequest::POST("/log")
+            .header("Content-Encoding", "compress")
+            .content("application/json", LOG.as_bytes());
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnsupportedMediaType);
+        assert_eq!(res.header("Accept-Encoding"), Some("zstd, br, gzip, deflate"));
+    }
+    {
+        let req = TestRequest::POST("/log")
+            .header("Content-Encoding", "gzip")
+            .content("application/json", &b"not gzip"[..]);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::BadRequest);
+    }
+    {
+        let bomb = format!(r#"{{"message":"{}"}}"#, "a".repeat(1024));
+        let req = TestRequest::POST("/log")
+            .header("Content-Encoding", "gzip")
+            .content("application/json", Encoder::compress(Encoding::Gzip, bomb.as_bytes()));
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::PayloadTooLarge);
+    }
+}
@@ -0,0 +1,172 @@
+use ::hmac::{Hmac, Mac};
+use ::sha2::Sha256;
+use ::chacha20poly1305::{ChaCha20Poly1305, KeyInit, AeadCore, Nonce};
+use ::chacha20poly1305::aead::{Aead, OsRng, Payload};
+use ohkami_lib::base64;
+
+
+/// Secret key for signed and private cookies, shared by
+/// `Ohkami::with_state`.
+///
+/// Keys for signing and encryption are derived from the given secret,
+/// which must be at least 32 bytes.
+///
+/// ---
+///
+/// *key rotation*
+///
+/// ```
+/// use ohkami::cookie::Key;
+///
+/// # let (new_secret, old_secret) = ([1; 32], [0; 32]);
+/// let key = Key::new(new_secret)
+///     .with_previous(old_secret);
+/// ```
+///
+/// Cookies are signed / encrypted by the newest secret,
+/// and ones by the previous secrets are still accepted.
+#[derive(Clone)]
+pub struct Key {
+    /// newest first
+    keys: Vec<DerivedKeys>,
+}
+
+#[derive(Clone)]
+struct DerivedKeys {
+    signing:    [u8; 32],
+    encryption: ChaCha20Poly1305,
+}
+
+const MIN_SECRET_LEN: usize = 32;
+
+/// length of base64url-encoded HMAC-SHA256
+const SIGNATURE_LEN: usize = 43;
+
+/// length of the random nonce of private cookies
+const NONCE_LEN: usize = 12;
+
+impl Key {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self { keys: vec![DerivedKeys::from_secret(secret.as_ref())] }
+    }
+
+    /// Also accept cookies signed / encrypted by `secret`.
+    /// Can be called multiple times.
+    pub fn with_previous(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.keys.push(DerivedKeys::from_secret(secret.as_ref()));
+        self
+    }
+}
+
+impl DerivedKeys {
+    fn from_secret(secret: &[u8]) -> Self {
+        if secret.len() < MIN_SECRET_LEN {
+            panic!("Cookie secret must be at least {MIN_SECRET_LEN} bytes")
+        }
+        let derive = |label: &[u8]| -> [u8; 32] {
+            let mut mac = hmac(secret);
+            mac.update(label);
+            mac.finalize().into_bytes().into()
+        };
+        Self {
+            signing:    derive(b"ohkami cookie signing"),
+            encryption: ChaCha20Poly1305::new(&derive(b"ohkami cookie encryption").into()),
+        }
+    }
+
+    fn signature(&self, name: &str, value: &str) -> Hmac<Sha256> {
+        let mut mac = hmac(&self.signing);
+        mac.update(name.as_bytes());
+        mac.update(b"=");
+        mac.update(value.as_bytes());
+        mac
+    }
+}
+
+fn hmac(key: &[u8]) -> Hmac<Sha256> {
+    <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any size")
+}
+
+/// `base64::decode_url` panics for invalid input, so check it first
+fn decode_base64url(encoded: &str) -> Option<Vec<u8>> {
+    let valid = encoded.len() % 4 != 1 && encoded.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    valid.then(|| base64::decode_url(encoded))
+}
+
+impl Key {
+    /// `{base64url signature}{value}`
+    pub(crate) fn sign(&self, name: &str, value: &str) -> String {
+        let signature = self.keys[0].signature(name, value).finalize().into_bytes();
+        base64::encode_url(signature) + value
+    }
+
+    pub(crate) fn verify<'v>(&self, name: &str, signed: &'v str) -> Option<&'v str> {
+        if signed.len() < SIGNATURE_LEN || !signed.is_char_boundary(SIGNATURE_LEN) {
+            return None
+        }
+        let (signature, value) = signed.split_at(SIGNATURE_LEN);
+        let signature = decode_base64url(signature)?;
+        self.keys.iter()
+            .any(|keys| keys.signature(name, value).verify_slice(&signature).is_ok())
+            .then_some(value)
+    }
+
+    /// `{base64url (nonce)(ciphertext)}` by ChaCha20-Poly1305 with a random nonce,
+    /// authenticating `name` as the associated data
+    pub(crate) fn encrypt(&self, name: &str, value: &str) -> String {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.keys[0].encryption
+            .encrypt(&nonce, Payload { msg: value.as_bytes(), aad: name.as_bytes() })
+            .expect("ChaCha20-Poly1305 fails only for too large plaintext");
+
+        let mut data = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+        base64::encode_url(data)
+    }
+
+    pub(crate) fn decrypt(&self, name: &str, encrypted: &str) -> Option<String> {
+        let data = decode_base64url(encrypted)?;
+        if data.len() < NONCE_LEN {
+            return None
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+
+        self.keys.iter().find_map(|keys| {
+            let plaintext = keys.encryption
+                .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: name.as_bytes() })
+                .ok()?;
+            String::from_utf8(plaintext).ok()
+        })
+    }
+}
+
+#[cfg(test)]
+#[test] fn test_sign_and_encrypt() {
+    let key = Key::new([42; 32]);
+
+    let signed = key.sign("session", "user:1");
+    assert_eq!(key.verify("session", &signed), Some("user:1"));
+    assert_eq!(key.verify("other", &signed), None);
+    assert_eq!(key.verify("session", &signed.replace("user:1", "user:2")), None);
+    assert_eq!(key.verify("session", "user:1"), None);
+    assert_eq!(key.verify("session", "!!"), None);
+
+    let encrypted = key.encrypt("prefs", "theme=dark; lang=ja; and a long long value over a block");
+    assert!(!encrypted.contains("dark"));
+    assert_ne!(key.encrypt("prefs", "theme=dark"), key.encrypt("prefs", "theme=dark"));
+    assert_eq!(key.decrypt("prefs", &encrypted).as_deref(), Some("theme=dark; lang=ja; and a long long value over a block"));
+    assert_eq!(key.decrypt("other", &encrypted), None);
+    assert_eq!(key.decrypt("prefs", "not+base64"), None);
+    assert_eq!(key.decrypt("prefs", "AAAA"), None);
+    {
+        let mut tampered = base64::decode_url(&encrypted);
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(key.decrypt("prefs", &base64::encode_url(tampered)), None);
+    }
+
+    let rotated = Key::new([7; 32]).with_previous([42; 32]);
+    assert_eq!(rotated.verify("session", &signed), Some("user:1"));
+    assert_eq!(rotated.decrypt("prefs", &encrypted).as_deref(), Some("theme=dark; lang=ja; and a long long value over a block"));
+    assert_eq!(key.verify("session", &rotated.sign("session", "user:1")), None);
+}
@@ -0,0 +1,151 @@
+//! Cookies of a request, and signed / private ones
+//!
+//! `CookieJar` reads cookies of the request and collects `Set-Cookie`s
+//! to be applied to a response by `Response::with_cookies` :
+//!
+//! ```
+//! use ohkami::prelude::*;
+//! use ohkami::cookie::{CookieJar, Key};
+//!
+//! async fn login(mut jar: CookieJar<'_>) -> Response {
+//!     jar.add_signed("user", "ohkami", |d| d.Path("/").HttpOnly());
+//!     jar.add_private("prefs", "theme=dark", |d| d.Path("/").HttpOnly());
+//!     Response::OK().with_cookies(jar)
+//! }
+//!
+//! async fn me(jar: CookieJar<'_>) -> Result<String, Response> {
+//!     let user = jar.get_signed("user").ok_or_else(Response::Unauthorized)?;
+//!     let prefs = jar.get_private("prefs").unwrap_or_default();
+//!     Ok(format!("{user} ({prefs})"))
+//! }
+//!
+//! async fn logout(mut jar: CookieJar<'_>) -> Response {
+//!     jar.remove("user", |d| d.Path("/"));
+//!     Response::OK().with_cookies(jar)
+//! }
+//!
+//! # let _ =
+//! Ohkami::new((
+//!     "/login".POST(login),
+//!     "/me".GET(me),
+//!     "/logout".POST(logout),
+//! )).with_state(Key::new(b"a secret of at least 32 bytes!!!"))
+//! # ;
+//! ```
+//!
+//! - **signed** cookie is readable by the client, but can't be modified.
+//!   It's signed by HMAC-SHA256.
+//! - **private** cookie is encrypted and authenticated, so the client
+//!   can neither read nor modify it. It's encrypted by ChaCha20-Poly1305
+//!   with a random nonce.
+//!
+//! Both are bound to the cookie name : a value can't be moved to another cookie.
+//! Signed / private cookies require `Key` set by `Ohkami::with_state` : without it,
+//! `get_signed` / `get_private` find nothing, and `add_signed` / `add_private` panic.
+
+mod key;
+pub use key::Key;
+
+use crate::{Request, FromRequest};
+use crate::header::SetCookieBuilder;
+use std::borrow::Cow;
+
+
+pub struct CookieJar<'req> {
+    cookies: Vec<(&'req str, Cow<'req, str>)>,
+    key:     Option<&'req Key>,
+    pub(crate) setcookies: Vec<String>,
+}
+
+impl<'req> FromRequest<'req> for CookieJar<'req> {
+    type Error = std::convert::Infallible;
+
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        let cookies = req.headers.Cookies()
+            .filter_map(|(name, value)| Some((name, ohkami_lib::percent_decode_utf8(value.as_bytes()).ok()?)))
+            .collect();
+        let key = req.states.as_ref().and_then(|states| states.get::<Key>());
+        Some(Ok(Self { cookies, key, setcookies: Vec::new() }))
+    }
+}
+
+impl<'req> CookieJar<'req> {
+    /// `None` when no `Key` is set
+    fn key(&self) -> Option<&'req Key> {
+        #[cfg(debug_assertions)]
+        if self.key.is_none() {
+            crate::warning!("[CookieJar] Signed or private cookies require `ohkami::cookie::Key` set by `Ohkami::with_state`")
+        }
+        self.key
+    }
+
+    fn required_key(&self) -> &'req Key {
+        self.key.expect("Signed or private cookies require `ohkami::cookie::Key` set by `Ohkami::with_state`")
+    }
+
+    /// Value of the cookie `name` in the request
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.cookies.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| &**v)
+    }
+
+    /// Value of the signed cookie `name` in the request,
+    /// or `None` if missing or its signature is invalid
+    pub fn get_signed(&self, name: &str) -> Option<&str> {
+        let key = self.key()?;
+        self.get(name).and_then(|signed| key.verify(name, signed))
+    }
+
+    /// Value of the private cookie `name` in the request,
+    /// or `None` if missing or it can't be decrypted
+    pub fn get_private(&self, name: &str) -> Option<String> {
+        let key = self.key()?;
+        self.get(name).and_then(|encrypted| key.decrypt(name, encrypted))
+    }
+
+    /// Add `Set-Cookie` of the cookie. See `SetHeaders::SetCookie` for details.
+    pub fn add(&mut self,
+        name:  &'static str,
+        value: impl Into<Cow<'static, str>>,
+        directives: impl FnOnce(SetCookieBuilder)->SetCookieBuilder
+    ) {
+        self.setcookies.push(directives(SetCookieBuilder::new(name, value)).build());
+    }
+
+    /// Add `Set-Cookie` of the cookie signed by the `Key`
+    /// 
+    /// *panics* if no `Key` is set
+    pub fn add_signed(&mut self,
+        name:  &'static str,
+        value: impl AsRef<str>,
+        directives: impl FnOnce(SetCookieBuilder)->SetCookieBuilder
+    ) {
+        let signed = self.required_key().sign(name, value.as_ref());
+        self.add(name, signed, directives)
+    }
+
+    /// Add `Set-Cookie` of the cookie encrypted by the `Key`
+    /// 
+    /// *panics* if no `Key` is set
+    pub fn add_private(&mut self,
+        name:  &'static str,
+        value: impl AsRef<str>,
+        directives: impl FnOnce(SetCookieBuilder)->SetCookieBuilder
+    ) {
+        let encrypted = self.required_key().encrypt(name, value.as_ref());
+        self.add(name, encrypted, directives)
+    }
+
+    /// Add `Set-Cookie` removing the cookie. `directives` should have
+    /// the same `Path` and `Domain` as the cookie was set with.
+    pub fn remove(&mut self,
+        name: &'static str,
+        directives: impl FnOnce(SetCookieBuilder)->SetCookieBuilder
+    ) {
+        self.add(name, "", |d| directives(d)
+            .Expires("Thu, 01 Jan 1970 00:00:00 GMT")
+            .MaxAge(0)
+        )
+    }
+}
@@ -1,8 +1,14 @@
 use std::{future::Future, pin::Pin};
 use super::Handler;
-use crate::{Response, FromRequest, FromParam, Request, IntoResponse};
+use crate::fangs::BoxedFPC;
+use crate::{Response, FromRequest, AsyncFromRequest, FromParam, Request, IntoResponse, Rejection};
 
 
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a valid handler",
+    label   = "not a handler",
+    note    = "a handler is an async function taking path param(s) (`FromParam` type, or a tuple of 1 or 2 of them) and/or up to 16 `FromRequest` or `AsyncFromRequest` items, and returning an `IntoResponse` type",
+)]
 pub trait IntoHandler<T> {
     fn into_handler(self) -> Handler;
 }
@@ -14,12 +20,36 @@ pub trait IntoHandler<T> {
 /* FIXME: omit unsafe... */
 #[inline(always)] fn from_request<'fr, 'req, R: FromRequest<'fr>>(
     req: &'req Request
-) -> Result<R, Response> {
+) -> Result<R, Rejection> where R::Error: 'static {
     <R as FromRequest>::from_request(unsafe {
         std::mem::transmute::<&'req _, &'fr _>(req)
     })
-        .ok_or_else(|| Response::BadRequest().with_text("missing something expected in request"))?
-        .map_err(IntoResponse::into_response)
+        .ok_or_else(Rejection::missing)
+        .and_then(|result| result.map_err(Rejection::__from))
+        .map_err(Rejection::__of::<R>)
+}
+
+/// Parse the `$index`-th param of the route as `$P`, or return the rejection
+macro_rules! from_param {
+    ($req:ident, $index:literal, $P:ty, $raw_param:expr) => {{
+        let raw_param: &[u8] = $raw_param;
+        let param = match ohkami_lib::percent_decode_utf8(raw_param) {
+            Ok(param) => <$P as FromParam>::from_param(param).map_err(Rejection::__from),
+            Err(e)    => Err(undecodable(raw_param, e)),
+        };
+        match param {
+            Ok(param) => param,
+            Err(e) => return __error__($req.reject(e.of_param($req.param_name($index)).__of::<$P>())),
+        }
+    }};
+}
+
+#[cold] #[inline(never)] fn undecodable(_raw_param: &[u8], e: std::str::Utf8Error) -> Rejection {
+    #[cfg(debug_assertions)] crate::warning!(
+        "Failed to decode percent encoded param `{}`: {e}",
+        _raw_param.escape_ascii()
+    );
+    Rejection::custom(e.to_string(), Response::InternalServerError())
 }
 
 
@@ -42,47 +72,39 @@ const _: (/* no args */) = {
 };
 
 const _: (/* FromParam */) = {
-    impl<'req, F, Fut, Body, P1:FromParam<'req>> IntoHandler<fn((P1,))->Body> for F
+    impl<'req, F, Fut, Body, P1:FromParam<'req, Error: 'static>> IntoHandler<fn((P1,))->Body> for F
     where
         F:    Fn(P1) -> Fut + Send + Sync + 'static,
         Body: IntoResponse,
         Fut:  Future<Output = Body> + Send + 'static,
     {
         fn into_handler(self) -> Handler {
-            Handler::new(move |req|
-                match P1::from_raw_param(unsafe {req.path.assume_one_param()}) {
-                    Ok(p1) => {
-                        let res = self(p1);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    Err(e) => __error__(e)
-                }
-            )
+            Handler::new(move |req| {
+                let p1 = from_param!(req, 0, P1, unsafe {req.path.assume_one_param()});
+                let res = self(p1);
+                Box::pin(async move {res.await.into_response()})
+            })
         }
     }
 
-    impl<'req, F, Body, Fut, P1:FromParam<'req>> IntoHandler<fn(((P1,),))->Body> for F
+    impl<'req, F, Body, Fut, P1:FromParam<'req, Error: 'static>> IntoHandler<fn(((P1,),))->Body> for F
     where
         F:    Fn((P1,)) -> Fut + Send + Sync + 'static,
         Body: IntoResponse,
         Fut:  Future<Output = Body> + Send + 'static,
     {
         fn into_handler(self) -> Handler {
-            Handler::new(move |req|
+            Handler::new(move |req| {
                 // SAFETY: Due to the architecture of `Router`,
                 // `params` has already `append`ed once before this code
-                match P1::from_raw_param(unsafe {req.path.assume_one_param()}) {
-                    Ok(p1) => {
-                        let res = self((p1,));
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    Err(e) => __error__(e)
-                }
-            )
+                let p1 = from_param!(req, 0, P1, unsafe {req.path.assume_one_param()});
+                let res = self((p1,));
+                Box::pin(async move {res.await.into_response()})
+            })
         }
     }
 
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>> IntoHandler<fn(((P1, P2),))->Body> for F
+    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req, Error: 'static>, P2:FromParam<'req, Error: 'static>> IntoHandler<fn(((P1, P2),))->Body> for F
     where
         F:   Fn((P1, P2)) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Body> + Send + 'static,
@@ -90,402 +112,139 @@ const _: (/* FromParam */) = {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
                 let (p1, p2) = unsafe {req.path.assume_two_params()};
-                match (P1::from_raw_param(p1), P2::from_raw_param(p2)) {
-                    (Ok(p1), Ok(p2)) => {
-                        let res = self((p1, p2));
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e), _) | (_, Err(e)) => __error__(e),
-                }
+                let (p1, p2) = (from_param!(req, 0, P1, p1), from_param!(req, 1, P2, p2));
+                let res = self((p1, p2));
+                Box::pin(async move {res.await.into_response()})
             })
         }
     }
 };
 
-const _: (/* FromRequest items */) = {
-    impl<'req, F, Fut, Body:IntoResponse, Item1:FromRequest<'req>> IntoHandler<fn(Item1)->Body> for F
-    where
-        F:   Fn(Item1) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req|
-                match from_request::<Item1>(req) {
-                    Ok(item1) => {
-                        let res = self(item1);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    Err(e) => __error__(e)
-                }
-            )
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, Item1:FromRequest<'req>, Item2:FromRequest<'req>> IntoHandler<fn(Item1, Item2)->Body> for F
-    where
-        F:   Fn(Item1, Item2) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req|
-                match (from_request::<Item1>(req), from_request::<Item2>(req)) {
-                    (Ok(item1), Ok(item2)) => {
-                        let res = self(item1, item2);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e), _) |
-                    (_, Err(e)) => __error__(e),
-                }
-            )
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>> IntoHandler<fn(Item1, Item2, Item3)->Body> for F
-    where
-        F:   Fn(Item1, Item2, Item3) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req|
-                match (from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
-                    (Ok(item1), Ok(item2), Ok(item3)) => {
-                        let res = self(item1, item2, item3);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e), _, _) |
-                    (_, Err(e), _) |
-                    (_, _, Err(e)) => __error__(e),
-                }
-            )
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>, Item4:FromRequest<'req>> IntoHandler<fn(Item1, Item2, Item3, Item4)->Body> for F
-    where
-        F:   Fn(Item1, Item2, Item3, Item4) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req|
-                match (from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
-                    (Ok(item1), Ok(item2), Ok(item3), Ok(item4)) => {
-                        let res = self(item1, item2, item3, item4);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e), _, _,_) |
-                    (_, Err(e), _,_) |
-                    (_, _, Err(e),_) |
-                    (_,_, _, Err(e)) => __error__(e),
-                }
-            )
-        }
-    }
-};
-
-const _: (/* one FromParam without tuple and FromRequest items */) = {
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>> IntoHandler<fn(((P1,),), Item1)->Body> for F
-    where
-        F:   Fn(P1, Item1) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request(req)) {
-                    (Ok(p1), Ok(item1)) => {
-                        let res = self(p1, item1);
-                        Box::pin(async move {res.await.into_response()})
-                    },
-                    (Err(e), _) |
-                    (_, Err(e)) => __error__(e),
-                }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>> IntoHandler<fn(((P1,),), Item1, Item2)->Body> for F
-    where
-        F:   Fn(P1, Item1, Item2) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req)) {
-                    (Ok(p1), Ok(item1), Ok(item2)) => {
-                        let res = self(p1, item1, item2);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_) |
-                    (_,Err(e),_) |
-                    (_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>> IntoHandler<fn(((P1,),), Item1, Item2, Item3)->Body> for F
-    where
-        F:   Fn(P1, Item1, Item2, Item3) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
-                    (Ok(p1), Ok(item1), Ok(item2), Ok(item3)) => {
-                        let res = self(p1, item1, item2, item3);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_) |
-                    (_,Err(e),_,_) |
-                    (_,_,Err(e),_) |
-                    (_,_,_,Err(e)) => __error__(e),
-                }
-            })
+/// `FromRequest` or `AsyncFromRequest`, distinguished by the marker `M`
+#[doc(hidden)]
+pub trait Extract<M>: Sized + Send {
+    fn extract(req: &'static Request) -> impl Future<Output = Result<Self, Rejection>> + Send;
+}
+#[doc(hidden)] pub enum SyncItem {}
+#[doc(hidden)] pub enum AsyncItem {}
+const _: () = {
+    impl<FR: FromRequest<'static, Error: 'static> + Send> Extract<SyncItem> for FR {
+        #[inline(always)]
+        fn extract(req: &'static Request) -> impl Future<Output = Result<Self, Rejection>> + Send {
+            /* no async operation for sync extractors */
+            std::future::ready(from_request::<FR>(req))
         }
     }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>, Item4:FromRequest<'req>> IntoHandler<fn(((P1,),), Item1, Item2, Item3, Item4)->Body> for F
-    where
-        F:   Fn(P1, Item1, Item2, Item3, Item4) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
-                    (Ok(p1), Ok(item1), Ok(item2), Ok(item3), Ok(item4)) => {
-                        let res = self(p1, item1, item2, item3, item4);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_,_) |
-                    (_,Err(e),_,_,_) |
-                    (_,_,Err(e),_,_) |
-                    (_,_,_,Err(e),_) |
-                    (_,_,_,_,Err(e)) => __error__(e),
-                }
-            })
+    impl<AFR: AsyncFromRequest<'static, Error: 'static> + Send> Extract<AsyncItem> for AFR {
+        #[inline(always)]
+        async fn extract(req: &'static Request) -> Result<Self, Rejection> {
+            AFR::from_request(req).await
+                .ok_or_else(Rejection::missing)
+                .and_then(|result| result.map_err(Rejection::__from))
+                .map_err(Rejection::__of::<AFR>)
         }
     }
 };
 
-const _: (/* one FromParam and FromRequest items */) = {
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>> IntoHandler<fn((P1,), Item1)->Body> for F
-    where
-        F:   Fn((P1,), Item1) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req)) {
-                    (Ok(p1), Ok(item1)) => {
-                        let res = self((p1,), item1);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_) |
-                    (_,Err(e)) => __error__(e),
-                }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>> IntoHandler<fn((P1,), Item1, Item2)->Body> for F
-    where
-        F:   Fn((P1,), Item1, Item2) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req)) {
-                    (Ok(p1), Ok(item1), Ok(item2)) => {
-                        let res = self((p1,), item1, item2);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_) |
-                    (_,Err(e),_) |
-                    (_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>> IntoHandler<fn((P1,), Item1, Item2, Item3)->Body> for F
-    where
-        F:   Fn((P1,), Item1, Item2, Item3) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-                
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
-                    (Ok(p1), Ok(item1), Ok(item2), Ok(item3)) => {
-                        let res = self((p1,), item1, item2, item3);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_) |
-                    (_,Err(e),_,_) |
-                    (_,_,Err(e),_) |
-                    (_,_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
+/// Handler proc calling `f` by `call`, whose future can borrow `f` while extracting items
+struct Extracting<F> {
+    f:    F,
+    call: ExtractingCall<F>,
+}
+type ExtractingCall<F> = for<'b> fn(&'b F, &'b mut Request) -> Pin<Box<dyn Future<Output = Response> + Send + 'b>>;
+impl<F: Send + Sync + 'static> crate::FangProc for Extracting<F> {
+    #[inline(always)]
+    fn bite<'b>(&'b self, req: &'b mut Request) -> impl Future<Output = Response> + Send {
+        (self.call)(&self.f, req)
     }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>, Item4:FromRequest<'req>> IntoHandler<fn((P1,), Item1, Item2, Item3, Item4)->Body> for F
-    where
-        F:   Fn((P1,), Item1, Item2, Item3, Item4) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-                
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
-                    (Ok(p1), Ok(item1), Ok(item2), Ok(item3), Ok(item4)) => {
-                        let res = self((p1,), item1, item2, item3, item4);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_,_) |
-                    (_,Err(e),_,_,_) |
-                    (_,_,Err(e),_,_) |
-                    (_,_,_,Err(e),_) |
-                    (_,_,_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
+    #[inline(always)]
+    fn bite_boxed<'b>(&'b self, req: &'b mut Request) -> Pin<Box<dyn Future<Output = Response> + Send + 'b>> {
+        (self.call)(&self.f, req)
     }
-};
-
-const _: (/* two PathParams and FromRequest items */) = {
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
+}
 
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1)) => {
-                        let res = self((p1, p2), item1); 
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_) |
-                    (_,Err(e),_) |
-                    (_,_,Err(e)) => __error__(e),
+/// Implement `IntoHandler` for handlers taking `FromRequest` or `AsyncFromRequest`
+/// items, with no path param, one without tuple, one in tuple, or two params
+macro_rules! IntoHandler {
+    ($( $item:ident $marker:ident $var:ident ),+) => {
+        IntoHandler! { @params [] [] [] [] $( $item $marker $var ),+ }
+        IntoHandler! { @params [P1 p1] [((P1,),),] [P1,] [p1,] $( $item $marker $var ),+ }
+        IntoHandler! { @params [P1 p1] [(P1,),] [(P1,),] [(p1,),] $( $item $marker $var ),+ }
+        IntoHandler! { @params [P1 p1 P2 p2] [(P1, P2),] [(P1, P2),] [(p1, p2),] $( $item $marker $var ),+ }
+    };
+    (@params [$( $param:ident $param_var:ident )*] [$( $params_marker:tt )*] [$( $params_type:tt )*] [$( $params_arg:tt )*] $( $item:ident $marker:ident $var:ident ),+) => {
+        impl<F, Fut, Body, $( $param, )* $( $item, $marker ),+> IntoHandler<(fn($( $params_marker )* $( $item ),+)->Body, $( $marker ),+)> for F
+        where
+            F:    Fn($( $params_type )* $( $item ),+) -> Fut + Send + Sync + 'static,
+            Fut:  Future<Output = Body> + Send + 'static,
+            Body: IntoResponse + 'static,
+            $( $param: FromParam<'static, Error: 'static> + Send + 'static, )*
+            $( $item: Extract<$marker> + Send + 'static, $marker: 'static, )+
+        {
+            fn into_handler(self) -> Handler {
+                #[allow(non_snake_case)]
+                fn call<'b, F, Fut, Body, $( $param, )* $( $item, $marker ),+>(
+                    f:   &'b F,
+                    req: &'b mut Request,
+                ) -> Pin<Box<dyn Future<Output = Response> + Send + 'b>>
+                where
+                    F:    Fn($( $params_type )* $( $item ),+) -> Fut + Send + Sync + 'static,
+                    Fut:  Future<Output = Body> + Send + 'static,
+                    Body: IntoResponse + 'static,
+                    $( $param: FromParam<'static, Error: 'static> + Send + 'static, )*
+                    $( $item: Extract<$marker> + Send + 'static, $marker: 'static, )+
+                {
+                    IntoHandler! { @extract_params req [$( $param $param_var )*] }
+
+                    /* FIXME: omit unsafe... */
+                    let req = unsafe {std::mem::transmute::<&'b Request, &'static Request>(&*req)};
+                    Box::pin(async move {
+                        $(
+                            let $var = match <$item as Extract<$marker>>::extract(req).await {
+                                Ok(item) => item,
+                                Err(e)   => return req.reject(e),
+                            };
+                        )+
+                        f($( $params_arg )* $( $var ),+).await.into_response()
+                    })
                 }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1, Item2)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1, Item2) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
 
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req), from_request::<Item2>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1), Ok(item2)) => {
-                        let res = self((p1, p2), item1, item2);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_) |
-                    (_,Err(e),_,_) |
-                    (_,_,Err(e),_) |
-                    (_,_,_,Err(e)) => __error__(e),
-                }
-            })
+                Handler(BoxedFPC::from_proc(Extracting { f: self, call: call::<F, Fut, Body, $( $param, )* $( $item, $marker ),+> }))
+            }
         }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1, Item2, Item3)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1, Item2, Item3) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
-
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1), Ok(item2), Ok(item3)) => {
-                        let res = self((p1, p2), item1, item2, item3);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_,_) |
-                    (_,Err(e),_,_,_) |
-                    (_,_,Err(e),_,_) |
-                    (_,_,_,Err(e),_) |
-                    (_,_,_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>, Item4:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1, Item2, Item3, Item4)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1, Item2, Item3, Item4) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = Body> + Send + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
+    };
+    (@extract_params $req:ident []) => {};
+    (@extract_params $req:ident [$P1:ident $p1:ident]) => {
+        // SAFETY: Due to the architecture of `Router`,
+        // `params` has already `append`ed once before this code
+        let $p1 = from_param!($req, 0, $P1, unsafe {$req.path.assume_one_param()});
+    };
+    (@extract_params $req:ident [$P1:ident $p1:ident $P2:ident $p2:ident]) => {
+        // SAFETY: Due to the architecture of `Router`,
+        // `params` has already `append`ed twice before this code
+        let ($p1, $p2) = unsafe {$req.path.assume_two_params()};
+        let ($p1, $p2) = (from_param!($req, 0, $P1, $p1), from_param!($req, 1, $P2, $p2));
+    };
+}
 
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1), Ok(item2), Ok(item3), Ok(item4)) => {
-                        let res = self((p1, p2), item1, item2, item3, item4);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_,_,_) |
-                    (_,Err(e),_,_,_,_) |
-                    (_,_,Err(e),_,_,_) |
-                    (_,_,_,Err(e),_,_) |
-                    (_,_,_,_,Err(e),_) |
-                    (_,_,_,_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
-    }
-};
+/// `IntoHandler!` for each of 1, 2, ..., N items
+macro_rules! IntoHandlers {
+    ($( $item:ident $marker:ident $var:ident ),+) => {
+        IntoHandlers! { @[] $( $item $marker $var ),+ }
+    };
+    (@[$( $done:ident $done_marker:ident $done_var:ident ),*] $item:ident $marker:ident $var:ident $(, $rest:ident $rest_marker:ident $rest_var:ident )*) => {
+        IntoHandler! { $( $done $done_marker $done_var, )* $item $marker $var }
+        IntoHandlers! { @[$( $done $done_marker $done_var, )* $item $marker $var] $( $rest $rest_marker $rest_var ),* }
+    };
+    (@[$( $done:ident $done_marker:ident $done_var:ident ),*]) => {};
+}
 
+IntoHandlers! {
+    Item1 M1 item1, Item2 M2 item2, Item3 M3 item3, Item4 M4 item4,
+    Item5 M5 item5, Item6 M6 item6, Item7 M7 item7, Item8 M8 item8,
+    Item9 M9 item9, Item10 M10 item10, Item11 M11 item11, Item12 M12 item12,
+    Item13 M13 item13, Item14 M14 item14, Item15 M15 item15, Item16 M16 item16
+}
 
 
 #[cfg(test)] #[test] fn handler_args() {
@@ -503,9 +262,32 @@ const _: (/* two PathParams and FromRequest items */) = {
     }
     async fn h3(_param: P) -> String {format!("")}
 
+    struct I;
+    impl<'req> FromRequest<'req> for I {
+        type Error = std::convert::Infallible;
+        fn from_request(_req: &'req Request) -> Option<Result<Self, Self::Error>> {
+            Some(Ok(Self))
+        }
+    }
+    async fn h4(_: &Request, _: I, _: Option<I>, _: I) -> &'static str {""}
+    async fn h5(_: String, _: &Request, _: I) -> &'static str {""}
+    async fn h6((_,): (P,), _: I) -> &'static str {""}
+    async fn h7((_, _): (String, u64), _: I, _: I) -> &'static str {""}
+    #[allow(clippy::too_many_arguments)]
+    async fn h8(
+        _: I, _: I, _: I, _: I, _: I, _: I, _: I, _: I,
+        _: I, _: I, _: I, _: I, _: I, _: I, _: I, _: &Request,
+    ) -> &'static str {""}
+    #[allow(clippy::too_many_arguments)]
+    async fn h9(
+        (_, _): (P, P),
+        _: I, _: I, _: I, _: I, _: I, _: I, _: I, _: I,
+        _: I, _: I, _: I, _: I, _: I, _: I, _: I, _: I,
+    ) -> &'static str {""}
+
     macro_rules! assert_handlers {
         ( $($function:ident)* ) => {
             $( let _ = $function.into_handler(); )*
         };
-    } assert_handlers! { h0 h1 h2 h3 }
+    } assert_handlers! { h0 h1 h2 h3 h4 h5 h6 h7 h8 h9 }
 }
@@ -1,7 +1,7 @@
 mod into_handler;
 pub(crate) use into_handler::IntoHandler;
 
-use super::{FangProcCaller, BoxedFPC};
+use super::{FangProcCaller, BoxedFPC, Fangs};
 use crate::{Request, Response};
 use std::{pin::Pin, future::Future};
 
@@ -46,17 +46,47 @@ impl Handler {
 
         Self(BoxedFPC::from_proc(HandlerProc(proc)))
     }
+
+    /// Wrap this handler by `fangs`, as the most inner ones
+    pub(crate) fn with_fangs(self, fangs: &dyn Fangs) -> Self {
+        Self(fangs.build(self.0))
+    }
+}
+
+/// Response of `Handler::default_not_found` instead of `404 Not Found`,
+/// memorized in a request to be returned through the fangs of the catcher
+/// (a redirect by `PathPolicy`, the response to a panic, ...)
+pub(crate) struct CatchWith(std::sync::Mutex<Option<Response>>);
+impl CatchWith {
+    pub(crate) fn new(res: Response) -> Self {
+        Self(std::sync::Mutex::new(Some(res)))
+    }
+
+    pub(crate) fn take(&self) -> Option<Response> {
+        self.0.lock().ok()?.take()
+    }
 }
 
 impl Handler {
     pub(crate) fn default_not_found() -> Self {        
+        Handler({
+            static H: std::sync::OnceLock<Handler> = std::sync::OnceLock::new();
+            H.get_or_init(|| Handler::new(|req| Box::pin(async move {
+                req.memorized::<CatchWith>()
+                    .and_then(CatchWith::take)
+                    .unwrap_or_else(Response::NotFound)
+            }))).0.clone()
+        })
+    }
+
+    pub(crate) fn default_method_not_allowed() -> Self {
         Handler({
             static H: std::sync::OnceLock<Handler> = std::sync::OnceLock::new();
             H.get_or_init(|| {
-                async fn not_found() -> Response {
-                    Response::NotFound()
+                async fn method_not_allowed() -> Response {
+                    Response::MethodNotAllowed()
                 }
-                not_found.into_handler()
+                method_not_allowed.into_handler()
             }).0.clone()
         })
     }
@@ -5,19 +5,116 @@ use super::{Fang, BoxedFPC};
 
 #[allow(private_interfaces)]
 pub trait Fangs {
+    /// Number of fangs in this
+    fn count(&self) -> usize;
+
     // returning box for object-safety
     fn build(&self, inner: BoxedFPC) -> BoxedFPC;
 }
 
 impl<F: Fang<BoxedFPC>> Fangs for F {
+    fn count(&self) -> usize {1}
+
     fn build(&self, inner: BoxedFPC) -> BoxedFPC {
         BoxedFPC::from_proc(self.chain(inner))
     }
 }
 
+
+/// Object-safe `Fang`, to be collected in `Vec<Box<dyn DynFang>>` or `FangStack`.
+/// 
+/// This is automatically implemented for `Fang`s including `FangAction`s.
+#[allow(private_interfaces)]
+pub trait DynFang: Send + Sync + 'static {
+    #[doc(hidden)]
+    fn chain_dyn(&self, inner: BoxedFPC) -> BoxedFPC;
+}
+impl<F: Fang<BoxedFPC> + Send + Sync + 'static> DynFang for F {
+    #[inline]
+    fn chain_dyn(&self, inner: BoxedFPC) -> BoxedFPC {
+        BoxedFPC::from_proc(self.chain(inner))
+    }
+}
+
+/// Fangs built at runtime, like from configuration :
+/// 
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::FangStack;
+/// 
+/// # #[derive(Clone)] struct Logger;
+/// # impl FangAction for Logger {}
+/// # #[derive(Clone)] struct Compression;
+/// # impl FangAction for Compression {}
+/// # let enable_compression = true;
+/// let mut fangs = FangStack::new();
+/// fangs.push(Logger);
+/// if enable_compression {
+///     fangs.push(Compression);
+/// }
+/// 
+/// # let _ =
+/// Ohkami::with(fangs, (
+///     "/".GET(|| async {"Hello!"}),
+/// ))
+/// # ;
+/// ```
+/// 
+/// Like tuple fangs, the first pushed one is the most outer.
+/// `Vec<Box<dyn DynFang>>` works in the same way.
+#[derive(Default)]
+pub struct FangStack(Vec<Box<dyn DynFang>>);
+impl FangStack {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Push `fang` inside the ones pushed before
+    pub fn push(&mut self, fang: impl DynFang) -> &mut Self {
+        self.0.push(Box::new(fang));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+const _: () = {
+    impl From<Vec<Box<dyn DynFang>>> for FangStack {
+        fn from(fangs: Vec<Box<dyn DynFang>>) -> Self {
+            Self(fangs)
+        }
+    }
+
+    impl Fangs for FangStack {
+        fn count(&self) -> usize {
+            self.0.count()
+        }
+
+        fn build(&self, inner: BoxedFPC) -> BoxedFPC {
+            self.0.build(inner)
+        }
+    }
+
+    impl Fangs for Vec<Box<dyn DynFang>> {
+        fn count(&self) -> usize {
+            self.len()
+        }
+
+        fn build(&self, inner: BoxedFPC) -> BoxedFPC {
+            self.iter().rev().fold(inner, |proc, fang| fang.chain_dyn(proc))
+        }
+    }
+};
+
 #[allow(private_interfaces)]
 const _: (/* tuple fangs */) = {
     impl Fangs for () {
+        fn count(&self) -> usize {0}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             inner
         }
@@ -28,6 +125,8 @@ const _: (/* tuple fangs */) = {
     > Fangs for (F1,)
     where
     {
+        fn count(&self) -> usize {1}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1,) = self;
             BoxedFPC::from_proc(
@@ -40,6 +139,8 @@ const _: (/* tuple fangs */) = {
         F1: Fang<F2::Proc>,
         F2: Fang<BoxedFPC>,
     > Fangs for (F1, F2) {
+        fn count(&self) -> usize {2}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1, f2) = self;
             BoxedFPC::from_proc(
@@ -55,6 +156,8 @@ const _: (/* tuple fangs */) = {
         F2: Fang<F3::Proc>,
         F3: Fang<BoxedFPC>,
     > Fangs for (F1, F2, F3) {
+        fn count(&self) -> usize {3}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1, f2, f3) = self;
             BoxedFPC::from_proc(
@@ -73,6 +176,8 @@ const _: (/* tuple fangs */) = {
         F3: Fang<F4::Proc>,
         F4: Fang<BoxedFPC>,
     > Fangs for (F1, F2, F3, F4) {
+        fn count(&self) -> usize {4}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1, f2, f3, f4) = self;
             BoxedFPC::from_proc(
@@ -94,6 +199,8 @@ const _: (/* tuple fangs */) = {
         F4: Fang<F5::Proc>,
         F5: Fang<BoxedFPC>,
     > Fangs for (F1, F2, F3, F4, F5) {
+        fn count(&self) -> usize {5}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1, f2, f3, f4, f5) = self;
             BoxedFPC::from_proc(
@@ -118,6 +225,8 @@ const _: (/* tuple fangs */) = {
         F5: Fang<F6::Proc>,
         F6: Fang<BoxedFPC>,
     > Fangs for (F1, F2, F3, F4, F5, F6) {
+        fn count(&self) -> usize {6}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1, f2, f3, f4, f5, f6) = self;
             BoxedFPC::from_proc(
@@ -145,6 +254,8 @@ const _: (/* tuple fangs */) = {
         F6: Fang<F7::Proc>,
         F7: Fang<BoxedFPC>,
     > Fangs for (F1, F2, F3, F4, F5, F6, F7) {
+        fn count(&self) -> usize {7}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1, f2, f3, f4, f5, f6, f7) = self;
             BoxedFPC::from_proc(
@@ -175,6 +286,8 @@ const _: (/* tuple fangs */) = {
         F7: Fang<F8::Proc>,
         F8: Fang<BoxedFPC>,
     > Fangs for (F1, F2, F3, F4, F5, F6, F7, F8) {
+        fn count(&self) -> usize {8}
+
         fn build(&self, inner: BoxedFPC) -> BoxedFPC {
             let (f1, f2, f3, f4, f5, f6, f7, f8) = self;
             BoxedFPC::from_proc(
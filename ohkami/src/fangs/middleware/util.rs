@@ -1,5 +1,6 @@
-use super::super::{Fang, FangProc};
+use super::super::{Fang, FangProc, BoxedFPC};
 use crate::{Request, Response};
+use std::sync::Arc;
 
 
 /// # Fang action - utility wrapper of `Fang`
@@ -110,6 +111,194 @@ pub trait FangAction: Clone + Send + Sync + 'static {
 
 
 
+/// Combinators of `Fang`s applied to some of the requests :
+/// 
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::utils::FangExt;
+/// 
+/// # #[derive(Clone)] struct Logger;
+/// # impl FangAction for Logger {}
+/// # #[derive(Clone)] struct Auth;
+/// # impl FangAction for Auth {}
+/// # async fn healthz() -> &'static str {"OK"}
+/// # async fn metrics() -> &'static str {""}
+/// # async fn users() -> &'static str {""}
+/// # let _ =
+/// Ohkami::with((
+///     Logger.except_paths(["/healthz", "/metrics"]),
+///     Auth.when(|req| req.method != Method::GET),
+/// ), (
+///     "/healthz".GET(healthz),
+///     "/metrics".GET(metrics),
+///     "/users".GET(users).POST(users),
+/// ))
+/// # ;
+/// ```
+/// 
+/// The condition is checked per request, and when not satisfied,
+/// the request is passed to the inner fangs or the handler as if
+/// there's no such fang.
+/// 
+/// This is implemented for the `Fang`s (including `FangAction`s) that can be
+/// given to `Ohkami::with`, not for any other types :
+/// 
+/// ```compile_fail
+/// use ohkami::utils::FangExt;
+/// 
+/// let _ = String::from("not a fang").when(|_| true);
+/// ```
+pub trait FangExt: Sized {
+    /// Apply this fang only to the requests satisfying `condition`
+    fn when<C: Fn(&Request) -> bool + Send + Sync + 'static>(self, condition: C) -> When<Self, C> {
+        When { fang: self, condition: Arc::new(condition) }
+    }
+
+    /// Apply this fang to the requests except for the ones to `paths`.
+    /// A path ending with `/*` excludes the ones under it too, like
+    /// `/static/*` for `/static` and `/static/style.css`.
+    fn except_paths<const N: usize>(self, paths: [&'static str; N]) -> When<Self, ExceptPaths> {
+        When { fang: self, condition: Arc::new(ExceptPaths(paths.into())) }
+    }
+}
+impl<F: Fang<BoxedFPC>> FangExt for F {}
+
+/// Condition of `When`, a closure `Fn(&Request) -> bool` or `ExceptPaths`
+pub trait Condition: Send + Sync + 'static {
+    fn check(&self, req: &Request) -> bool;
+}
+const _: () = {
+    impl<F: Fn(&Request) -> bool + Send + Sync + 'static> Condition for F {
+        #[inline]
+        fn check(&self, req: &Request) -> bool {
+            self(req)
+        }
+    }
+};
+
+/// `Condition` of `FangExt::except_paths`
+pub struct ExceptPaths(Box<[&'static str]>);
+const _: () = {
+    impl Condition for ExceptPaths {
+        fn check(&self, req: &Request) -> bool {
+            let path: &str = &req.path;
+            !self.0.iter().any(|p| match p.strip_suffix("/*") {
+                None         => *p == path,
+                Some(prefix) => path.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+            })
+        }
+    }
+};
+
+/// Fang built by `FangExt::when` or `FangExt::except_paths`
+pub struct When<F, C> {
+    fang:      F,
+    condition: Arc<C>,
+}
+const _: () = {
+    impl<F, C, I> Fang<I> for When<F, C>
+    where
+        F: Fang<Shared<I>>,
+        C: Condition,
+        I: FangProc,
+    {
+        type Proc = WhenProc<F::Proc, I, C>;
+        fn chain(&self, inner: I) -> Self::Proc {
+            let inner = Shared(Arc::new(inner));
+            WhenProc {
+                proc:      self.fang.chain(inner.clone()),
+                inner,
+                condition: self.condition.clone(),
+            }
+        }
+    }
+
+    pub struct WhenProc<P, I, C> {
+        proc:      P,
+        inner:     Shared<I>,
+        condition: Arc<C>,
+    }
+    impl<P: FangProc, I: FangProc, C: Condition> FangProc for WhenProc<P, I, C> {
+        #[inline]
+        async fn bite<'b>(&'b self, req: &'b mut Request) -> Response {
+            if self.condition.check(req) {
+                self.proc.bite(req).await
+            } else {
+                self.inner.bite(req).await
+            }
+        }
+    }
+};
+
+/// Inner `FangProc` shared by both branches of `When`
+pub struct Shared<I>(Arc<I>);
+const _: () = {
+    impl<I> Clone for Shared<I> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+
+    impl<I: FangProc> FangProc for Shared<I> {
+        #[inline(always)]
+        fn bite<'b>(&'b self, req: &'b mut Request) -> impl std::future::Future<Output = Response> + Send {
+            self.0.bite(req)
+        }
+    }
+};
+
+/// One of 2 fangs chosen when building, like by configuration :
+/// 
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::utils::Either;
+/// 
+/// # #[derive(Clone)] struct VerboseLogger;
+/// # impl FangAction for VerboseLogger {}
+/// # #[derive(Clone)] struct Logger;
+/// # impl FangAction for Logger {}
+/// let logger = if cfg!(debug_assertions) {
+///     Either::Left(VerboseLogger)
+/// } else {
+///     Either::Right(Logger)
+/// };
+/// 
+/// # let _ =
+/// Ohkami::with(logger, (
+///     "/".GET(|| async {"Hello!"}),
+/// ))
+/// # ;
+/// ```
+/// 
+/// `FangExt::when` for the choice per request.
+#[derive(Clone, Debug)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+const _: () = {
+    impl<A: Fang<I>, B: Fang<I>, I: FangProc> Fang<I> for Either<A, B> {
+        type Proc = Either<A::Proc, B::Proc>;
+        fn chain(&self, inner: I) -> Self::Proc {
+            match self {
+                Self::Left(a)  => Either::Left(a.chain(inner)),
+                Self::Right(b) => Either::Right(b.chain(inner)),
+            }
+        }
+    }
+
+    impl<A: FangProc, B: FangProc> FangProc for Either<A, B> {
+        #[inline]
+        async fn bite<'b>(&'b self, req: &'b mut Request) -> Response {
+            match self {
+                Self::Left(a)  => a.bite(req).await,
+                Self::Right(b) => b.bite(req).await,
+            }
+        }
+    }
+};
+
+
 
 #[cfg(all(test, feature="testing", any(feature="rt_tokio",feature="rt_async-std")))]
 mod test {
@@ -186,4 +375,39 @@ mod test {
             ]);
         }
     }
+
+    #[crate::__rt__::test]
+    async fn conditional_fangs() {
+        /// appends its name to `X-Trace` of response
+        #[derive(Clone)]
+        struct Trace(&'static str);
+        impl FangAction for Trace {
+            async fn back<'a>(&'a self, res: &'a mut Response) {
+                res.headers.set().custom("X-Trace", crate::header::append(self.0));
+            }
+        }
+
+        let t = Ohkami::with((
+            Trace("logger").except_paths(["/healthz", "/static/*"]),
+            Trace("writer").when(|req| req.method != Method::GET),
+            if true {Either::Left(Trace("left"))} else {Either::Right(Trace("right"))},
+        ), (
+            "/healthz".GET(|| async {"OK"}),
+            "/static/:file".GET(|_: String| async {"file"}),
+            "/staticfile".GET(|| async {"not static"}),
+            "/users".GET(|| async {"list"}).POST(|| async {"create"}),
+        )).test();
+
+        for (req, trace) in [
+            (TestRequest::GET("/healthz"),         "left"),
+            (TestRequest::GET("/static/style.css"), "left"),
+            (TestRequest::GET("/staticfile"),      "left, logger"),
+            (TestRequest::GET("/users"),           "left, logger"),
+            (TestRequest::POST("/users"),          "left, writer, logger"),
+        ] {
+            let res = t.oneshot(req).await;
+            assert_eq!(res.status(), Status::OK);
+            assert_eq!(res.header("X-Trace"), Some(trace));
+        }
+    }
 }
@@ -1,10 +1,10 @@
 #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
 mod handler;
 #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
-pub(crate) use handler::{Handler, IntoHandler};
+pub(crate) use handler::{Handler, IntoHandler, CatchWith};
 
 mod middleware;
-pub use middleware::{Fangs, util};
+pub use middleware::{Fangs, DynFang, FangStack, util};
 
 use crate::{Request, Response};
 use std::{future::Future, pin::Pin, ops::Deref};
@@ -76,14 +76,14 @@ impl<Proc: FangProc> FangProcCaller for Proc {
     }
 }
 
-#[derive(Clone/* copy pointer */)]
-pub struct BoxedFPC(&'static (dyn
+#[derive(Clone/* share the proc */)]
+pub struct BoxedFPC(std::sync::Arc<dyn
     FangProcCaller + Send + Sync + 'static
-));
+>);
 const _: () = {
     impl BoxedFPC {
         pub(crate) fn from_proc(proc: impl FangProcCaller + Send + Sync + 'static) -> Self {
-            Self(Box::leak(Box::new(proc)))
+            Self(std::sync::Arc::new(proc))
         }
     }
 
@@ -91,7 +91,7 @@ const _: () = {
         type Target = dyn FangProcCaller + Send + Sync + 'static;
         #[inline(always)]
         fn deref(&self) -> &Self::Target {
-            self.0
+            &*self.0
         }
     }
 
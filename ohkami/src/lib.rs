@@ -85,14 +85,14 @@ mod __rt__ {
 
 
 mod request;
-pub use request::{Request, Method, FromRequest, FromParam, Memory};
+pub use request::{Request, Method, CustomMethod, FromRequest, AsyncFromRequest, FromParam, Memory, State, Rejection, RejectionKind};
 pub use ::ohkami_macros::FromRequest;
 
 mod response;
 pub use response::{Response, Status, IntoResponse};
 
 mod fangs;
-pub use fangs::{Fang, FangProc};
+pub use fangs::{Fang, FangProc, DynFang, FangStack};
 
 mod session;
 #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
@@ -100,10 +100,13 @@ use session::Session;
 
 mod ohkami;
 #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
-pub use ohkami::{Ohkami, Route};
+pub use ohkami::{Ohkami, Route, Host, PathPolicy, Panic, RouterHandle, RouteTable, RouteInfo, RouteName, IntoRouteName, UrlError, guard};
 
 pub mod header;
 
+#[cfg(feature="cookie")]
+pub mod cookie;
+
 pub mod builtin;
 
 pub mod typed;
@@ -153,7 +156,7 @@ pub mod utils {
         };
     }
 
-    pub use crate::fangs::util::FangAction;
+    pub use crate::fangs::util::{FangAction, FangExt, Condition, When, ExceptPaths, Either};
 
     #[cfg(feature="sse")]
     pub use ohkami_lib::stream::{self, Stream, StreamExt};
@@ -283,6 +286,9 @@ pub mod __internal__ {
 
     pub use crate::fangs::Fangs;
 
+    #[cfg(feature="rt_worker")]
+    pub use crate::ohkami::WorkerRouter;
+
     /* for benchmarks */
     #[cfg(feature="DEBUG")]
     #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
@@ -121,11 +121,13 @@ fn my_ohkami() -> Ohkami {
 
     let req = TestRequest::GET("/api/profiles/the_user/follow");
     let res = t.oneshot(req).await;
-    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.status(), Status::MethodNotAllowed);
+    assert_eq!(res.header("Allow"), Some("POST, DELETE, OPTIONS"));
 
     let req = TestRequest::POST("/api/profiles/the_user");
     let res = t.oneshot(req).await;
-    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.status(), Status::MethodNotAllowed);
+    assert_eq!(res.header("Allow"), Some("GET, HEAD, OPTIONS"));
 
     let req = TestRequest::POST("/api/profiles/the_user/follow");
     let res = t.oneshot(req).await;
@@ -490,3 +492,1541 @@ async fn prefixy_routes() {
         assert_eq!(res.status(), Status::NotFound);
     }
 }
+
+#[__rt__::test]
+async fn method_not_allowed() {
+    let t = Ohkami::new((
+        "/users"
+            .GET(|| async {"list_users"})
+            .POST(|| async {"create_user"}),
+        "/users/me"
+            .DELETE(|| async {"delete_me"}),
+        "/users/:id"
+            .GET(|id: String| async move {format!("get_user {id}")}),
+        "/login"
+            .POST(|| async {"login"}),
+    )).test(); {
+        let req = TestRequest::DELETE("/users");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("GET, POST, HEAD, OPTIONS"));
+    } {
+        let req = TestRequest::PUT("/users/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("GET, HEAD, OPTIONS"));
+    } {
+        /* `/users/me` doesn't shadow `/users/:id` for GET */
+        let req = TestRequest::GET("/users/me");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("get_user me"));
+    } {
+        let req = TestRequest::PATCH("/users/me");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("GET, DELETE, HEAD, OPTIONS"));
+    } {
+        let req = TestRequest::HEAD("/login");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("POST, OPTIONS"));
+        assert_eq!(res.text(), None);
+    } {
+        let req = TestRequest::DELETE("/posts");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+        assert_eq!(res.header("Allow"), None);
+    }
+
+    /* customizing the body by a fang */
+
+    #[derive(Clone)]
+    struct JSONMethodNotAllowed;
+    impl FangAction for JSONMethodNotAllowed {
+        async fn back<'a>(&'a self, res: &'a mut Response) {
+            if res.status == Status::MethodNotAllowed {
+                res.set_json(serde_json::json!({"error": "method not allowed"}));
+            }
+        }
+    }
+
+    let t = Ohkami::with(JSONMethodNotAllowed, (
+        "/hello".GET(|| async {"Hello!"}),
+    )).test(); {
+        let req = TestRequest::PUT("/hello");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("GET, HEAD, OPTIONS"));
+        assert_eq!(res.json::<serde_json::Value>().unwrap().unwrap(), serde_json::json!({"error": "method not allowed"}));
+    }
+}
+
+#[__rt__::test]
+async fn custom_methods() {
+    let t = Ohkami::new((
+        "/files/:name"
+            .GET(|name: String| async move {format!("get {name}")})
+            .method("PROPFIND", |name: String| async move {format!("propfind {name}")})
+            .method("MKCOL", || async {"mkcol"}),
+        "/search"
+            .method("QUERY", || async {"query"})
+            .HEAD(|| async {Response::NoContent().with_headers(|h| h.custom("X-Head", "explicit"))})
+            .OPTIONS(|| async {Response::NoContent().with_headers(|h| h.Allow("QUERY, HEAD, OPTIONS"))})
+            .TRACE(|| async {"trace"}),
+    )).test(); {
+        let req = TestRequest::method("PROPFIND", "/files/a.txt");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("propfind a.txt"));
+    } {
+        let req = TestRequest::method("MKCOL", "/files/dir");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("mkcol"));
+    } {
+        let req = TestRequest::method("QUERY", "/search");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("query"));
+    } {
+        let req = TestRequest::method("QUERY", "/files/a.txt");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("GET, PROPFIND, MKCOL, HEAD, OPTIONS"));
+    } {
+        let req = TestRequest::method("UNKNOWN", "/files/a.txt");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+    } {
+        let req = TestRequest::method("UNKNOWN", "/unknown");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    }
+
+    /* explicit HEAD, OPTIONS, TRACE */
+    {
+        let req = TestRequest::HEAD("/search");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NoContent);
+        assert_eq!(res.header("X-Head"), Some("explicit"));
+    } {
+        let req = TestRequest::HEAD("/files/a.txt");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.text(), None);
+    } {
+        let req = TestRequest::OPTIONS("/search");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NoContent);
+        assert_eq!(res.header("Allow"), Some("QUERY, HEAD, OPTIONS"));
+    } {
+        let req = TestRequest::TRACE("/search");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("trace"));
+    } {
+        let req = TestRequest::GET("/search");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("TRACE, QUERY, HEAD, OPTIONS"));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Ohkami can't handle `CONNECT` method")]
+fn connect_method_registration() {
+    Ohkami::new((
+        "/tunnel".method("CONNECT", || async {"CONNECT"}),
+    ));
+}
+
+#[__rt__::test]
+async fn param_constraints() {
+    let t = Ohkami::new((
+        "/users/me"
+            .GET(|| async {"me"}),
+        "/users/:id<u64>"
+            .GET(|id: u64| async move {format!("user #{id}")}),
+        "/users/:name"
+            .GET(|name: String| async move {format!("user `{name}`")}),
+        "/files/:name{[a-z]+\\.png}"
+            .GET(|name: String| async move {format!("png {name}")}),
+        "/files/:name{[a-z]+\\.jpe?g}/meta"
+            .GET(|name: String| async move {format!("jpeg meta {name}")}),
+        "/files/:name/meta"
+            .GET(|name: String| async move {format!("meta {name}")}),
+    )).test(); {
+        let req = TestRequest::GET("/users/me");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("me"));
+    } {
+        let req = TestRequest::GET("/users/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("user #42"));
+    } {
+        let req = TestRequest::GET("/users/ohkami");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("user `ohkami`"));
+    } {
+        let req = TestRequest::GET("/users/-1");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("user `-1`"));
+    } {
+        let req = TestRequest::GET("/files/logo.png");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("png logo.png"));
+    } {
+        let req = TestRequest::GET("/files/Logo.png");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        /* falls through to `/files/:name/meta` after `/files/:name{[a-z]+\.png}` */
+        let req = TestRequest::GET("/files/logo.png/meta");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("meta logo.png"));
+    } {
+        let req = TestRequest::GET("/files/photo.jpeg/meta");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("jpeg meta photo.jpeg"));
+    }
+
+    let t = Ohkami::new((
+        "/items/:id<u32>"
+            .GET(|id: u32| async move {format!("item #{id}")}),
+    )).test(); {
+        let req = TestRequest::GET("/items/abc");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::PUT("/items/1");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Unknown type constraint `<uuid>`")]
+fn unknown_param_constraint() {
+    Ohkami::new((
+        "/users/:id<uuid>".GET(|| async {"user"}),
+    ));
+}
+
+#[__rt__::test]
+async fn mixed_sections() {
+    let t = Ohkami::new((
+        "/files/readme"
+            .GET(|| async {"readme"}),
+        "/files/:name.:ext"
+            .GET(|(name, ext): (String, String)| async move {format!("{name} ({ext})")}),
+        "/docs/:name.json"
+            .GET(|name: String| async move {format!("json {name}")}),
+        "/v:version/users"
+            .GET(|version: u32| async move {format!("users v{version}")}),
+        "/@:username"
+            .GET(|username: String| async move {format!("profile of {username}")}),
+    )).test(); {
+        let req = TestRequest::GET("/files/readme");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("readme"));
+    } {
+        let req = TestRequest::GET("/files/logo.png");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("logo (png)"));
+    } {
+        /* the last `.` separates `name` and `ext` */
+        let req = TestRequest::GET("/files/archive.tar.gz");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("archive.tar (gz)"));
+    } {
+        let req = TestRequest::GET("/files/.png");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/files/logo.");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/docs/index.json");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("json index"));
+    } {
+        let req = TestRequest::GET("/docs/index.JSON");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/v2/users");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("users v2"));
+    } {
+        let req = TestRequest::GET("/v/users");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/@ohkami");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("profile of ohkami"));
+    } {
+        let req = TestRequest::GET("/ohkami");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    }
+
+    let o = Ohkami::new((
+        "/files/:name.:ext".GET(|| async {"file"}).name("file"),
+        "/v:version/users".GET(|| async {"users"}).name("users"),
+    ));
+    assert_eq!(o.url_for("file", [&"a b", &"txt"]).unwrap(), "/files/a%20b.txt");
+    assert_eq!(o.url_for("users", [&1]).unwrap(), "/v1/users");
+}
+
+#[test]
+#[should_panic(expected = "Conflicting route definition: After 'files', pattern ':Param.:Param' conflicts with :Param")]
+fn mixed_section_conflicting_with_param() {
+    Ohkami::new((
+        "/files/:name".GET(|| async {"file"}),
+        "/files/:name.:ext".GET(|| async {"file with ext"}),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "pattern ':Param.json' conflicts with ':Param.:Param'")]
+fn overlapping_mixed_sections() {
+    Ohkami::new((
+        "/files/:name.:ext".GET(|| async {"file"}),
+        "/files/:name.json".GET(|| async {"json"}),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Params in a section must be separated by static text")]
+fn adjacent_params_in_section() {
+    Ohkami::new((
+        "/files/:a:b".GET(|| async {"file"}),
+    ));
+}
+
+#[__rt__::test]
+async fn route_guards() {
+    use crate::guard::{header, accept, content_type};
+
+    let t = Ohkami::new((
+        "/users"
+            .GET(|| async {"beta"})
+            .when(header("X-Beta", "true")),
+        "/users"
+            .GET(|| async {"v2"})
+            .when(accept("application/vnd.acme.v2+json")),
+        "/users"
+            .GET(|| async {"v1"}),
+        "/upload"
+            .POST(|| async {"json"})
+            .when(content_type("application/json")),
+        "/upload"
+            .POST(|| async {"form"})
+            .when(content_type("application/x-www-form-urlencoded")),
+        "/items"
+            .GET(|| async {"items v1"})
+            .when(header("Accept-Version", "1")),
+        "/items"
+            .PUT(|| async {"put items v2"})
+            .when(header("Accept-Version", "2")),
+    )).test(); {
+        let req = TestRequest::GET("/users").header("x-beta", "true");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("beta"));
+    } {
+        let req = TestRequest::GET("/users").header("Accept", "application/vnd.acme.v2+json");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("v2"));
+    } {
+        let req = TestRequest::GET("/users").header("Accept", "application/*;q=0.5, text/html");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("v2"));
+    } {
+        let req = TestRequest::GET("/users").header("Accept", "application/json");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("v1"));
+    } {
+        let req = TestRequest::GET("/users").header("Accept", "application/vnd.acme.v2+json;q=0");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("v1"));
+    } {
+        let req = TestRequest::POST("/upload").json_lit("{}");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("json"));
+    } {
+        let req = TestRequest::POST("/upload").content("application/x-www-form-urlencoded; charset=UTF-8", b"a=1".as_slice());
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("form"));
+    } {
+        let req = TestRequest::POST("/upload").content("text/plain", b"a".as_slice());
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnsupportedMediaType);
+    } {
+        let req = TestRequest::GET("/items").header("Accept-Version", "1");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("items v1"));
+    } {
+        let req = TestRequest::GET("/items").header("Accept-Version", "2");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotAcceptable);
+    } {
+        let req = TestRequest::PUT("/items").header("Accept-Version", "2");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("put items v2"));
+    } {
+        let req = TestRequest::DELETE("/items");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("GET, PUT, HEAD, OPTIONS"));
+    }
+}
+
+#[__rt__::test]
+async fn route_guards_between_fangs() {
+    use crate::guard::header;
+    use crate::header::append;
+
+    /// appends its name to `X-Trace` of response, from the most inner
+    #[derive(Clone)]
+    struct Trace(&'static str);
+    impl FangAction for Trace {
+        async fn back<'a>(&'a self, res: &'a mut Response) {
+            res.headers.set().custom("X-Trace", append(self.0));
+        }
+    }
+
+    /// turns testers into beta users
+    #[derive(Clone)]
+    struct Tester;
+    impl FangAction for Tester {
+        async fn fore<'a>(&'a self, req: &'a mut Request) -> Result<(), Response> {
+            if req.headers.custom("X-Tester").is_some() {
+                req.headers.set().custom("X-Beta", "true");
+            }
+            Ok(())
+        }
+    }
+
+    let t = Ohkami::with((Trace("ohkami"), Tester), (
+        "/users"
+            .GET(|| async {"beta"})
+            .when(header("X-Beta", "true"))
+            .with(Trace("beta")),
+        "/users"
+            .GET(|| async {"v1"}),
+        "/items"
+            .GET(|| async {"items v1"})
+            .when(header("Accept-Version", "1"))
+            .with(Trace("v1")),
+    )).test(); {
+        let req = TestRequest::GET("/users").header("X-Tester", "1");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("beta"));
+        assert_eq!(res.header("X-Trace"), Some("beta, ohkami"));
+    } {
+        let req = TestRequest::GET("/users");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("v1"));
+        assert_eq!(res.header("X-Trace"), Some("ohkami"));
+    } {
+        let req = TestRequest::GET("/items");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotAcceptable);
+        assert_eq!(res.header("X-Trace"), Some("ohkami"));
+    }
+}
+
+#[test]
+#[should_panic(expected = "`OPTIONS` handler can't be guarded: `/users`")]
+fn guarded_options_handler() {
+    Ohkami::new((
+        "/users"
+            .OPTIONS(|| async {"options"})
+            .when(crate::guard::header("X-Beta", "true")),
+    ));
+}
+
+#[__rt__::test]
+async fn async_extractors() {
+    use crate::AsyncFromRequest;
+
+    struct Session {
+        user: &'static str,
+    }
+    impl<'req> AsyncFromRequest<'req> for Session {
+        type Error = Response;
+        async fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+            async fn find_user(session_id: &str) -> Option<&'static str> {
+                /* as an async session store */
+                std::future::ready(()).await;
+                (session_id == "s3cr3t").then_some("ohkami")
+            }
+
+            let session_id = req.headers.custom("X-Session")?;
+            Some(find_user(session_id).await
+                .map(|user| Session { user })
+                .ok_or_else(Response::Unauthorized))
+        }
+    }
+
+    async fn me(session: Session) -> String {
+        format!("I'm {}", session.user)
+    }
+    async fn post(id: u64, session: Option<Session>, req: &Request) -> String {
+        let by = session.map(|s| s.user).unwrap_or("anonymous");
+        format!("{} post #{id} by {by}", req.method)
+    }
+
+    let t = Ohkami::new((
+        "/me"
+            .GET(me),
+        "/posts/:id"
+            .GET(post),
+    )).test(); {
+        let req = TestRequest::GET("/me").header("X-Session", "s3cr3t");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("I'm ohkami"));
+    } {
+        let req = TestRequest::GET("/me").header("X-Session", "unknown");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::Unauthorized);
+    } {
+        let req = TestRequest::GET("/me");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::BadRequest);
+    } {
+        let req = TestRequest::GET("/posts/42").header("X-Session", "s3cr3t");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("GET post #42 by ohkami"));
+    } {
+        let req = TestRequest::GET("/posts/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("GET post #42 by anonymous"));
+    }
+}
+
+#[__rt__::test]
+async fn router_states() {
+    use crate::State;
+
+    struct Greeting(&'static str);
+    struct Counter(std::sync::atomic::AtomicUsize);
+
+    async fn greet(g: State<'_, Greeting>) -> &'static str {
+        g.0
+    }
+    async fn count(c: State<'_, Counter>) -> String {
+        let n = c.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        n.to_string()
+    }
+    async fn missing(_: State<'_, String>) -> &'static str {
+        "unreachable"
+    }
+
+    let t = Ohkami::new((
+        "/hello"
+            .GET(greet),
+        "/count"
+            .GET(count),
+        "/missing"
+            .GET(missing),
+        "/ja".By(Ohkami::new((
+            "/hello"
+                .GET(greet),
+            "/count"
+                .GET(count),
+        )).with_state(Greeting("こんにちは"))),
+    )).with_state(Greeting("Hello")).with_state(Counter(0.into())).test(); {
+        let req = TestRequest::GET("/hello");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("Hello"));
+    } {
+        let req = TestRequest::GET("/ja/hello");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("こんにちは"));
+    } {
+        /* shared with the nested `Ohkami` */
+        let req = TestRequest::GET("/count");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("1"));
+
+        let req = TestRequest::GET("/ja/count");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("2"));
+    } {
+        let req = TestRequest::GET("/missing");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::InternalServerError);
+    }
+}
+
+#[__rt__::test]
+async fn rejection_mapper() {
+    use crate::{Rejection, RejectionKind, FromRequest};
+    use crate::builtin::payload::JSON;
+    use crate::typed::Payload;
+    use ::serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct CreateUser {
+        #[allow(unused)]
+        name: String,
+    } impl Payload for CreateUser {
+        type Type = JSON;
+    }
+
+    #[derive(Deserialize)]
+    struct Search {
+        q:    String,
+        #[allow(unused)]
+        page: Option<u8>,
+    } impl<'req> FromRequest<'req> for Search {
+        type Error = Rejection;
+        fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+            /* as `#[Query]` does */
+            req.__query()
+        }
+    }
+
+    async fn get_user(id: u8) -> String {
+        id.to_string()
+    }
+    async fn create_user(_: CreateUser) -> Status {
+        Status::Created
+    }
+    async fn search(s: Search) -> String {
+        s.q
+    }
+
+    fn envelope(r: Rejection) -> Response {
+        if r.kind == RejectionKind::Custom {
+            return r.response
+        }
+        Response::UnprocessableEntity().with_json(::serde_json::json!({
+            "code":      format!("{:?}", r.kind),
+            "extractor": r.extractor,
+            "field":     r.field,
+            "message":   r.message,
+        }))
+    }
+
+    let routes = || (
+        "/users/:id"
+            .GET(get_user),
+        "/users"
+            .POST(create_user),
+        "/search"
+            .GET(search),
+    );
+
+    let t = Ohkami::new((
+        "/mapped".By(Ohkami::new(routes()).map_rejection(envelope)),
+        "/default".By(Ohkami::new(routes())),
+    )).test(); {
+        let req = TestRequest::GET("/mapped/users/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("42"));
+
+        let req = TestRequest::GET("/mapped/users/abc");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnprocessableEntity);
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["code"], "Param");
+        assert_eq!(json["extractor"], "u8");
+        assert_eq!(json["field"], "id");
+    } {
+        let req = TestRequest::POST("/mapped/users").json_lit(r#"{"age":2}"#);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnprocessableEntity);
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["code"], "Payload");
+        assert_eq!(json["field"], "name");
+        assert!(json["extractor"].as_str().unwrap().ends_with("CreateUser"));
+        assert!(json["message"].as_str().unwrap().starts_with("missing field `name`"));
+
+        let req = TestRequest::POST("/mapped/users").json_lit(r#"{"name":42}"#);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnprocessableEntity);
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["code"], "Payload");
+        assert_eq!(json["field"], "name");
+        assert!(json["message"].as_str().unwrap().starts_with("invalid type: integer `42`"));
+
+        let req = TestRequest::POST("/mapped/users");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnprocessableEntity);
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["code"], "Missing");
+    } {
+        let req = TestRequest::GET("/mapped/search?query=ohkami");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnprocessableEntity);
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["code"], "Query");
+        assert_eq!(json["field"], "q");
+
+        let req = TestRequest::GET("/mapped/search?q=ohkami&page=x");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::UnprocessableEntity);
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["code"], "Query");
+        assert_eq!(json["field"], "page");
+    } {
+        /* params prefixed by the nesting route */
+        let t = Ohkami::new((
+            "/v:version".By(Ohkami::new((
+                "/users/:id".GET(|(_, id): (u8, u8)| async move {id.to_string()}),
+            ))),
+        )).map_rejection(envelope).test();
+
+        let req = TestRequest::GET("/v1/users/abc");
+        let res = t.oneshot(req).await;
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["field"], "id");
+
+        let req = TestRequest::GET("/vX/users/1");
+        let res = t.oneshot(req).await;
+        let json = res.json::<::serde_json::Value>().unwrap().unwrap();
+        assert_eq!(json["field"], "version");
+    } {
+        /* not affecting the other `Ohkami`s */
+        let req = TestRequest::POST("/default/users").json_lit(r#"{"age":2}"#);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert!(res.text().unwrap().starts_with("missing field `name`"));
+
+        let req = TestRequest::GET("/default/search?query=ohkami");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert_eq!(res.text(), Some("Unexpected query parameters: missing field `q`"));
+    }
+}
+
+#[cfg(feature="cookie")]
+#[__rt__::test]
+async fn cookie_jar() {
+    use crate::cookie::{CookieJar, Key};
+
+    async fn login(mut jar: CookieJar<'_>) -> Response {
+        jar.add("theme", "dark", |d| d.Path("/"));
+        jar.add_signed("user", "ohkami", |d| d.Path("/").HttpOnly());
+        jar.add_private("token", "s3cr3t", |d| d.Path("/").HttpOnly());
+        Response::OK().with_cookies(jar)
+    }
+    async fn me(jar: CookieJar<'_>) -> String {
+        format!("{:?} {:?} {:?}", jar.get("theme"), jar.get_signed("user"), jar.get_private("token"))
+    }
+    async fn logout(mut jar: CookieJar<'_>) -> Response {
+        jar.remove("user", |d| d.Path("/"));
+        Response::NoContent().with_cookies(jar)
+    }
+
+    fn routes(key: Key) -> Ohkami {
+        Ohkami::new((
+            "/login".POST(login),
+            "/me".GET(me),
+            "/logout".POST(logout),
+        )).with_state(key)
+    }
+
+    let t = routes(Key::new([1; 32])).test();
+
+    let cookie = {
+        let res = t.oneshot(TestRequest::POST("/login")).await;
+        let setcookies = res.headers()
+            .filter(|(name, _)| *name == "Set-Cookie")
+            .map(|(_, setcookie)| setcookie)
+            .collect::<Vec<_>>();
+        assert_eq!(setcookies.len(), 3);
+        assert_eq!(setcookies[0], "theme=dark; Path=/");
+        assert!(setcookies[1].starts_with("user=") && setcookies[1].contains("ohkami"));
+        assert!(setcookies[2].starts_with("token=") && !setcookies[2].contains("s3cr3t"));
+        setcookies.iter()
+            .map(|setcookie| setcookie.split(';').next().unwrap())
+            .collect::<Vec<_>>().join("; ")
+    }; {
+        let req = TestRequest::GET("/me").header("Cookie", cookie.clone());
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some(r#"Some("dark") Some("ohkami") Some("s3cr3t")"#));
+    } {
+        let forged = cookie.replace("ohkami", "admin");
+        let req = TestRequest::GET("/me").header("Cookie", forged);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some(r#"Some("dark") None Some("s3cr3t")"#));
+    } {
+        let res = t.oneshot(TestRequest::POST("/logout")).await;
+        assert!(res.headers().any(|(name, value)| name == "Set-Cookie" && value.starts_with("user=;") && value.contains("Max-Age=0")));
+    }
+
+    /* rotated */
+    {
+        let t = routes(Key::new([2; 32]).with_previous([1; 32])).test();
+        let req = TestRequest::GET("/me").header("Cookie", cookie.clone());
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some(r#"Some("dark") Some("ohkami") Some("s3cr3t")"#));
+    }
+    {
+        let t = routes(Key::new([2; 32])).test();
+        let req = TestRequest::GET("/me").header("Cookie", cookie.clone());
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some(r#"Some("dark") None None"#));
+    }
+
+    /* no `Key` */
+    {
+        let t = Ohkami::new(("/me".GET(me),)).test();
+        let req = TestRequest::GET("/me").header("Cookie", cookie);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some(r#"Some("dark") None None"#));
+    }
+}
+
+#[__rt__::test]
+async fn route_fangs() {
+    use crate::header::append;
+
+    /// appends its name to `X-Trace` of response, from the most inner
+    #[derive(Clone)]
+    struct Trace(&'static str);
+    impl FangAction for Trace {
+        async fn back<'a>(&'a self, res: &'a mut Response) {
+            res.headers.set().custom("X-Trace", append(self.0));
+        }
+    }
+
+    #[derive(Clone)]
+    struct Auth;
+    impl FangAction for Auth {
+        async fn fore<'a>(&'a self, req: &'a mut Request) -> Result<(), Response> {
+            req.headers.Authorization().map(|_| ()).ok_or_else(Response::Unauthorized)
+        }
+    }
+
+    let t = Ohkami::with(Trace("ohkami"), (
+        "/articles"
+            .GET(|| async {"list"})
+            .POST_with(Auth, || async {"create"})
+            .with(Trace("route")),
+        "/articles/:id"
+            .GET_with(Trace("method"), |id: u32| async move {format!("article {id}")})
+            .DELETE(|_: u32| async {"delete"})
+            .with(Auth),
+        "/health"
+            .GET_with(Auth, || async {"replaced"})
+            .GET(|| async {"OK"}),
+    )).test();
+
+    {
+        let req = TestRequest::GET("/articles");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.text(), Some("list"));
+        assert_eq!(res.header("X-Trace"), Some("route, ohkami"));
+    }
+    {
+        let req = TestRequest::POST("/articles");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::Unauthorized);
+        assert_eq!(res.header("X-Trace"), Some("route, ohkami"));
+
+        let req = TestRequest::POST("/articles")
+            .header("Authorization", "Bearer token");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.text(), Some("create"));
+    }
+    {
+        let req = TestRequest::GET("/articles/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::Unauthorized);
+        assert_eq!(res.header("X-Trace"), Some("ohkami"));
+
+        let req = TestRequest::GET("/articles/42")
+            .header("Authorization", "Bearer token");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.text(), Some("article 42"));
+        assert_eq!(res.header("X-Trace"), Some("method, ohkami"));
+
+        let req = TestRequest::DELETE("/articles/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::Unauthorized);
+    }
+    {
+        let req = TestRequest::GET("/health");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.text(), Some("OK"));
+    }
+
+    let routes = Ohkami::with(Trace("ohkami"), (
+        "/articles"
+            .GET(|| async {"list"})
+            .POST_with(Auth, || async {"create"})
+            .with((Trace("route"), Auth)),
+    )).routes();
+    assert_eq!(routes.get("/articles").unwrap().fangs(), 3);
+}
+
+#[__rt__::test]
+async fn dynamic_fangs() {
+    use crate::{DynFang, FangStack};
+    use crate::header::append;
+
+    /// appends its name to `X-Trace` of response, from the most inner
+    #[derive(Clone)]
+    struct Trace(&'static str);
+    impl FangAction for Trace {
+        async fn back<'a>(&'a self, res: &'a mut Response) {
+            res.headers.set().custom("X-Trace", append(self.0));
+        }
+    }
+
+    let mut stack = FangStack::new();
+    for name in ["a", "b", "c"] {
+        stack.push(Trace(name));
+    }
+    assert_eq!(stack.len(), 3);
+
+    let list: Vec<Box<dyn DynFang>> = vec![Box::new(Trace("x")), Box::new(Trace("y"))];
+
+    let o = Ohkami::with(stack, (
+        "/stack".GET(|| async {"stack"}),
+        "/list".GET(|| async {"list"}).with(list),
+        "/empty".GET(|| async {"empty"}).with(FangStack::new()),
+    ));
+
+    let routes = o.routes();
+    assert_eq!(routes.get("/stack").unwrap().fangs(), 3);
+    assert_eq!(routes.get("/list").unwrap().fangs(), 5);
+    assert_eq!(routes.get("/empty").unwrap().fangs(), 3);
+
+    let t = o.test();
+    for (path, trace) in [
+        ("/stack", "c, b, a"),
+        ("/list",  "y, x, c, b, a"),
+        ("/empty", "c, b, a"),
+    ] {
+        let res = t.oneshot(TestRequest::GET(path)).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.header("X-Trace"), Some(trace));
+    }
+}
+
+#[__rt__::test]
+async fn catch_panics() {
+    use std::sync::{Arc, Mutex};
+
+    async fn panic_in_polling() -> &'static str {
+        __rt__::sleep(std::time::Duration::from_millis(1)).await;
+        panic!("panicked in polling")
+    }
+    async fn panic_any() -> Status {
+        std::panic::panic_any(42_u8)
+    }
+    async fn panic_in_nested(_id: u32) -> &'static str {
+        panic!("panicked in nested")
+    }
+
+    let t = Ohkami::new((
+        "/ok".GET(|| async {"OK"}),
+        "/panic".GET(panic_in_polling),
+    )).test();
+
+    let res = t.oneshot(TestRequest::GET("/panic")).await;
+    assert_eq!(res.status(), Status::InternalServerError);
+
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let t = Ohkami::with(crate::builtin::fang::CORS::new("https://example.com"), (
+        "/ok".GET(|| async {"OK"}),
+        "/panic".GET(panic_in_polling),
+        "/panic_any".POST(panic_any),
+        "/api".By(Ohkami::new((
+            "/users/:id".GET(panic_in_nested),
+        ))),
+    )).on_panic(|panic| match panic.message() {
+        Some(message) => Response::ServiceUnavailable().with_text(message.to_string()),
+        None          => Response::ServiceUnavailable(),
+    }).report_panic({
+        let reported = reported.clone();
+        move |panic| reported.lock().unwrap().push(format!(
+            "{} {} {:?}", panic.method, panic.path, panic.payload().downcast_ref::<u8>()
+        ))
+    }).test();
+
+    let res = t.oneshot(TestRequest::GET("/panic")).await;
+    assert_eq!(res.status(), Status::ServiceUnavailable);
+    assert_eq!(res.text(), Some("panicked in polling"));
+    assert_eq!(res.header("Access-Control-Allow-Origin"), Some("https://example.com"));
+
+    let res = t.oneshot(TestRequest::POST("/panic_any")).await;
+    assert_eq!(res.status(), Status::ServiceUnavailable);
+    assert_eq!(res.text(), None);
+    assert_eq!(res.header("Access-Control-Allow-Origin"), Some("https://example.com"));
+
+    let res = t.oneshot(TestRequest::GET("/api/users/42")).await;
+    assert_eq!(res.status(), Status::ServiceUnavailable);
+    assert_eq!(res.text(), Some("panicked in nested"));
+    assert_eq!(res.header("Access-Control-Allow-Origin"), Some("https://example.com"));
+
+    let res = t.oneshot(TestRequest::GET("/ok")).await;
+    assert_eq!(res.status(), Status::OK);
+
+    assert_eq!(&*reported.lock().unwrap(), &[
+        "GET /panic None",
+        "POST /panic_any Some(42)",
+        "GET /api/users/42 None",
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "`on_panic` can't be set on a nested `Ohkami`")]
+fn nested_on_panic() {
+    Ohkami::new((
+        "/api".By(Ohkami::new((
+            "/".GET(|| async {"api"}),
+        )).on_panic(|_| Response::ServiceUnavailable())),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "`report_panic` can't be set on a nested `Ohkami`")]
+fn nested_report_panic() {
+    Ohkami::new((
+        "/api".By(Ohkami::new((
+            "/".GET(|| async {"api"}),
+        )).report_panic(|_| ())),
+    ));
+}
+
+#[test]
+fn route_table() {
+    #[derive(Clone)]
+    struct Logger;
+    impl FangAction for Logger {}
+
+    #[derive(Clone)]
+    struct Auth;
+    impl FangAction for Auth {}
+
+    let o = Ohkami::with(Logger, (
+        "/hc"
+            .GET(|| async {"OK"}),
+        "/api".By(Ohkami::with((Auth, Logger), (
+            "/"
+                .GET(|| async {"api"}),
+            "/users/:id<u64>"
+                .GET(|| async {"get_user"})
+                .PATCH(|| async {"update_user"})
+                .method("PROPFIND", || async {"propfind_user"}),
+        ))),
+        "/files/:name".By(Ohkami::new((
+            "/meta"
+                .OPTIONS(|| async {"meta"}),
+        ))),
+    ));
+
+    let routes = o.routes();
+    assert_eq!(
+        routes.iter().map(|r| (r.path(), r.methods().to_vec(), r.fangs())).collect::<Vec<_>>(),
+        [
+            ("/hc",                  vec![Method::GET], 1),
+            ("/api",                 vec![Method::GET], 3),
+            ("/api/users/:id<u64>",  vec![Method::GET, Method::PATCH, Method::from_name("PROPFIND").unwrap()], 3),
+            ("/files/:name/meta",    vec![Method::OPTIONS], 1),
+        ]
+    );
+
+    assert_eq!(routes.to_string(), "\
+        PATH                  METHODS                FANGS\n\
+        /hc                   GET                    1\n\
+        /api                  GET                    3\n\
+        /api/users/:id<u64>   GET, PATCH, PROPFIND   3\n\
+        /files/:name/meta     OPTIONS                1\n\
+    ");
+}
+
+#[__rt__::test]
+async fn named_routes() {
+    use crate::{RouteName, UrlError};
+
+    const USER: RouteName<1> = RouteName::new("user");
+
+    async fn index(req: &Request) -> Result<String, UrlError> {
+        req.url_for("post", [&"hello world", &"ohkami/rust"])
+    }
+    async fn create_user(req: &Request) -> Result<Response, UrlError> {
+        let location = req.url_for(USER, [&42])?;
+        Ok(Response::Created().with_headers(|h| h.Location(location)))
+    }
+    async fn unknown(req: &Request) -> Result<String, UrlError> {
+        req.url_for("unknown", [])
+    }
+
+    let o = Ohkami::new((
+        "/"
+            .GET(index)
+            .name("root"),
+        "/users/:id<u64>"
+            .GET(|id: u64| async move {format!("user {id}")})
+            .name(USER),
+        "/blog".By(Ohkami::new((
+            "/:category/:slug"
+                .GET(|(_, _): (String, String)| async {"post"})
+                .name("post"),
+            "/"
+                .POST(create_user),
+        ))),
+        "/unknown"
+            .GET(unknown),
+    ));
+
+    assert_eq!(o.url_for("root", []).unwrap(), "/");
+    assert_eq!(o.url_for(USER, [&1]).unwrap(), "/users/1");
+    assert_eq!(o.url_for("post", [&"a b", &"c/d?"]).unwrap(), "/blog/a%20b/c%2Fd%3F");
+    assert_eq!(o.url_for("users", []), Err(UrlError::UnknownName("users")));
+    assert_eq!(o.url_for("post", [&"a"]), Err(UrlError::WrongParams { name: "post", expected: 2, given: 1 }));
+
+    let t = o.test(); {
+        let req = TestRequest::GET("/");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("/blog/hello%20world/ohkami%2Frust"));
+    } {
+        let req = TestRequest::POST("/blog");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::Created);
+        assert_eq!(res.header("Location"), Some("/users/42"));
+    } {
+        let req = TestRequest::GET("/unknown");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::InternalServerError);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Route name `user` expects 2 param(s), but `/users/:id` has 1")]
+fn route_name_with_wrong_params() {
+    const USER: crate::RouteName<2> = crate::RouteName::new("user");
+
+    Ohkami::new((
+        "/users/:id".GET(|| async {"user"}).name(USER),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Duplicate route name `user`")]
+fn duplicate_route_names() {
+    Ohkami::new((
+        "/user".GET(|| async {"me"}).name("user"),
+        "/api".By(Ohkami::new((
+            "/users/:id".GET(|| async {"user"}).name("user"),
+        ))),
+    ));
+}
+
+#[__rt__::test]
+async fn host_routing() {
+    use crate::Host;
+
+    let t = Ohkami::new((
+        Host("api.example.com").By(Ohkami::new((
+            "/users/:id"
+                .GET(|id: u64| async move {format!("api user {id}")}),
+        ))),
+        Host("*.example.com").By(Ohkami::new((
+            "/"
+                .GET(|tenant: String| async move {format!("tenant {tenant}")}),
+            "/items/:id"
+                .GET(|(tenant, id): (String, u64)| async move {format!("tenant {tenant} item {id}")}),
+            "/users/:id"
+                .GET(|(tenant, id): (String, u64)| async move {format!("tenant {tenant} user {id}")}),
+        ))),
+        "/hc"
+            .GET(|| async {"OK"}),
+        "/v2".By(Ohkami::new((
+            Host("admin.example.com").By(Ohkami::new((
+                "/"
+                    .GET(|| async {"admin v2"}),
+            ))),
+        ))),
+    )).test(); {
+        /* exact host is prior to wildcard */
+        let req = TestRequest::GET("/users/42").header("Host", "api.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("api user 42"));
+    } {
+        let req = TestRequest::GET("/users/42").header("Host", "API.example.com:8080");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("api user 42"));
+    } {
+        /* no route in the exact host, not falling back to wildcard */
+        let req = TestRequest::GET("/").header("Host", "api.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/").header("Host", "acme.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("tenant acme"));
+    } {
+        let req = TestRequest::GET("/items/3").header("Host", "acme.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("tenant acme item 3"));
+    } {
+        let req = TestRequest::GET("/users/42").header("Host", "other.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("tenant other user 42"));
+    } {
+        let req = TestRequest::GET("/users/42").header("Host", "unknown.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        /* not falling back to the other routes */
+        let req = TestRequest::GET("/hc").header("Host", "acme.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/hc").header("Host", "unknown.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("OK"));
+    } {
+        let req = TestRequest::GET("/hc");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("OK"));
+    } {
+        let req = TestRequest::GET("/v2").header("Host", "admin.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("admin v2"));
+    } {
+        let req = TestRequest::GET("/v2").header("Host", "api.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::POST("/items/3").header("Host", "acme.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Allow"), Some("GET, HEAD, OPTIONS"));
+    }
+}
+
+#[__rt__::test]
+async fn host_routing_through_host_fangs() {
+    use crate::Host;
+    use crate::builtin::fang::CORS;
+
+    let t = Ohkami::new((
+        Host("api.example.com").By(Ohkami::with(CORS::new("https://api.example.com"), (
+            "/users".GET(|| async {"users"}),
+        ))),
+        "/hc"
+            .GET(|| async {"OK"}),
+    )).test(); {
+        let req = TestRequest::GET("/hc").header("Host", "api.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+        assert_eq!(res.header("Access-Control-Allow-Origin"), Some("https://api.example.com"));
+    } {
+        let req = TestRequest::POST("/users").header("Host", "api.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::MethodNotAllowed);
+        assert_eq!(res.header("Access-Control-Allow-Origin"), Some("https://api.example.com"));
+    }
+
+    let t = Ohkami::new((
+        Host("api.example.com").By(Ohkami::new((
+            "/users".GET(|| async {"users"}),
+        ))),
+        "/hc"
+            .GET(|| async {"OK"}),
+    )).reject_unknown_hosts().test(); {
+        let req = TestRequest::GET("/users").header("Host", "api.example.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("users"));
+    } {
+        let req = TestRequest::GET("/hc").header("Host", "unknown.com");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/hc");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    }
+}
+
+#[test]
+#[should_panic(expected = "`reject_unknown_hosts` can't be set on a nested `Ohkami`")]
+fn nested_reject_unknown_hosts() {
+    Ohkami::new((
+        "/api".By(Ohkami::new((
+            "/".GET(|| async {"api"}),
+        )).reject_unknown_hosts()),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Routes under wildcard host `*.example.com` can have at most 1 path param: `/:a/:b`")]
+fn wildcard_host_with_too_many_params() {
+    Ohkami::new((
+        crate::Host("*.example.com").By(Ohkami::new((
+            "/:a/:b".GET(|| async {"ab"}),
+        ))),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "A route can have at most 2 path params, but found 3: `/v:version/:name.:ext`")]
+fn route_with_too_many_params() {
+    Ohkami::new((
+        "/v:version/:name.:ext".GET(|| async {"too many"}),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "A route can have at most 2 path params, but found 3: `/:org/:name.:ext`")]
+fn nested_route_with_too_many_params() {
+    Ohkami::new((
+        "/:org".By(Ohkami::new((
+            "/:name.:ext".GET(|| async {"too many"}),
+        ))),
+    ));
+}
+
+#[__rt__::test]
+async fn path_policy() {
+    use crate::PathPolicy;
+    use crate::builtin::fang::CORS;
+
+    async fn echo(req: &Request) -> String {
+        req.path.str().into_owned()
+    }
+
+    fn routes() -> Ohkami {
+        Ohkami::with(CORS::new("https://ohkami.rs"), (
+            "/"
+                .GET(|| async {"root"}),
+            "/api/users/:id"
+                .GET(|id: String| async move {format!("user {id}")}),
+            "/admin/secret"
+                .GET(|| async {"secret"}),
+            "/echo"
+                .GET(echo),
+            "/docs/v:version.html"
+                .GET(|version: String| async move {format!("docs v{version}")}),
+        ))
+    }
+
+    let t = routes().test(); /* lenient by default */ {
+        let req = TestRequest::GET("/api//users/42/");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("user 42"));
+    } {
+        let req = TestRequest::GET("/api/users/..");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/api/users/../../admin/secret");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("secret"));
+    } {
+        let req = TestRequest::GET("/x/%2e%2e/echo/.");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("/echo"));
+    } {
+        let req = TestRequest::GET("//");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("root"));
+    }
+
+    let t = routes().path_policy(PathPolicy::redirect()).test(); {
+        let req = TestRequest::GET("/api//users/42/").query("q", "1");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::PermanentRedirect);
+        assert_eq!(res.header("Location"), Some("/api/users/42?q=1"));
+        assert_eq!(res.header("Access-Control-Allow-Origin"), Some("https://ohkami.rs"));
+    } {
+        let req = TestRequest::GET("/api/users/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("user 42"));
+    } {
+        let req = TestRequest::GET("/admin/./");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::PermanentRedirect);
+        assert_eq!(res.header("Location"), Some("/admin"));
+    }
+
+    let t = routes().path_policy(PathPolicy::strict()).test(); {
+        let req = TestRequest::GET("/api/users/42/");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+        assert_eq!(res.header("Access-Control-Allow-Origin"), Some("https://ohkami.rs"));
+    } {
+        let req = TestRequest::GET("/api/users/a%2Fb");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("root"));
+    } {
+        let req = TestRequest::GET("/api/users/42");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("user 42"));
+    }
+
+    let t = routes().path_policy(PathPolicy::lenient().case_insensitive()).test(); {
+        let req = TestRequest::GET("/API/Users/Ab");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("user Ab"));
+    } {
+        let req = TestRequest::GET("/Admin/SECRET/");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("secret"));
+    } {
+        let req = TestRequest::GET("/ECHO");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("/echo"));
+    }
+
+    let t = routes().path_policy(PathPolicy::redirect().case_insensitive()).test(); {
+        let req = TestRequest::GET("/API/Users/Ab");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::PermanentRedirect);
+        assert_eq!(res.header("Location"), Some("/api/users/Ab"));
+    } {
+        let req = TestRequest::GET("/Admin//SECRET/");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::PermanentRedirect);
+        assert_eq!(res.header("Location"), Some("/admin/secret"));
+    } {
+        let req = TestRequest::GET("/Docs/V2.HTML");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::PermanentRedirect);
+        assert_eq!(res.header("Location"), Some("/docs/v2.html"));
+    } {
+        let req = TestRequest::GET("/admin/secret");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("secret"));
+    }
+
+    let t = routes().path_policy(PathPolicy::strict().case_insensitive()).test(); {
+        let req = TestRequest::GET("/Admin/Secret");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    } {
+        let req = TestRequest::GET("/admin/secret");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("secret"));
+    }
+}
+
+#[test]
+#[should_panic(expected = "`path_policy` can't be set on a nested `Ohkami`")]
+fn nested_path_policy() {
+    Ohkami::new((
+        "/api".By(Ohkami::new((
+            "/".GET(|| async {"api"}),
+        )).path_policy(crate::PathPolicy::strict())),
+    ));
+}
+
+
+
+#[__rt__::test]
+async fn router_handle_replace() {
+    use crate::RouterHandle;
+
+    let handle = RouterHandle::new(Ohkami::new((
+        "/".GET(|| async {"v1"}),
+    )));
+    let t = handle.clone().test(); {
+        let res = t.oneshot(TestRequest::GET("/")).await;
+        assert_eq!(res.text(), Some("v1"));
+        let res = t.oneshot(TestRequest::GET("/plugin")).await;
+        assert_eq!(res.status(), Status::NotFound);
+    }
+
+    handle.replace(Ohkami::new((
+        "/".GET(|| async {"v2"}),
+        "/plugin".GET(|| async {"plugin"}),
+    ))); {
+        let res = t.oneshot(TestRequest::GET("/")).await;
+        assert_eq!(res.text(), Some("v2"));
+        let res = t.oneshot(TestRequest::GET("/plugin")).await;
+        assert_eq!(res.text(), Some("plugin"));
+    }
+}
+
+#[__rt__::test]
+async fn static_files_dir() {
+    static DIR: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    let dir = DIR.get_or_init(|| std::env::temp_dir()
+        .join(format!("ohkami-static-files-dir-{}", std::process::id()))
+        .to_str().unwrap().to_owned()
+    );
+    let dir_path = std::path::Path::new(dir);
+    std::fs::create_dir_all(dir_path.join("docs")).unwrap();
+    std::fs::write(dir_path.join("index.html"), "<h1>Hello</h1>").unwrap();
+    std::fs::write(dir_path.join("docs/intro.html"), "<p>intro</p>").unwrap();
+
+    let handle = crate::RouterHandle::new(Ohkami::new((
+        "/static".Dir(dir).omit_extensions(["html"]),
+    )));
+    let t = handle.clone().test();
+    for _ in 0..3 {
+        let res = t.oneshot(TestRequest::GET("/static")).await;
+        assert_eq!(res.header("Content-Type"), Some("text/html; charset=UTF-8"));
+        assert_eq!(res.html(), Some("<h1>Hello</h1>"));
+
+        let res = t.oneshot(TestRequest::GET("/static/docs/intro")).await;
+        assert_eq!(res.html(), Some("<p>intro</p>"));
+    }
+
+    /* the files and their routes are freed with the router */
+    handle.replace(Ohkami::new(()));
+    let res = t.oneshot(TestRequest::GET("/static")).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    std::fs::remove_dir_all(dir_path).unwrap();
+}
+
+#[test]
+fn router_is_dropped() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CREATED: AtomicUsize = AtomicUsize::new(0);
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+    impl Counted {
+        fn new() -> Self {
+            CREATED.fetch_add(1, Ordering::SeqCst);
+            Self
+        }
+    }
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            Self::new()
+        }
+    }
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    impl FangAction for Counted {}
+
+    for _ in 0..1000 {
+        let counted = Counted::new();
+        let o = Ohkami::with(Counted::new(), (
+            "/users/:id".GET(move |id: u64| {
+                let _ = &counted;
+                async move {format!("{id}")}
+            }),
+            "/api".By(Ohkami::with(Counted::new(), (
+                "/hc".GET(|| async {"OK"}),
+            ))),
+        ));
+        drop(o.test());
+    }
+
+    /* every `Counted` in fangs and handlers is dropped with the router */
+    assert!(CREATED.load(Ordering::SeqCst) >= 3 * 1000);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), CREATED.load(Ordering::SeqCst));
+}
@@ -0,0 +1,65 @@
+#![cfg(feature="testing")]
+#![cfg(feature="rt_worker")]
+
+use crate::prelude::*;
+use crate::testing::TestRequest;
+use crate::__internal__::WorkerRouter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+
+
+/// no async runtime for `rt_worker` out of Workers
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output
+        }
+    }
+}
+
+fn respond(router: &WorkerRouter, req: TestRequest) -> Response {
+    block_on(async {
+        let mut request = Request::init();
+        let mut request = unsafe {std::pin::Pin::new_unchecked(&mut request)};
+        match request.as_mut().read(&mut &req.encode()[..]).await {
+            Ok(Some(())) => router.respond(&mut request).await,
+            Ok(None) => panic!("No request"),
+            Err(res) => res,
+        }
+    })
+}
+
+#[test]
+fn worker_router_is_built_once() {
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+    fn my_ohkami() -> Ohkami {
+        BUILT.fetch_add(1, Ordering::SeqCst);
+        Ohkami::new((
+            "/".GET(|| async {"index"}),
+            "/users/:id".GET(|id: u64| async move {format!("user {id}")}),
+        ))
+    }
+
+    static OHKAMI: WorkerRouter = WorkerRouter::new();
+
+    assert!(!OHKAMI.is_built());
+
+    for i in 0..100 {
+        /* `#[ohkami::worker]` calls this for each request */
+        block_on(OHKAMI.build(|| async {my_ohkami()}));
+
+        let res = respond(&OHKAMI, TestRequest::GET("/"));
+        assert_eq!(res.status, Status::OK);
+
+        let res = respond(&OHKAMI, TestRequest::GET(format!("/users/{i}")));
+        assert_eq!(res.status, Status::OK);
+    }
+    assert_eq!(BUILT.load(Ordering::SeqCst), 1);
+
+    /* building again keeps the first router */
+    block_on(OHKAMI.build(|| async {Ohkami::new(())}));
+    assert_eq!(respond(&OHKAMI, TestRequest::GET("/")).status, Status::OK);
+}
@@ -1,27 +1,62 @@
 #![allow(non_snake_case, unused_mut)]
 
-use super::router::{TrieRouter, RouteSections};
-use crate::fangs::{Handler, IntoHandler};
+use super::router::{TrieRouter, RouteSections, IntoRouteName, HostPattern, canonicalize_route};
+use super::guard::Guard;
+use crate::fangs::{Fangs, Handler, IntoHandler};
 use crate::response::Content;
-use crate::Ohkami;
+use crate::{Ohkami, Method, CustomMethod};
+use std::sync::Arc;
 
 
 macro_rules! Handlers {
-    ($( $method:ident ),*) => {
+    ($( ($method:ident, $method_with:ident) ),*) => {
         pub struct Handlers {
             pub(crate) route: RouteSections,
+            pub(crate) name:  Option<(&'static str, Option<usize>)>,
+            pub(crate) guard: Option<Guard>,
             $(
                 pub(crate) $method: Option<Handler>,
             )*
+            pub(crate) custom: Vec<(CustomMethod, Handler)>,
+            /// Fangs of the whole route by `with(..)`, from inner
+            pub(crate) fangs: Vec<Arc<dyn Fangs>>,
+            /// Fangs of each method by `{METHOD}_with(..)`
+            pub(crate) method_fangs: Vec<(Method, Arc<dyn Fangs>)>,
         }
         
         impl Handlers {
-            pub(crate) fn new(route_str: &'static str) -> Self {
+            pub(crate) fn new(route: RouteSections) -> Self {
                 Self {
-                    route:   RouteSections::from_literal(route_str),
+                    route,
+                    name:    None,
+                    guard:   None,
                     $(
                         $method: None,
                     )*
+                    custom:  Vec::new(),
+                    fangs:   Vec::new(),
+                    method_fangs: Vec::new(),
+                }
+            }
+
+            fn set(&mut self, method: &str, handler: Handler) {
+                match Method::from_name(method) {
+                    $(
+                        Some(Method::$method) => {self.$method.replace(handler);}
+                    )*
+                    Some(Method::Custom(custom)) => match self.custom.iter_mut().find(|(c, _)| *c == custom) {
+                        Some((_, h)) => *h = handler,
+                        None         => self.custom.push((custom, handler)),
+                    }
+                    None => panic!("Ohkami can't handle `{method}` method")
+                }
+            }
+
+            /// Replace the fangs of `method`, which belong to its handler
+            fn set_method_fangs(&mut self, method: Method, fangs: Option<Arc<dyn Fangs>>) {
+                self.method_fangs.retain(|(m, _)| *m != method);
+                if let Some(fangs) = fangs {
+                    self.method_fangs.push((method, fangs));
                 }
             }
         }
@@ -30,18 +65,199 @@ macro_rules! Handlers {
             $(
                 pub fn $method<T>(mut self, handler: impl IntoHandler<T>) -> Self {
                     self.$method.replace(handler.into_handler());
+                    self.set_method_fangs(Method::$method, None);
+                    self
+                }
+
+                /// Register a handler with fangs applied only to it.
+                /// See `with` for how they're ordered.
+                pub fn $method_with<T>(mut self, fangs: impl Fangs + 'static, handler: impl IntoHandler<T>) -> Self {
+                    self.$method.replace(handler.into_handler());
+                    self.set_method_fangs(Method::$method, Some(Arc::new(fangs)));
                     self
                 }
             )*
+
+            /// Register a handler by method name, mainly for extension methods :
+            /// 
+            /// ```
+            /// use ohkami::prelude::*;
+            /// 
+            /// # async fn get_file() -> &'static str {""}
+            /// # async fn propfind() -> &'static str {""}
+            /// Ohkami::new((
+            ///     "/files"
+            ///         .GET(get_file)
+            ///         .method("PROPFIND", propfind),
+            /// ));
+            /// ```
+            /// 
+            /// **Note** : This panics if `method` is `CONNECT` or not a valid method name.
+            pub fn method<T>(mut self, method: &str, handler: impl IntoHandler<T>) -> Self {
+                self.set(method, handler.into_handler());
+                if let Some(method) = Method::from_name(method) {
+                    self.set_method_fangs(method, None);
+                }
+                self
+            }
+
+            /// Name this route to generate its URL by `url_for` of
+            /// `Request` or `Ohkami` :
+            /// 
+            /// ```
+            /// use ohkami::prelude::*;
+            /// 
+            /// # async fn show_user(id: u64) -> String {format!("{id}")}
+            /// let o = Ohkami::new((
+            ///     "/users/:id"
+            ///         .GET(show_user)
+            ///         .name("user"),
+            /// ));
+            /// 
+            /// assert_eq!(o.url_for("user", [&42]).unwrap(), "/users/42");
+            /// ```
+            /// 
+            /// `name` can be a `RouteName<N>` to check the number of params
+            /// when building `Ohkami`.
+            /// 
+            /// **Note** : Names must be unique in the whole `Ohkami`, including nested ones.
+            pub fn name(mut self, name: impl IntoRouteName) -> Self {
+                self.name = Some(name.into_route_name());
+                self
+            }
+
+            /// Handle requests to this route only when `guard` is satisfied,
+            /// to register multiple handlers to the same route :
+            /// 
+            /// ```
+            /// use ohkami::prelude::*;
+            /// use ohkami::guard::content_type;
+            /// 
+            /// # async fn upload_json() -> &'static str {"json"}
+            /// # async fn upload_form() -> &'static str {"form"}
+            /// # let _ =
+            /// Ohkami::new((
+            ///     "/upload"
+            ///         .POST(upload_json)
+            ///         .when(content_type("application/json")),
+            ///     "/upload"
+            ///         .POST(upload_form)
+            ///         .when(content_type("multipart/form-data")),
+            /// ))
+            /// # ;
+            /// ```
+            /// 
+            /// Calling this multiple times requires all the guards to be satisfied.
+            /// Guards are checked after the fangs of the `Ohkami`s and before
+            /// the fangs of this route. See `ohkami::guard` for details.
+            /// 
+            /// **Note** : Guarded routes can't have an explicit `OPTIONS` handler.
+            pub fn when(mut self, guard: Guard) -> Self {
+                self.guard = Some(match self.guard.take() {
+                    None           => guard,
+                    Some(previous) => previous.and(guard),
+                });
+                self
+            }
+
+            /// Apply `fangs` to all the handlers of this route, including
+            /// the default `OPTIONS` one, like `Ohkami::with` does :
+            /// 
+            /// ```
+            /// use ohkami::prelude::*;
+            /// 
+            /// # #[derive(Clone)] struct Auth;
+            /// # impl FangAction for Auth {}
+            /// # #[derive(Clone)] struct Logger;
+            /// # impl FangAction for Logger {}
+            /// # async fn list_articles() -> &'static str {""}
+            /// # async fn create_article() -> &'static str {""}
+            /// # async fn delete_article() -> &'static str {""}
+            /// # let _ =
+            /// Ohkami::with(Logger, (
+            ///     "/articles"
+            ///         .GET(list_articles)
+            ///         .POST_with(Auth, create_article),
+            ///     "/articles/:id"
+            ///         .DELETE(delete_article)
+            ///         .with(Auth),
+            /// ))
+            /// # ;
+            /// ```
+            /// 
+            /// The fangs of a handler are ordered from outer as :
+            /// 
+            /// 1. fangs of the outer `Ohkami`s ( `Logger` here )
+            /// 2. fangs of the route by `with`, the later `with`s outer
+            /// 3. fangs of the method by `{METHOD}_with`
+            pub fn with(mut self, fangs: impl Fangs + 'static) -> Self {
+                self.fangs.push(Arc::new(fangs));
+                self
+            }
         }
     };
-} Handlers! { GET, PUT, POST, PATCH, DELETE }
+} Handlers! {
+    (GET, GET_with), (PUT, PUT_with), (POST, POST_with), (PATCH, PATCH_with),
+    (DELETE, DELETE_with), (HEAD, HEAD_with), (OPTIONS, OPTIONS_with), (TRACE, TRACE_with)
+}
 
 pub struct ByAnother {
     pub(crate) route:  RouteSections,
     pub(crate) ohkami: Ohkami,
 }
 
+/// Route requests by `Host` header to another `Ohkami` :
+/// 
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::Host;
+/// 
+/// # async fn api() -> &'static str {"api"}
+/// # async fn admin() -> &'static str {"admin"}
+/// # async fn tenant(name: String) -> String {name}
+/// # async fn index() -> &'static str {"index"}
+/// # let _ =
+/// Ohkami::new((
+///     Host("api.example.com").By(Ohkami::new((
+///         "/".GET(api),
+///     ))),
+///     Host("admin.example.com").By(Ohkami::new((
+///         "/".GET(admin),
+///     ))),
+///     /* the subdomain is passed as the first path param */
+///     Host("*.example.com").By(Ohkami::new((
+///         "/".GET(tenant),
+///     ))),
+///     /* other hosts */
+///     "/".GET(index),
+/// ))
+/// # ;
+/// ```
+/// 
+/// Hosts are matched case-insensitively and ignoring port, exact ones before
+/// wildcards. A request is handled only by the `Ohkami` of the first matching
+/// host, and `404` / `405` through its fangs when it has no route for that.
+/// 
+/// Requests of the other hosts are handled by the other routes, or responded
+/// `404` by `Ohkami::reject_unknown_hosts`.
+/// 
+/// This can be nested in another `Ohkami` by `"/path".By(..)`, but not in
+/// another `Host(..)`.
+pub struct Host(pub &'static str);
+impl Host {
+    pub fn By(self, another: Ohkami) -> ByHost {
+        match HostPattern::parse(self.0) {
+            Ok(host) => ByHost { host, ohkami: another },
+            Err(e)   => panic!("{e}: `{}`", self.0)
+        }
+    }
+}
+
+pub struct ByHost {
+    pub(crate) host:   HostPattern,
+    pub(crate) ohkami: Ohkami,
+}
+
 pub struct Dir {
     pub(crate) route: &'static str,
     pub(crate) files: Vec<(
@@ -131,7 +347,7 @@ pub struct Dir {
 
 
 macro_rules! Route {
-    ($( $method:ident ),*) => {
+    ($( ($method:ident, $method_with:ident) ),*) => {
         /// Core trait for Ohkami's routing definition.
         /// 
         /// <br>
@@ -166,8 +382,11 @@ macro_rules! Route {
         pub trait Route {
             $(
                 fn $method<T>(self, handler: impl IntoHandler<T>) -> Handlers;
+                fn $method_with<T>(self, fangs: impl Fangs + 'static, handler: impl IntoHandler<T>) -> Handlers;
             )*
 
+            fn method<T>(self, method: &str, handler: impl IntoHandler<T>) -> Handlers;
+
             fn By(self, another: Ohkami) -> ByAnother;
 
             fn Dir(self, static_files_dir_path: &'static str) -> Dir;
@@ -176,12 +395,19 @@ macro_rules! Route {
         impl Route for &'static str {
             $(
                 fn $method<T>(self, handler: impl IntoHandler<T>) -> Handlers {
-                    let mut handlers = Handlers::new(self);
+                    let mut handlers = Handlers::new(RouteSections::from_literal(self));
                     handlers.$method.replace(handler.into_handler());
                     handlers
                 }
+                fn $method_with<T>(self, fangs: impl Fangs + 'static, handler: impl IntoHandler<T>) -> Handlers {
+                    Handlers::new(RouteSections::from_literal(self)).$method_with(fangs, handler)
+                }
             )*
 
+            fn method<T>(self, method: &str, handler: impl IntoHandler<T>) -> Handlers {
+                Handlers::new(RouteSections::from_literal(self)).method(method, handler)
+            }
+
             fn By(self, another: Ohkami) -> ByAnother {
                 ByAnother {
                     route:  RouteSections::from_literal(self),
@@ -203,7 +429,10 @@ macro_rules! Route {
             }
         }
     };
-} Route! { GET, PUT, POST, PATCH, DELETE }
+} Route! {
+    (GET, GET_with), (PUT, PUT_with), (POST, POST_with), (PATCH, PATCH_with),
+    (DELETE, DELETE_with), (HEAD, HEAD_with), (OPTIONS, OPTIONS_with), (TRACE, TRACE_with)
+}
 
 
 trait RoutingItem {
@@ -221,12 +450,18 @@ trait RoutingItem {
         }
     }
 
+    impl RoutingItem for ByHost {
+        fn apply(self, router: &mut TrieRouter) {
+            router.route_host(self.host, self.ohkami.into_router())
+        }
+    }
+
     impl RoutingItem for Dir {
         fn apply(self, router: &mut TrieRouter) {
             #[derive(Clone)]
             struct StaticFileHandler {
                 mime:     &'static str,
-                content:  std::sync::Arc<Vec<u8>>,
+                content:  std::sync::Arc<[u8]>,
             }
             const _: () = {
                 impl StaticFileHandler {
@@ -252,26 +487,24 @@ trait RoutingItem {
                             return Err(format!("[.Dir] got `{filename}`: Ohkami doesn't support non UTF-8 text file"))
                         }
 
-                        Ok(Self { mime, content:std::sync::Arc::new(content) })
+                        Ok(Self { mime, content:content.into() })
                     }
                 }
                 
                 impl IntoHandler<std::fs::File> for StaticFileHandler {
                     fn into_handler(self) -> Handler {
-                        let this: &'static StaticFileHandler
-                            = Box::leak(Box::new(self));
-
-                        Handler::new(|_| Box::pin(async {
-                            let mut res = crate::Response::OK();
-                            {
-                                res.headers.set().ContentType(this.mime);
-                                res.content = Content::Payload({
-                                    let content: &'static [u8] = &this.content;
-                                    content.into()
-                                });
-                            }
-                            res
-                        }))
+                        Handler::new(move |_| {
+                            /* shared, not referenced, for the router to be droppable */
+                            let (mime, content) = (self.mime, self.content.clone());
+                            Box::pin(async move {
+                                let mut res = crate::Response::OK();
+                                {
+                                    res.headers.set().ContentType(mime);
+                                    res.content = Content::Payload(content.into());
+                                }
+                                res
+                            })
+                        })
                     }
                 }
             };
@@ -280,13 +513,9 @@ trait RoutingItem {
             println!{ "[Dir] .files = {:#?}", self.files }
 
             let mut register = |path: Vec<String>, handler: StaticFileHandler| router.register_handlers(
-                Handlers::new(Box::leak({
-                    let base_path = self.route.trim_end_matches('/').to_string();
-                    match &*path.join("/") {
-                        ""   => if !base_path.is_empty() {base_path} else {"/".into()},
-                        some => base_path + "/" + some,
-                    }
-                }.into_boxed_str())).GET(handler)
+                Handlers::new(RouteSections::from_string(
+                    canonicalize_route(&format!("{}/{}", self.route, path.join("/")))
+                )).GET(handler)
             );
 
             for (mut path, file) in self.files {
@@ -0,0 +1,141 @@
+//! Guards to select a handler among ones of the same route by request headers,
+//! given to `.when(..)` :
+//!
+//! ```
+//! use ohkami::prelude::*;
+//! use ohkami::guard::{accept, header};
+//!
+//! # async fn users_v1() -> &'static str {"v1"}
+//! # async fn users_v2() -> &'static str {"v2"}
+//! # async fn users_beta() -> &'static str {"beta"}
+//! # let _ =
+//! Ohkami::new((
+//!     "/users"
+//!         .GET(users_beta)
+//!         .when(header("X-Beta", "true")),
+//!     "/users"
+//!         .GET(users_v2)
+//!         .when(accept("application/vnd.acme.v2+json")),
+//!     "/users"
+//!         .GET(users_v1),
+//! ))
+//! # ;
+//! ```
+//!
+//! Guards are tried in registration order and the first satisfied one's handler
+//! handles the request. The one without guard, if any, is the fallback.
+//!
+//! A request goes through, in order :
+//!
+//! 1. fangs of the `Ohkami`s, which run for every guard and may rewrite the headers guards see
+//! 2. the guards, choosing a handler
+//! 3. fangs given to the chosen one by `with` or `{METHOD}_with`, then the handler
+//!
+//! When no guard is satisfied and there's no fallback, the response is
+//! `415 Unsupported Media Type` if all the guards are `content_type`,
+//! or else `406 Not Acceptable`.
+
+use crate::{Request, Response, Status};
+use crate::request::RequestHeader;
+use crate::fangs::{BoxedFPC, FangProcCaller, Handler};
+use std::sync::Arc;
+
+
+#[derive(Clone)]
+pub struct Guard {
+    /// Status when rejected by every guard
+    pub(crate) rejection: Status,
+    check: Arc<dyn Fn(&Request) -> bool + Send + Sync>,
+}
+
+const _: () = {
+    impl std::fmt::Debug for Guard {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Guard").field("rejection", &self.rejection).finish_non_exhaustive()
+        }
+    }
+};
+
+impl Guard {
+    pub(crate) fn and(self, another: Guard) -> Guard {
+        let (this, that) = (self.check, another.check);
+        Guard {
+            rejection: if self.rejection == another.rejection {self.rejection} else {Status::NotAcceptable},
+            check: Arc::new(move |req| this(req) && that(req)),
+        }
+    }
+}
+
+/// Satisfied when the header `name` (case-insensitive) has exactly `value`
+pub fn header(name: &'static str, value: &'static str) -> Guard {
+    let standard = RequestHeader::from_bytes(name.to_ascii_lowercase().as_bytes());
+    Guard {
+        rejection: Status::NotAcceptable,
+        check: Arc::new(move |req| match standard {
+            Some(header) => req.headers.get(header).is_some_and(|v| v.trim() == value),
+            None => req.headers.iter().any(|(n, v)| n.eq_ignore_ascii_case(name) && v.trim() == value),
+        }),
+    }
+}
+
+/// Satisfied when `Accept` accepts `media_type`, including by `type/*` or `*/*`.
+/// A request without `Accept` accepts any media type.
+pub fn accept(media_type: &'static str) -> Guard {
+    let (main_type, _) = media_type.split_once('/')
+        .unwrap_or_else(|| panic!("Invalid media type: `{media_type}`"));
+    Guard {
+        rejection: Status::NotAcceptable,
+        check: Arc::new(move |req| match req.headers.Accept() {
+            None         => true,
+            Some(accept) => accept.split(',').any(|range| {
+                let mut range = range.split(';').map(str::trim);
+                let media_range = range.next().unwrap_or_default();
+                let rejected = range.any(|param| param.split_once('=').is_some_and(|(k, q)|
+                    k.trim().eq_ignore_ascii_case("q") && q.trim().parse::<f32>().is_ok_and(|q| q == 0.)
+                ));
+                !rejected && (
+                    media_range == "*/*" ||
+                    media_range.eq_ignore_ascii_case(media_type) ||
+                    media_range.strip_suffix("/*").is_some_and(|t| t.eq_ignore_ascii_case(main_type))
+                )
+            }),
+        }),
+    }
+}
+
+/// Satisfied when the media type of `Content-Type` is `media_type`,
+/// ignoring parameters like `charset`
+pub fn content_type(media_type: &'static str) -> Guard {
+    Guard {
+        rejection: Status::UnsupportedMediaType,
+        check: Arc::new(move |req| req.headers.ContentType().is_some_and(|ct|
+            ct.split(';').next().unwrap_or_default().trim().eq_ignore_ascii_case(media_type)
+        )),
+    }
+}
+
+/// Handler dispatching to the first one whose guard is satisfied, or else to `fallback`
+pub(crate) fn dispatch(guarded: Vec<(Guard, Handler)>, fallback: Option<Handler>) -> Handler {
+    let rejection = if guarded.iter().all(|(g, _)| g.rejection == Status::UnsupportedMediaType) {
+        Status::UnsupportedMediaType
+    } else {
+        Status::NotAcceptable
+    };
+    let guarded = guarded.into_iter()
+        .map(|(guard, handler)| (guard.check, handler.into()))
+        .collect::<Box<[(_, BoxedFPC)]>>();
+    let fallback: Option<BoxedFPC> = fallback.map(Into::into);
+
+    Handler::new(move |req| {
+        let handler = match guarded.iter().find(|(check, _)| check(req)) {
+            Some((_, handler)) => Some(handler.clone()),
+            None => fallback.clone(),
+        };
+        Box::pin(async move {
+            match handler {
+                Some(handler) => handler.call_bite(req).await,
+                None => Response::of(rejection),
+            }
+        })
+    })
+}
@@ -0,0 +1,134 @@
+use super::Ohkami;
+use super::router::RadixRouter;
+use std::sync::{Arc, RwLock, PoisonError};
+
+#[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+use crate::{__rt__, Session};
+
+
+/// Shared handle to the router of an `Ohkami`, by which the routes can be
+/// replaced atomically while serving :
+/// 
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::RouterHandle;
+/// 
+/// # async fn hello() -> &'static str {"Hello"}
+/// # async fn plugin() -> &'static str {"plugin"}
+/// #[tokio::main]
+/// async fn main() {
+///     let handle = RouterHandle::new(Ohkami::new((
+///         "/".GET(hello),
+///     )));
+/// 
+///     tokio::spawn({
+///         let handle = handle.clone();
+///         async move {
+///             tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+///             handle.replace(Ohkami::new((
+///                 "/".GET(hello),
+///                 "/plugin".GET(plugin),
+///             )));
+///         }
+///     });
+/// 
+///     handle.howl("localhost:3000").await
+/// }
+/// ```
+/// 
+/// Requests being handled when `replace` is called are completed by the old
+/// router, and it's dropped after all of them have finished.
+#[derive(Clone)]
+pub struct RouterHandle(Arc<RwLock<Arc<RadixRouter>>>);
+
+impl RouterHandle {
+    pub fn new(ohkami: Ohkami) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(ohkami.into_router().into_radix()))))
+    }
+
+    /// Replace the routes by `ohkami`'s. The new router is built before
+    /// swapping, so requests are never blocked by building.
+    pub fn replace(&self, ohkami: Ohkami) {
+        let new = Arc::new(ohkami.into_router().into_radix());
+        let old = std::mem::replace(&mut *self.0.write().unwrap_or_else(PoisonError::into_inner), new);
+        drop(old/* outside the lock */)
+    }
+
+    /// Current router, used for each request
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="testing"))]
+    #[inline]
+    pub(crate) fn load(&self) -> Arc<RadixRouter> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+    /// Start serving at `address` like `Ohkami::howl`, with the routes
+    /// replaceable by this handle's clones.
+    pub async fn howl(self, address: impl __rt__::ToSocketAddrs) {
+        let router = self;
+        let listener = __rt__::TcpListener::bind(address).await.expect("Failed to bind TCP listener: {e}");
+        
+        #[cfg(all(feature="rt_tokio", feature="graceful"))] {
+            let ctrl_c = tokio::signal::ctrl_c();
+
+            let (ctrl_c_tx, ctrl_c_rx) = tokio::sync::watch::channel(());
+            __rt__::task::spawn(async {
+                ctrl_c.await.expect("Something was wrong around Ctrl-C");
+                drop(ctrl_c_rx);
+            });
+
+            let (close_tx, close_rx) = tokio::sync::watch::channel(());
+            loop {
+                tokio::select! {
+                    accept = listener.accept() => {
+                        crate::DEBUG!("Accepted {accept:#?}");
+
+                        let Ok((connection, _)) = accept else {continue};
+                        let session = Session::new(router.clone(), connection);
+
+                        let close_rx = close_rx.clone();
+                        __rt__::task::spawn(async {
+                            session.manage().await;
+                            drop(close_rx)
+                        });
+                    },
+                    _ = ctrl_c_tx.closed() => {
+                        crate::DEBUG!("Recieved Ctrl-C, trying graceful shutdown");
+                        drop(listener);
+                        break
+                    }
+                }
+            }
+
+            crate::DEBUG!("Waiting {} session(s) to finish...", close_tx.receiver_count());
+            drop(close_rx);
+            close_tx.closed().await;
+        }
+        #[cfg(all(feature="rt_tokio", not(feature="graceful")))] {
+            loop {
+                let Ok((connection, _)) = listener.accept().await else {continue};
+
+                __rt__::task::spawn({
+                    Session::new(
+                        router.clone(),
+                        connection,
+                    ).manage()
+                });
+            }
+        }
+        #[cfg(feature="rt_async-std")] {
+            use async_std::stream::StreamExt as _/* .next() */;
+
+            while let Some(connection) = listener.incoming().next().await {
+                let Ok(connection) = connection else {continue};
+
+                __rt__::task::spawn({
+                    Session::new(
+                        router.clone(),
+                        connection,
+                    ).manage()
+                });
+            }
+        }
+    }
+}
@@ -2,18 +2,28 @@
 
 #[cfg(test)]
 mod _test;
+#[cfg(test)]
+mod _test_worker;
 
 pub(crate) mod build;
 pub(crate) mod router;
 
-pub use build::{Route, Routes};
+mod handle;
+pub use handle::RouterHandle;
+
+pub mod guard;
+
+pub use build::{Route, Routes, Host};
+pub use router::{RouteTable, RouteInfo, RouteName, IntoRouteName, UrlError, PathPolicy, Panic};
 
 use crate::fangs::Fangs;
+use crate::request::{States, RejectionMapper};
+use crate::{IntoResponse, Rejection};
 use std::sync::Arc;
 use router::TrieRouter;
 
 #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
-use crate::{__rt__, Session};
+use crate::__rt__;
 
 
 /// # Ohkami - a robust wolf who serves your web app
@@ -105,13 +115,22 @@ use crate::{__rt__, Session};
 /// <br>
 /// 
 /// #### handler schema：
-/// `async ({path_params}?, {FromRequest type}s...) -> {IntoResponse type}`
+/// `async ({path_params}?, {FromRequest type}s...) -> {IntoResponse type}`\
+/// (up to 16 `FromRequest` or `AsyncFromRequest` items)
 /// 
 /// #### path_params：
 /// A tuple of types that implement `FromParam` trait.\
 /// If the path contains only one parameter, then you can omit the tuple.\
 /// (In current ohkami, at most *2* path params can be handled.)
 /// 
+/// A param can have a constraint checked in routing, like `/users/:id<u64>`
+/// or `/files/:name{[a-z]+\.png}`. A segment not satisfying it moves on to
+/// the next candidate route (e.g. `/users/:name`) instead of being an error.
+/// 
+/// A segment can also mix static text and params, like `/files/:name.:ext`,
+/// `/v:version/users` or `/@:username`. The static text between params is
+/// searched from the end, so `archive.tar.gz` is `archive.tar` and `gz`.
+/// 
 /// <br>
 /// 
 /// ```
@@ -133,11 +152,45 @@ use crate::{__rt__, Session};
 ///     todo!()
 /// }
 /// ```
+/// 
+/// <br>
+/// 
+/// #### unmatched requests：
+/// A request to no route is responded with `404 Not Found`, and a request
+/// to an existing route by a method it doesn't handle is responded with
+/// `405 Method Not Allowed` with `Allow` header listing the available methods.\
+/// Both of them go through the fangs, so the body can be customized by a fang :
+/// 
+/// ```
+/// use ohkami::prelude::*;
+/// 
+/// #[derive(Clone)]
+/// struct CustomMethodNotAllowed;
+/// impl FangAction for CustomMethodNotAllowed {
+///     async fn back<'a>(&'a self, res: &'a mut Response) {
+///         if res.status == Status::MethodNotAllowed {
+///             res.set_text("This method is not allowed here")
+///         }
+///     }
+/// }
+/// 
+/// fn my_ohkami() -> Ohkami {
+///     Ohkami::with(CustomMethodNotAllowed, (
+///         "/hello".GET(|| async {"Hello!"}),
+///     ))
+/// }
+/// ```
 pub struct Ohkami {
     pub(crate) routes: TrieRouter,
 
     /// apply just before merged to another or called `howl`
     pub(crate) fangs:  Option<Arc<dyn Fangs>>,
+
+    /// apply at the same time as `fangs`
+    pub(crate) states: States,
+
+    /// apply at the same time as `fangs`
+    pub(crate) rejection_mapper: Option<Arc<RejectionMapper>>,
 }
 
 
@@ -181,6 +234,8 @@ impl Ohkami {
         Self {
             routes: router,
             fangs:  None,
+            states: States::new(),
+            rejection_mapper: None,
         }
     }
 
@@ -227,7 +282,187 @@ impl Ohkami {
         Self {
             routes: router,
             fangs:  Some(Arc::new(fangs)),
+            states: States::new(),
+            rejection_mapper: None,
+        }
+    }
+
+    /// Set how to handle non-canonical request paths like `/users/`,
+    /// `/api//users` or `/admin/../users`. See `PathPolicy` for details.
+    /// 
+    /// The default is `PathPolicy::lenient()`.
+    /// 
+    /// **Note** : This is only for the `Ohkami` that `howl`s (or `test`s),
+    /// and setting it on a nested one panics.
+    pub fn path_policy(mut self, policy: PathPolicy) -> Self {
+        self.routes.path_policy = Some(policy);
+        self
+    }
+
+    /// Respond `404 Not Found` to requests whose `Host` matches no `Host(..)`,
+    /// instead of handling them by the other routes (the default) :
+    /// 
+    /// ```
+    /// use ohkami::prelude::*;
+    /// use ohkami::Host;
+    /// 
+    /// # let _ =
+    /// Ohkami::new((
+    ///     Host("api.example.com").By(Ohkami::new((
+    ///         "/".GET(|| async {"api"}),
+    ///     ))),
+    /// )).reject_unknown_hosts()
+    /// # ;
+    /// ```
+    /// 
+    /// **Note** : Like `path_policy`, this is only for the `Ohkami` that `howl`s
+    /// (or `test`s), and setting it on a nested one panics.
+    pub fn reject_unknown_hosts(mut self) -> Self {
+        self.routes.reject_unknown_hosts = true;
+        self
+    }
+
+    /// Build the response to a request whose handler or fangs panicked,
+    /// instead of the default `500 Internal Server Error` :
+    /// 
+    /// ```
+    /// use ohkami::prelude::*;
+    /// 
+    /// # let _ =
+    /// Ohkami::new((
+    ///     "/".GET(|| async {"Hello!"}),
+    /// )).on_panic(|_| Response::InternalServerError().with_json(
+    ///     ohkami::serde::json::json!({"error": "internal"})
+    /// ))
+    /// # ;
+    /// ```
+    /// 
+    /// A panic is caught also while the handler's future is being polled,
+    /// so that the client always gets a response. The response goes through
+    /// the fangs for the path like `404 Not Found` does, so fangs like `CORS`
+    /// still apply to it.
+    /// 
+    /// **Note** : Like `path_policy`, this is only for the `Ohkami` that `howl`s
+    /// (or `test`s), and setting it on a nested one panics.
+    pub fn on_panic<R: IntoResponse>(mut self,
+        hook: impl Fn(&Panic) -> R + Send + Sync + 'static
+    ) -> Self {
+        self.routes.panic_hooks.respond = Some(Arc::new(move |panic| hook(panic).into_response()));
+        self
+    }
+
+    /// Report a panic in a handler or fangs, with the request's method and path,
+    /// instead of the default warning to stderr :
+    /// 
+    /// ```
+    /// use ohkami::prelude::*;
+    /// 
+    /// # let _ =
+    /// Ohkami::new((
+    ///     "/".GET(|| async {"Hello!"}),
+    /// )).report_panic(|panic| eprintln!(
+    ///     "[{} {}] {}", panic.method, panic.path, panic.message().unwrap_or("panicked")
+    /// ))
+    /// # ;
+    /// ```
+    /// 
+    /// `panic.path` is the request path, decoded lossily if it's not UTF-8.
+    /// This is called before the hook of `on_panic`.
+    /// 
+    /// **Note** : Like `path_policy`, this is only for the `Ohkami` that `howl`s
+    /// (or `test`s), and setting it on a nested one panics.
+    pub fn report_panic(mut self,
+        reporter: impl Fn(&Panic) + Send + Sync + 'static
+    ) -> Self {
+        self.routes.panic_hooks.report = Some(Arc::new(reporter));
+        self
+    }
+
+    /// Share `state` among the handlers of this `Ohkami`, including
+    /// the ones of nested `Ohkami`s, retrieved by `State<'_, T>` extractor.
+    /// 
+    /// A nested `Ohkami` can add its own states, or override the outer
+    /// one of the same type within it :
+    /// 
+    /// ```
+    /// use ohkami::prelude::*;
+    /// use ohkami::State;
+    /// 
+    /// struct Greeting(&'static str);
+    /// 
+    /// async fn greet(g: State<'_, Greeting>) -> &'static str {
+    ///     g.0
+    /// }
+    /// 
+    /// # let _ =
+    /// Ohkami::new((
+    ///     "/hello".GET(greet),
+    ///     "/ja".By(Ohkami::new((
+    ///         "/hello".GET(greet),
+    ///     )).with_state(Greeting("こんにちは"))),
+    /// )).with_state(Greeting("Hello"))
+    /// # ;
+    /// ```
+    /// 
+    /// Calling this twice with the same type, the latter one is used.
+    pub fn with_state<T: Send + Sync + 'static>(mut self, state: T) -> Self {
+        self.states.insert(state);
+        self
+    }
+
+    /// Build the response of the failed extraction of handlers' arguments
+    /// ( `FromRequest`, `FromParam`, `#[Payload]`, `#[Query]`, `#[Headers]`, ... )
+    /// by `mapper`, instead of the default `400 Bad Request` with text.
+    /// See `Rejection` for an example.
+    ///
+    /// Like `with_state`, this applies to the nested `Ohkami`s too, and
+    /// a nested `Ohkami` can override it by its own mapper.
+    pub fn map_rejection<R: IntoResponse>(mut self,
+        mapper: impl Fn(Rejection) -> R + Send + Sync + 'static
+    ) -> Self {
+        self.rejection_mapper = Some(Arc::new(RejectionMapper(Box::new(move |rejection| mapper(rejection).into_response()))));
+        self
+    }
+
+    /// Registered routes, including the ones of nested `Ohkami`s :
+    /// 
+    /// ```
+    /// use ohkami::prelude::*;
+    /// 
+    /// # async fn hc() -> &'static str {""}
+    /// # async fn get_user() -> &'static str {""}
+    /// # async fn update_user() -> &'static str {""}
+    /// # #[derive(Clone)] struct Auth;
+    /// # impl FangAction for Auth {}
+    /// let o = Ohkami::new((
+    ///     "/hc".GET(hc),
+    ///     "/api".By(Ohkami::with(Auth, (
+    ///         "/users/:id"
+    ///             .GET(get_user)
+    ///             .PATCH(update_user),
+    ///     ))),
+    /// ));
+    /// 
+    /// let routes = o.routes();
+    /// assert_eq!(routes.len(), 2);
+    /// 
+    /// let users = routes.get("/api/users/:id").unwrap();
+    /// assert_eq!(users.methods(), [Method::GET, Method::PATCH]);
+    /// assert_eq!(users.fangs(), 1);
+    /// 
+    /// println!("{routes}");
+    /// ```
+    /// 
+    /// Setting environment variable `OHKAMI_PRINT_ROUTES=1`, the table
+    /// is printed at `howl`.
+    pub fn routes(&self) -> RouteTable {
+        let mut table = self.routes.table();
+        if let Some(fangs) = &self.fangs {
+            for info in &mut table {
+                info.fangs += fangs.count();
+            }
         }
+        RouteTable(table)
     }
 
     #[cfg(any(feature="rt_tokio", feature="rt_async-std"))]
@@ -241,6 +476,9 @@ impl Ohkami {
     /// *note* : Keep-Alive timeout is 42 seconds and this is not
     /// configureable by user (it'll be in future version...)
     /// 
+    /// Setting environment variable `OHKAMI_PRINT_ROUTES=1`, the route table
+    /// (see `Ohkami::routes`) is printed before starting.
+    /// 
     /// <br>
     /// 
     /// *example.rs*
@@ -265,71 +503,11 @@ impl Ohkami {
     /// }
     /// ```
     pub async fn howl(self, address: impl __rt__::ToSocketAddrs) {
-        let router = Arc::new(self.into_router().into_radix());
-        let listener = __rt__::TcpListener::bind(address).await.expect("Failed to bind TCP listener: {e}");
-        
-        #[cfg(all(feature="rt_tokio", feature="graceful"))] {
-            let ctrl_c = tokio::signal::ctrl_c();
-
-            let (ctrl_c_tx, ctrl_c_rx) = tokio::sync::watch::channel(());
-            __rt__::task::spawn(async {
-                ctrl_c.await.expect("Something was wrong around Ctrl-C");
-                drop(ctrl_c_rx);
-            });
-
-            let (close_tx, close_rx) = tokio::sync::watch::channel(());
-            loop {
-                tokio::select! {
-                    accept = listener.accept() => {
-                        crate::DEBUG!("Accepted {accept:#?}");
-
-                        let Ok((connection, _)) = accept else {continue};
-                        let session = Session::new(router.clone(), connection);
-
-                        let close_rx = close_rx.clone();
-                        __rt__::task::spawn(async {
-                            session.manage().await;
-                            drop(close_rx)
-                        });
-                    },
-                    _ = ctrl_c_tx.closed() => {
-                        crate::DEBUG!("Recieved Ctrl-C, trying graceful shutdown");
-                        drop(listener);
-                        break
-                    }
-                }
-            }
-
-            crate::DEBUG!("Waiting {} session(s) to finish...", close_tx.receiver_count());
-            drop(close_rx);
-            close_tx.closed().await;
-        }
-        #[cfg(all(feature="rt_tokio", not(feature="graceful")))] {
-            loop {
-                let Ok((connection, _)) = listener.accept().await else {continue};
-
-                __rt__::task::spawn({
-                    Session::new(
-                        router.clone(),
-                        connection,
-                    ).manage()
-                });
-            }
-        }
-        #[cfg(feature="rt_async-std")] {
-            use async_std::stream::StreamExt as _/* .next() */;
-
-            while let Some(connection) = listener.incoming().next().await {
-                let Ok(connection) = connection else {continue};
-
-                __rt__::task::spawn({
-                    Session::new(
-                        router.clone(),
-                        connection,
-                    ).manage()
-                });
-            }
+        if std::env::var("OHKAMI_PRINT_ROUTES").is_ok_and(|v| !matches!(&*v, "" | "0" | "false")) {
+            println!("{}", self.routes());
         }
+
+        RouterHandle::new(self).howl(address).await
     }
 
     #[cfg(feature="rt_worker")]
@@ -338,6 +516,49 @@ impl Ohkami {
         req: ::worker::Request,
         env: ::worker::Env,
         ctx: ::worker::Context,
+    ) -> ::worker::Response {
+        let router = WorkerRouter::new();
+        router.build(|| async {self}).await;
+        router.handle(req, env, ctx).await
+    }
+}
+
+/// Router compiled once and cached in a Workers isolate, used by `#[ohkami::worker]`
+#[cfg(feature="rt_worker")]
+#[doc(hidden)]
+pub struct WorkerRouter(std::sync::OnceLock<router::RadixRouter>);
+
+#[cfg(feature="rt_worker")]
+impl Default for WorkerRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature="rt_worker")]
+impl WorkerRouter {
+    pub const fn new() -> Self {
+        Self(std::sync::OnceLock::new())
+    }
+
+    pub fn is_built(&self) -> bool {
+        self.0.get().is_some()
+    }
+
+    /// Build the router of the `Ohkami` generated by `ohkami` if not built yet,
+    /// without calling `ohkami` when already built.
+    /// (When concurrent requests build it at the same time, only the first one is kept.)
+    pub async fn build<Fut: std::future::Future<Output = Ohkami>>(&self, ohkami: impl FnOnce() -> Fut) {
+        if !self.is_built() {
+            let router = ohkami().await.into_router().into_radix();
+            let _ = self.0.set(router);
+        }
+    }
+
+    pub async fn handle(&self,
+        req: ::worker::Request,
+        env: ::worker::Env,
+        ctx: ::worker::Context,
     ) -> ::worker::Response {
         #[cfg(feature="DEBUG")] ::worker::console_debug!("Called `#[ohkami::worker]`; req: {req:?}");
 
@@ -352,14 +573,7 @@ impl Ohkami {
 
         let ohkami_res = match take_over {
             Ok(()) => {#[cfg(feature="DEBUG")] ::worker::console_debug!("`take_over` succeed");
-
-                let router = self.into_router();
-                #[cfg(feature="DEBUG")] ::worker::console_debug!("Done `Ohkami::into_router`");
-
-                let router = router.into_radix();
-                #[cfg(feature="DEBUG")] ::worker::console_debug!("Done `TrieRouter::into_radix` (without compressions)");
-                
-                let mut res = router.handle(&mut ohkami_req).await;
+                let mut res = self.respond(&mut ohkami_req).await;
                 res.complete();
                 res
             }
@@ -374,16 +588,33 @@ impl Ohkami {
 
         res
     }
+
+    /// Handle a request taken over from `worker::Request` by the cached router
+    pub(crate) async fn respond(&self, req: &mut crate::Request) -> crate::Response {
+        let Some(router) = self.0.get() else {
+            crate::warning!("`WorkerRouter::handle` is called before built");
+            return crate::Response::InternalServerError()
+        };
+        router.handle(req).await
+    }
 }
 
 impl Ohkami {
     pub(crate) fn into_router(self) -> TrieRouter {
-        let Self { routes: mut router, fangs } = self;
+        let Self { routes: mut router, fangs, states, rejection_mapper } = self;
 
         if let Some(fangs) = fangs {
             router.apply_fangs(router.id(), fangs);
         }
 
+        if !states.is_empty() {
+            router.apply_states(&states);
+        }
+
+        if let Some(mapper) = rejection_mapper {
+            router.apply_rejection_mapper(mapper);
+        }
+
         #[cfg(feature="DEBUG")]
         println!("{router:#?}");
 
@@ -0,0 +1,107 @@
+/// Pattern of `Host` to route requests by, given to `Host(..)`
+///
+/// - `api.example.com` ... exactly this host
+/// - `*.example.com` ... any single-label subdomain of `example.com`,
+///   captured as the first path param
+///
+/// Both are matched case-insensitively, ignoring port.
+#[derive(Clone, PartialEq)]
+pub(crate) enum HostPattern {
+    Exact(String),
+    /// `.example.com` for `*.example.com`
+    Wildcard(String),
+}
+
+impl HostPattern {
+    pub(crate) fn parse(pattern: &'static str) -> Result<Self, String> {
+        fn validate_host(host: &str) -> Result<(), String> {
+            if host.is_empty() || host.split('.').any(|label| label.is_empty()
+                || label.starts_with('-') || label.ends_with('-')
+                || !label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+            ) {
+                return Err(String::from("Invalid host pattern"))
+            }
+            Ok(())
+        }
+
+        match pattern.strip_prefix("*.") {
+            Some(domain) => {
+                validate_host(domain)?;
+                Ok(Self::Wildcard(format!(".{}", domain.to_ascii_lowercase())))
+            }
+            None => {
+                validate_host(pattern)?;
+                Ok(Self::Exact(pattern.to_ascii_lowercase()))
+            }
+        }
+    }
+
+    pub(crate) fn is_wildcard(&self) -> bool {
+        matches!(self, Self::Wildcard(_))
+    }
+
+    /// `Some(Some(subdomain))` for wildcard, `Some(None)` for exact match
+    pub(crate) fn matches<'h>(&self, host: &'h str) -> Option<Option<&'h str>> {
+        let host = strip_port(host);
+        let host = host.strip_suffix('.').unwrap_or(host);
+
+        match self {
+            Self::Exact(exact) => {
+                host.eq_ignore_ascii_case(exact).then_some(None)
+            }
+            Self::Wildcard(domain) => {
+                let subdomain_len = host.len().checked_sub(domain.len())?;
+                let (subdomain, rest) = (host.get(..subdomain_len)?, host.get(subdomain_len..)?);
+                (!subdomain.is_empty() && !subdomain.contains('.') && rest.eq_ignore_ascii_case(domain))
+                    .then_some(Some(subdomain))
+            }
+        }
+    }
+}
+
+fn strip_port(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+        _ => host,
+    }
+}
+
+const _: () = {
+    impl std::fmt::Debug for HostPattern {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Exact(host)      => f.write_str(host),
+                Self::Wildcard(domain) => write!(f, "*{domain}"),
+            }
+        }
+    }
+
+    impl std::fmt::Display for HostPattern {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            std::fmt::Debug::fmt(self, f)
+        }
+    }
+};
+
+
+#[cfg(test)]
+#[test] fn test_host_pattern() {
+    let api = HostPattern::parse("api.example.com").unwrap();
+    assert_eq!(api.matches("api.example.com"),      Some(None));
+    assert_eq!(api.matches("API.Example.com:8080"), Some(None));
+    assert_eq!(api.matches("api.example.com."),     Some(None));
+    assert_eq!(api.matches("admin.example.com"),    None);
+    assert_eq!(api.matches("v1.api.example.com"),   None);
+
+    let any = HostPattern::parse("*.example.com").unwrap();
+    assert_eq!(any.matches("tenant1.example.com"),      Some(Some("tenant1")));
+    assert_eq!(any.matches("Tenant1.EXAMPLE.com:3000"), Some(Some("Tenant1")));
+    assert_eq!(any.matches("example.com"),              None);
+    assert_eq!(any.matches(".example.com"),             None);
+    assert_eq!(any.matches("a.b.example.com"),          None);
+    assert_eq!(any.matches("a.example.org"),            None);
+
+    for invalid in ["", "*", "*.", "a..com", "-a.com", "a_b.com", "a.*.com", "/api"] {
+        assert!(HostPattern::parse(invalid).is_err(), "`{invalid}` should be invalid");
+    }
+}
@@ -4,18 +4,68 @@ mod trie;
 pub(crate) use trie::TrieRouter;
 
 mod radix;
-pub(crate) use radix::RadixRouter;
+pub(crate) use radix::{RadixRouter, RouteData, RouteRef};
 
-use std::{collections::VecDeque, iter::Peekable, str::Chars};
+mod table;
+pub use table::{RouteTable, RouteInfo};
+
+mod url;
+pub use url::{RouteName, IntoRouteName, UrlError};
+pub(crate) use url::Urls;
+
+mod host;
+pub(crate) use host::HostPattern;
+
+mod normalize;
+pub use normalize::PathPolicy;
+pub(crate) use normalize::canonicalize_route;
+
+mod panic;
+pub use panic::Panic;
+pub(crate) use panic::PanicHooks;
+
+use std::{borrow::Cow, collections::VecDeque, iter::Peekable, str::Chars};
+
+
+/// Methods available for a route having handlers of `methods`,
+/// used for `Allow` and `Access-Control-Allow-Methods`
+pub(super) fn available_methods(mut methods: Vec<crate::Method>) -> Vec<crate::Method> {
+    use crate::Method;
+
+    if methods.contains(&Method::GET) && !methods.contains(&Method::HEAD) {
+        methods.push(Method::HEAD)
+    }
+    methods.push(Method::OPTIONS);
+    methods
+}
+
+/// Route template of `path` in an `Ohkami` nested at `prefix`
+pub(super) fn join_route(prefix: &str, path: &str) -> String {
+    match (prefix.trim_end_matches('/'), path) {
+        ("",     path) => path.into(),
+        (prefix, "/")  => prefix.into(),
+        (prefix, path) => format!("{prefix}{path}"),
+    }
+}
 
 
 #[derive(Clone, Debug)]
 pub struct RouteSections {
-    litreral: &'static str,
+    litreral: Cow<'static, str>,
     sections: VecDeque<RouteSection>,
 }
 impl RouteSections {
     pub(crate) fn from_literal(route: &'static str) -> Self {
+        Self::parse(Cow::Borrowed(route))
+    }
+
+    /// Route built at runtime, like the one of a file of `Dir`,
+    /// held by this itself to be freed with the router
+    pub(crate) fn from_string(route: String) -> Self {
+        Self::parse(Cow::Owned(route))
+    }
+
+    fn parse(route: Cow<'static, str>) -> Self {
         if route.is_empty() {panic!("Found an empty route: `{route}`")}
         if !route.starts_with('/') {panic!("Routes must start with '/': `{route}`")}
 
@@ -26,9 +76,14 @@ impl RouteSections {
             }
         }
 
+        let pieces: Vec<Cow<'static, [u8]>> = match &route {
+            Cow::Borrowed(route) => route.split('/').skip(1).map(|s| Cow::Borrowed(s.as_bytes())).collect(),
+            Cow::Owned(route)    => route.split('/').skip(1).map(|s| Cow::Owned(s.as_bytes().to_vec())).collect(),
+        };
+
         let mut sections = VecDeque::new();
-        for section in {let mut s = route.split('/'); s.next(); s} {
-            let section = match RouteSection::new(section.as_bytes()) {
+        for section in pieces {
+            let section = match RouteSection::new(section) {
                 Err(e) => panic!("{e}: `{route}`"),
                 Ok(rs) => rs,
             };
@@ -38,8 +93,8 @@ impl RouteSections {
         Self { litreral:route, sections }
     }
 
-    pub(crate)  fn literal(&self) -> &'static str {
-        self.litreral
+    pub(crate)  fn literal(&self) -> &str {
+        &self.litreral
     }
 }
 const _: () = {
@@ -52,12 +107,207 @@ const _: () = {
 
 #[derive(Clone)]
 pub enum RouteSection {
-    Static(&'static [u8]),
-    Param,
+    Static(Cow<'static, [u8]>),
+    Param(Option<ParamConstraint>),
+    Mixed(MixedSection),
+}
+
+/// Section mixing static text and params like `:name.:ext`, `v:version` or `@:username`.
+/// 
+/// Params in a section must be separated by static text, and each of them matches
+/// non-empty text. The static text between params is searched from the end of the
+/// segment, so `:name.:ext` matches `archive.tar.gz` as `archive.tar` and `gz`.
+/// 
+/// Such a section can't have param constraints, and conflicts with plain param
+/// sections at the same position.
+#[derive(Clone, PartialEq)]
+pub struct MixedSection {
+    pub(crate) prefix: Box<[u8]>,
+    /// Static text following each param, only the last one can be empty
+    pub(crate) delimiters: Vec<Box<[u8]>>,
+}
+impl MixedSection {
+    /// At most `Params::LIMIT` params in a section
+    const LIMIT: usize = 2;
+
+    fn parse(section: &str) -> Result<Self, String> {
+        let is_valid_static_char = |c: char| matches!(c,
+            '.' | '-' | '_' | '~' | '@' | '+' | '0'..='9' | 'a'..='z' | 'A'..='Z'
+        );
+        let is_name_char = |b: u8| b == b'_' || b.is_ascii_alphanumeric();
+
+        if section.contains(['<', '{']) {
+            return Err("Param constraints are not supported in a section mixing static text and params".into())
+        }
+
+        let mut pieces = section.split(':');
+        let prefix = pieces.next().unwrap();
+
+        let mut delimiters = Vec::new();
+        for piece in pieces {
+            let name_len = piece.bytes().take_while(|b| is_name_char(*b)).count();
+            if name_len == 0 {
+                return Err("Found an empty param name".into())
+            }
+            delimiters.push(&piece[name_len..]);
+        }
+
+        if let Some(c) = [prefix].iter().chain(&delimiters).flat_map(|s| s.chars()).find(|c| !is_valid_static_char(*c)) {
+            return Err(format!("Path section can't contain '{c}'"))
+        }
+        if delimiters.iter().rev().skip(1).any(|d| d.is_empty()) {
+            return Err("Params in a section must be separated by static text".into())
+        }
+        if delimiters.len() > Self::LIMIT {
+            return Err(format!("A section can have at most {} params", Self::LIMIT))
+        }
+
+        Ok(Self {
+            prefix:     prefix.as_bytes().into(),
+            delimiters: delimiters.into_iter().map(|d| d.as_bytes().into()).collect(),
+        })
+    }
+
+    fn suffix(&self) -> &[u8] {
+        self.delimiters.last().unwrap(/* at least one param */)
+    }
+
+    /// Whether some segment can be matched by both of `self` and `other`,
+    /// conservatively judged by their prefixes and suffixes
+    pub(crate) fn overlaps(&self, other: &Self) -> bool {
+        (self.prefix.starts_with(&other.prefix) || other.prefix.starts_with(&self.prefix)) &&
+        (self.suffix().ends_with(other.suffix()) || other.suffix().ends_with(self.suffix()))
+    }
+
+    /// Params in `segment` when it matches this section, with the number of them
+    #[inline]
+    pub(crate) fn captures<'s>(&self, segment: &'s [u8], ignore_case: bool) -> Option<([&'s [u8]; Self::LIMIT], usize)> {
+        let eq = |a: &[u8], b: &[u8]| if ignore_case {a.eq_ignore_ascii_case(b)} else {a == b};
+
+        let rest = segment.get(self.prefix.len()..)
+            .filter(|_| eq(&segment[..self.prefix.len()], &self.prefix))?;
+        let mut rest = rest.get(..rest.len().checked_sub(self.suffix().len())?)
+            .filter(|body| eq(&rest[body.len()..], self.suffix()))?;
+
+        let n = self.delimiters.len();
+        let mut params: [&[u8]; Self::LIMIT] = [&[]; Self::LIMIT];
+        for i in (1..n).rev() {
+            let delimiter = &*self.delimiters[i - 1];
+            /* the last occurrence leaving a non-empty param after it */
+            let start = (0..rest.len().checked_sub(delimiter.len() + 1)? + 1).rev()
+                .find(|&j| eq(&rest[j..j + delimiter.len()], delimiter))?;
+            params[i] = &rest[start + delimiter.len()..];
+            rest = &rest[..start];
+        }
+        params[0] = rest;
+
+        params[..n].iter().all(|p| !p.is_empty()).then_some((params, n))
+    }
 }
+const _: () = {
+    impl std::fmt::Debug for MixedSection {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(std::str::from_utf8(&self.prefix).unwrap())?;
+            for delimiter in &self.delimiters {
+                f.write_str(":Param")?;
+                f.write_str(std::str::from_utf8(delimiter).unwrap())?;
+            }
+            Ok(())
+        }
+    }
+};
+
+/// Whether `section` of a route is a `MixedSection`, that is, has static text
+/// before or after a param (whose name consists of alphanumerics and `_`)
+pub(super) fn is_mixed_section(section: &str) -> bool {
+    let head = &section[..section.find(['<', '{']).unwrap_or(section.len())];
+    match head.strip_prefix(':') {
+        None       => head.contains(':'),
+        Some(rest) => !rest.bytes().all(|b| b == b'_' || b.is_ascii_alphanumeric()),
+    }
+}
+
+/// Constraint on a path param, checked against the raw (not percent-decoded)
+/// segment while searching routes :
+/// 
+/// - `:id<u64>` ... the segment must be parsed as the type
+///   (`u8`〜`u128`, `usize`, `i8`〜`i128`, `isize`, `f32`, `f64`, `bool`)
+/// - `:name{[a-z]+\.png}` ... the whole segment must match the regex
+///   (see `ohkami_lib::regex` for supported syntax, and it can't contain `/`)
+/// 
+/// When the constraint fails, the router moves on to the next candidate route.
+#[derive(Clone)]
+pub enum ParamConstraint {
+    Type { name: &'static str, check: fn(&[u8]) -> bool },
+    Regex(ohkami_lib::regex::Regex),
+}
+impl ParamConstraint {
+    fn parse(spec: &str) -> Result<Self, String> {
+        fn parses<T: std::str::FromStr>(bytes: &[u8]) -> bool {
+            std::str::from_utf8(bytes).is_ok_and(|s| s.parse::<T>().is_ok())
+        }
+
+        if let Some(name) = spec.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            let (name, check): (&'static str, fn(&[u8]) -> bool) = match name {
+                "u8"    => ("u8",    parses::<u8>),
+                "u16"   => ("u16",   parses::<u16>),
+                "u32"   => ("u32",   parses::<u32>),
+                "u64"   => ("u64",   parses::<u64>),
+                "u128"  => ("u128",  parses::<u128>),
+                "usize" => ("usize", parses::<usize>),
+                "i8"    => ("i8",    parses::<i8>),
+                "i16"   => ("i16",   parses::<i16>),
+                "i32"   => ("i32",   parses::<i32>),
+                "i64"   => ("i64",   parses::<i64>),
+                "i128"  => ("i128",  parses::<i128>),
+                "isize" => ("isize", parses::<isize>),
+                "f32"   => ("f32",   parses::<f32>),
+                "f64"   => ("f64",   parses::<f64>),
+                "bool"  => ("bool",  parses::<bool>),
+                _ => return Err(format!("Unknown type constraint `<{name}>`"))
+            };
+            Ok(Self::Type { name, check })
+
+        } else if let Some(pattern) = spec.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            ohkami_lib::regex::Regex::new(pattern).map(Self::Regex)
+
+        } else {
+            Err(format!("Invalid param constraint `{spec}`"))
+        }
+    }
+
+    #[inline]
+    pub(crate) fn check(&self, param: &[u8]) -> bool {
+        match self {
+            Self::Type { check, .. } => check(param),
+            Self::Regex(regex)       => regex.is_match(param),
+        }
+    }
+}
+const _: () = {
+    impl PartialEq for ParamConstraint {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (Self::Type { name: a, .. }, Self::Type { name: b, .. }) => a == b,
+                (Self::Regex(a), Self::Regex(b)) => a == b,
+                _ => false
+            }
+        }
+    }
+
+    impl std::fmt::Debug for ParamConstraint {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Type { name, .. } => write!(f, "<{name}>"),
+                Self::Regex(regex)      => write!(f, "{{{}}}", regex.as_str()),
+            }
+        }
+    }
+};
 impl RouteSection {
-    pub(crate) fn new(section_bytes: &'static [u8]) -> Result<Self, String> {
-        let mut section_chars = std::str::from_utf8(section_bytes).unwrap().chars().peekable();
+    pub(crate) fn new(section_bytes: Cow<'static, [u8]>) -> Result<Self, String> {
+        let section_str = std::str::from_utf8(&section_bytes).unwrap();
+        let mut section_chars = section_str.chars().peekable();
 
         fn validate_section_name(mut name: Peekable<Chars>) -> Result<(), String> {
             let is_invalid_head_or_tail_char = |c: char| !/* NOT */ matches!(c,
@@ -87,15 +337,29 @@ impl RouteSection {
             Ok(())
         }
 
+        if is_mixed_section(section_str) {
+            return MixedSection::parse(section_str).map(Self::Mixed)
+        }
+
         match section_chars.peek() {
             None => Err(format!("Found an empty route section_chars")),
             Some(':') => {
-                let _/* colon */ = section_chars.next();
-                let _/* validation */ = validate_section_name(section_chars)?;
-                Ok(Self::Param)
+                section_chars.next(/* colon */);
+                let section = &section_str[1..];
+                match section.find(['<', '{']) {
+                    None => {
+                        validate_section_name(section_chars)?;
+                        Ok(Self::Param(None))
+                    }
+                    Some(i) => {
+                        let (name, constraint) = section.split_at(i);
+                        validate_section_name(name.chars().peekable())?;
+                        Ok(Self::Param(Some(ParamConstraint::parse(constraint)?)))
+                    }
+                }
             },
             _ => {
-                let _/* validation */ = validate_section_name(section_chars)?;
+                validate_section_name(section_chars)?;
                 Ok(Self::Static(section_bytes))
             }
         }
@@ -105,8 +369,10 @@ const _: () = {
     impl std::fmt::Debug for RouteSection {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
-                Self::Param         => f.write_str(":Param"),
-                Self::Static(bytes) => f.write_str(std::str::from_utf8(bytes).unwrap()),
+                Self::Param(None)    => f.write_str(":Param"),
+                Self::Param(Some(c)) => write!(f, ":Param{c:?}"),
+                Self::Static(bytes)  => f.write_str(std::str::from_utf8(bytes).unwrap()),
+                Self::Mixed(mixed)   => write!(f, "{mixed:?}"),
             }
         }
     }
@@ -0,0 +1,165 @@
+/// How `Ohkami` handles request paths not in the canonical form,
+/// set by `Ohkami::path_policy` :
+///
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::PathPolicy;
+///
+/// # async fn about() -> &'static str {"about"}
+/// # let _ =
+/// Ohkami::new((
+///     "/about".GET(about),
+/// )).path_policy(PathPolicy::redirect())
+/// # ;
+/// ```
+///
+/// The canonical form has
+///
+/// - no trailing slash (except for `/`)
+/// - no duplicate slashes like `/api//users`
+/// - no dot segments like `/./` or `/../` (also percent-encoded ones like `/%2e%2e/`)
+///
+/// and a non-canonical path is
+///
+/// - `strict` ... `404 Not Found`. Also paths with percent-encoded slash (`%2F`)
+///   in a segment are rejected.
+/// - `redirect` ... `308 Permanent Redirect` to the canonical one
+/// - `lenient` (default) ... routed as the canonical one, and `Request::path`
+///   is also the canonical one
+///
+/// Both of `404` and `308` go through the fangs as a usual `404 Not Found`,
+/// so that fangs like `CORS` apply to them.
+///
+/// Additionally `case_insensitive()` makes static parts of routes matched ignoring
+/// ASCII case, and then a path in another case than the route's one is also
+/// non-canonical: `strict` rejects it, `redirect` redirects to the route's case,
+/// and `lenient` routes it with `Request::path` in the route's case.
+///
+/// **Note** : A policy can be set only on the `Ohkami` that `howl`s (or `test`s),
+/// and setting one on nested `Ohkami`s panics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathPolicy {
+    pub(crate) mode:             PathMode,
+    pub(crate) case_insensitive: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PathMode {
+    Strict,
+    Redirect,
+    Lenient,
+}
+
+impl PathPolicy {
+    pub const fn strict() -> Self {
+        Self { mode: PathMode::Strict, case_insensitive: false }
+    }
+    pub const fn redirect() -> Self {
+        Self { mode: PathMode::Redirect, case_insensitive: false }
+    }
+    pub const fn lenient() -> Self {
+        Self { mode: PathMode::Lenient, case_insensitive: false }
+    }
+
+    pub const fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+}
+impl Default for PathPolicy {
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+
+pub(crate) struct Normalized {
+    /// `None` if the path is already canonical. Root is represented by empty bytes.
+    pub(crate) canonical:     Option<Vec<u8>>,
+    pub(crate) encoded_slash: bool,
+}
+
+/// Normalize `path` (with trailing slash stripped if `trailing_slash`)
+pub(crate) fn normalize(path: &[u8], trailing_slash: bool) -> Normalized {
+    /// Number of dots if `segment` consists of only `.` or `%2e`s
+    fn dots(mut segment: &[u8]) -> usize {
+        let mut n = 0;
+        while !segment.is_empty() {
+            if segment[0] == b'.' {
+                segment = &segment[1..];
+            } else if segment.len() >= 3 && segment[..3].eq_ignore_ascii_case(b"%2e") {
+                segment = &segment[3..];
+            } else {
+                return 0
+            }
+            n += 1;
+        }
+        n
+    }
+
+    let mut changed       = trailing_slash && !path.is_empty();
+    let mut encoded_slash = false;
+    for segment in path.split(|b| *b == b'/').skip(1) {
+        match (segment, dots(segment)) {
+            (b"", _) | (_, 1 | 2) => changed = true,
+            _ => encoded_slash |= segment.windows(3).any(|w| w.eq_ignore_ascii_case(b"%2f")),
+        }
+    }
+
+    let canonical = changed.then(|| {
+        let mut segments = Vec::new();
+        for segment in path.split(|b| *b == b'/').skip(1) {
+            match (segment, dots(segment)) {
+                (b"", _) | (_, 1) => (),
+                (_, 2) => {segments.pop();}
+                _ => segments.push(segment),
+            }
+        }
+
+        let mut canonical = Vec::with_capacity(path.len());
+        for segment in segments {
+            canonical.push(b'/');
+            canonical.extend_from_slice(segment);
+        }
+        canonical
+    });
+
+    Normalized { canonical, encoded_slash }
+}
+
+/// Canonical form of a route like `/static//css/` (to `/static/css`)
+pub(crate) fn canonicalize_route(route: &str) -> String {
+    match normalize(route.as_bytes(), false).canonical {
+        None => route.into(),
+        Some(canonical) if canonical.is_empty() => "/".into(),
+        Some(canonical) => String::from_utf8(canonical).unwrap(),
+    }
+}
+
+
+#[cfg(test)]
+#[test] fn test_normalize() {
+    for (path, trailing_slash, expected, encoded_slash) in [
+        ("/users/42",          false, None,                false),
+        ("",                   false, None,                false),
+        ("/users",             true,  Some("/users"),      false),
+        ("/api//users",        false, Some("/api/users"),  false),
+        ("/",                  false, Some(""),            false),
+        ("/a/./b",             false, Some("/a/b"),        false),
+        ("/a/../b",            false, Some("/b"),          false),
+        ("/../../a",           false, Some("/a"),          false),
+        ("/admin/%2e%2E/open", false, Some("/open"),       false),
+        ("/a/.%2e/b",          false, Some("/b"),          false),
+        ("/files/a%2Fb",       false, None,                true),
+        ("/.well-known/x",     false, None,                false),
+        ("/a/.../b",           false, None,                false),
+    ] {
+        let normalized = normalize(path.as_bytes(), trailing_slash);
+        assert_eq!(normalized.canonical.as_deref(), expected.map(str::as_bytes), "{path}");
+        assert_eq!(normalized.encoded_slash, encoded_slash, "{path}");
+    }
+
+    assert_eq!(canonicalize_route("/static//css/"), "/static/css");
+    assert_eq!(canonicalize_route("//"), "/");
+    assert_eq!(canonicalize_route("/index"), "/index");
+}
@@ -0,0 +1,81 @@
+use crate::{Method, Response};
+use std::{any::Any, future::Future, pin::Pin, sync::Arc, task::{Context, Poll}};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+
+/// A panic caught while handling a request, in a handler or a fang,
+/// given to the hooks of `Ohkami::on_panic` and `Ohkami::report_panic`
+pub struct Panic<'p> {
+    pub method: Method,
+    /// decoded lossily if it's not UTF-8
+    pub path:   &'p str,
+    payload:    &'p (dyn Any + Send),
+}
+impl Panic<'_> {
+    /// Message of `panic!`, `unwrap`, `expect`, ... if it's a string
+    pub fn message(&self) -> Option<&str> {
+        match self.payload.downcast_ref::<String>() {
+            Some(s) => Some(s),
+            None    => self.payload.downcast_ref::<&'static str>().copied(),
+        }
+    }
+
+    /// Raw payload, for `std::panic::panic_any`
+    pub fn payload(&self) -> &(dyn Any + Send) {
+        self.payload
+    }
+}
+
+/// Hooks of `Ohkami::on_panic` and `Ohkami::report_panic`
+#[derive(Clone, Default)]
+pub(crate) struct PanicHooks {
+    pub(crate) respond: Option<RespondHook>,
+    pub(crate) report:  Option<ReportHook>,
+}
+pub(crate) type RespondHook = Arc<dyn Fn(&Panic) -> Response + Send + Sync>;
+pub(crate) type ReportHook  = Arc<dyn Fn(&Panic) + Send + Sync>;
+impl PanicHooks {
+    #[cold] #[inline(never)]
+    pub(super) fn handle(&self, method: Method, path: &str, payload: Box<dyn Any + Send>) -> Response {
+        let panic = Panic { method, path, payload: &*payload };
+
+        match &self.report {
+            Some(report) => report(&panic),
+            None => match panic.message() {
+                Some(message) => crate::warning!("[Panicked] {method} {path}: {message}"),
+                None          => crate::warning!("[Panicked] {method} {path}"),
+            }
+        }
+
+        match &self.respond {
+            Some(respond) => respond(&panic),
+            None => Response::InternalServerError(),
+        }
+    }
+}
+const _: () = {
+    impl std::fmt::Debug for PanicHooks {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("PanicHooks")
+                .field("respond", &self.respond.as_ref().map(|_| '#'))
+                .field("report",  &self.report.as_ref().map(|_| '#'))
+                .finish()
+        }
+    }
+};
+
+/// `Future` catching a panic while being polled
+pub(super) struct CatchUnwind<F>(pub(super) F);
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `self.0` is never moved out
+        let future = unsafe {self.map_unchecked_mut(|this| &mut this.0)};
+        match catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(poll)   => poll.map(Ok),
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
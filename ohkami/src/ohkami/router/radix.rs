@@ -1,8 +1,8 @@
-use crate::request::Path;
-use crate::{Method, Request, Response};
-use crate::fangs::{FangProcCaller, BoxedFPC};
+use crate::request::{Path, States, RejectionMapper};
+use crate::{Method, CustomMethod, Request, Response, Status};
+use crate::fangs::{FangProcCaller, BoxedFPC, CatchWith};
 use ohkami_lib::Slice;
-use std::fmt::Write as _;
+use std::{borrow::Cow, fmt::Write as _, sync::Arc};
 
 
 #[derive(Debug)]
@@ -12,41 +12,83 @@ pub(crate) struct RadixRouter {
     pub(super) POST:    Node,
     pub(super) PATCH:   Node,
     pub(super) DELETE:  Node,
+    pub(super) HEAD:    Node,
     pub(super) OPTIONS: Node,
+    pub(super) TRACE:   Node,
+    pub(super) custom:  Box<[(CustomMethod, Node)]>,
+    pub(super) hosts:   Box<[(super::HostPattern, RadixRouter)]>,
+    pub(super) path_policy: super::PathPolicy,
+    pub(super) reject_unknown_hosts: bool,
+    pub(super) panic_hooks: super::PanicHooks,
 }
 
 pub(super) struct Node {
-    pub(super) patterns:  &'static [Pattern],
-    pub(super) children:  &'static [Node],
-    pub(super) proc:      BoxedFPC,
+    pub(super) patterns:  Box<[Pattern]>,
+    pub(super) children:  Box<[Node]>,
+    pub(super) proc:      Option<BoxedFPC>,
+    /// set to `Request` found this node's `proc`
+    pub(super) states:    Option<Arc<States>>,
+    /// referred by `Request` found this node's `proc`
+    pub(super) route:     RouteData,
     pub(super) __catch__: BoxedFPC,
+    pub(super) __method_not_allowed__: BoxedFPC,
 } const _: () = {
     impl std::fmt::Debug for Node {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            struct PatternsMarker(&'static [Pattern]);
-            impl std::fmt::Debug for PatternsMarker {
+            struct PatternsMarker<'p>(&'p [Pattern]);
+            impl std::fmt::Debug for PatternsMarker<'_> {
                 fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                     f.debug_list().entries(self.0).finish()
                 }
             }
 
             f.debug_struct("")
-                .field("patterns", &PatternsMarker(self.patterns))
+                .field("patterns", &PatternsMarker(&self.patterns))
                 .field("children", &self.children)
                 .finish()
         }
     }
 };
 
+/// Framework-internal data of a route, referred by the `Request`
+/// routed to it while the router handles the request
+pub(crate) struct RouteData {
+    /// for `Rejection::field` of invalid params
+    pub(crate) param_names: Box<[String]>,
+    /// set by `Ohkami::map_rejection`
+    pub(crate) rejection_mapper: Option<Arc<RejectionMapper>>,
+    /// for `Request::url_for`
+    pub(crate) urls: Option<Arc<super::Urls>>,
+}
+
+/// Reference to the `RouteData` of the route a `Request` is routed to
+#[derive(Clone, Copy)]
+pub(crate) struct RouteRef(std::ptr::NonNull<RouteData>);
+// SAFETY: `RouteData` is never mutated after the router is built
+unsafe impl Send for RouteRef {}
+unsafe impl Sync for RouteRef {}
+impl RouteRef {
+    /// SAFETY: the router having the `RouteData` must be alive
+    pub(crate) unsafe fn get<'r>(self) -> &'r RouteData {
+        unsafe {self.0.as_ref()}
+    }
+}
+
 pub(super) enum Pattern {
-    Static(&'static [u8]),
-    Param,
+    Static(Cow<'static, [u8]>),
+    /// by `PathPolicy::case_insensitive`
+    StaticIgnoreCase(Cow<'static, [u8]>),
+    Param(Option<super::ParamConstraint>),
+    /// with whether to ignore case of the static parts
+    Mixed(super::MixedSection, bool),
 } const _: () = {
     impl std::fmt::Debug for Pattern {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
-                Self::Param         => f.write_str(":Param"),
-                Self::Static(bytes) => {
+                Self::Param(None)    => f.write_str(":Param"),
+                Self::Param(Some(c)) => write!(f, ":Param{c:?}"),
+                Self::Mixed(m, _)    => write!(f, "'{m:?}'"),
+                Self::Static(bytes) | Self::StaticIgnoreCase(bytes) => {
                     f.write_char('\'')?;
                     f.write_str(std::str::from_utf8(bytes).unwrap())?;
                     f.write_char('\'')?;
@@ -66,109 +108,455 @@ impl RadixRouter {
         &self,
         req: &mut Request,
     ) -> Response {
-        (match req.method {
+        /* catching panics while polling handlers and fangs, not only while creating the future */
+        let res = match super::panic::CatchUnwind(self.handle_path(req)).await {
+            Ok(res)    => res,
+            Err(panic) => self.respond_to_panic(req, panic).await,
+        };
+
+        /* `req.route` refers to this router, so must not be used after here */
+        req.route = None;
+
+        res
+    }
+
+    /// Response by the panic hooks, through the fangs of the catcher for the path
+    /// (or as it is, if they panic again)
+    #[cold] #[inline(never)]
+    async fn respond_to_panic(&self, req: &mut Request, panic: Box<dyn std::any::Any + Send>) -> Response {
+        // SAFETY: same as `Node::search`
+        let path = match unsafe {req.path.normalized_bytes()} {
+            b""  => Cow::Borrowed("/"),
+            path => String::from_utf8_lossy(path),
+        };
+        let res = self.panic_hooks.handle(req.method, &path, panic);
+
+        /* the params pushed before the panic are no longer used, and `host_router` pushes the subdomain again */
+        // SAFETY: routing is over
+        unsafe {req.path.truncate_params(0)}
+        let router = self.host_router(req).unwrap_or(self);
+
+        req.memorize(CatchWith::new(res));
+        match super::panic::CatchUnwind(router.not_found(req)).await {
+            Ok(res) => res,
+            Err(_)  => req.memorized::<CatchWith>()
+                .and_then(CatchWith::take)
+                .unwrap_or_else(Response::InternalServerError)
+        }
+    }
+
+    #[inline(always)]
+    async fn handle_path(
+        &self,
+        req: &mut Request,
+    ) -> Response {
+        use super::normalize::{normalize, PathMode};
+
+        // SAFETY: same as `Node::search`
+        let normalized = normalize(unsafe {req.path.normalized_bytes()}, unsafe {req.path.has_trailing_slash()});
+        if self.path_policy.mode == PathMode::Strict && (normalized.canonical.is_some() || normalized.encoded_slash) {
+            return self.not_found(req).await
+        }
+
+        let non_canonical = normalized.canonical.is_some();
+        if let Some(canonical) = normalized.canonical {
+            // SAFETY: routing hasn't started yet
+            unsafe {req.path.set_canonical(canonical)}
+        }
+
+        self.route(req, non_canonical).await
+    }
+
+    /// `non_canonical`: whether the path has been replaced with the canonical one
+    async fn route(
+        &self,
+        req: &mut Request,
+        mut non_canonical: bool,
+    ) -> Response {
+        use super::normalize::PathMode;
+
+        if let Some(router) = self.host_router(req) {
+            /* only within the host's routes, not falling back to the others */
+            return Box::pin(router.route(req, non_canonical)).await
+        } else if self.reject_unknown_hosts {
+            return self.not_found(req).await
+        }
+
+        if self.path_policy.case_insensitive {
+            /* `OPTIONS` has handlers for all registered routes */
+            // SAFETY: same as `Node::search`
+            if let Some(canonical) = self.OPTIONS.canonical_case(unsafe {req.path.normalized_bytes()}) {
+                if self.path_policy.mode == PathMode::Strict {
+                    return self.not_found(req).await
+                }
+                // SAFETY: routing hasn't started yet
+                unsafe {req.path.set_canonical(canonical)}
+                non_canonical = true;
+            }
+        }
+
+        if non_canonical && self.path_policy.mode == PathMode::Redirect {
+            return self.redirect_to_canonical(req).await
+        }
+
+        let target = match req.method {
             Method::GET     => &self.GET,
             Method::PUT     => &self.PUT,
             Method::POST    => &self.POST,
             Method::PATCH   => &self.PATCH,
             Method::DELETE  => &self.DELETE,
             Method::OPTIONS => &self.OPTIONS,
-            Method::HEAD => {
-                let mut res = self.GET.search(&mut req.path).call_bite(req).await;
-                {/* not `res.drop_content()` to leave `Content-Type`, `Content-Length` */
-                    res.content = crate::response::Content::None;
+            Method::TRACE   => &self.TRACE,
+            Method::HEAD    => {
+                /* explicitly registered `HEAD` handler, or `GET` one */
+                // SAFETY: same as `Node::search`
+                if self.HEAD.matches(unsafe {req.path.normalized_bytes()}) {&self.HEAD} else {&self.GET}
+            }
+            Method::Custom(method) => match self.custom.iter().find(|(m, _)| *m == method) {
+                Some((_, node)) => node,
+                None => {
+                    /* no route has a handler for `method` */
+                    // SAFETY: same as `Node::search`
+                    let catcher = self.OPTIONS.catcher(unsafe {req.path.normalized_bytes()});
+                    return self.fallback(catcher, req).await
+                }
+            }
+        };
+
+        let mut res = match target.search(&mut req.path) {
+            Ok((proc, node)) => {
+                req.states = node.states.clone();
+                req.route  = Some(RouteRef(std::ptr::NonNull::from(&node.route)));
+                proc.call_bite(req).await
+            }
+            Err(catcher) => self.fallback(catcher, req).await,
+        };
+
+        if req.method.isHEAD() {
+            /* not `res.drop_content()` to leave `Content-Type`, `Content-Length` */
+            res.content = crate::response::Content::None;
+        }
+
+        res
+    }
+
+    /// `404 Not Found` through the fangs of the catcher for the path
+    async fn not_found(&self, req: &mut Request) -> Response {
+        // SAFETY: same as `Node::search`
+        let catcher = self.OPTIONS.catcher(unsafe {req.path.normalized_bytes()});
+        catcher.__catch__.call_bite(req).await
+    }
+
+    /// `308 Permanent Redirect` to the (already set) canonical path,
+    /// through the fangs of the catcher for it
+    async fn redirect_to_canonical(&self, req: &mut Request) -> Response {
+        // SAFETY: same as `Node::search`
+        let canonical = unsafe {req.path.normalized_bytes()};
+
+        let mut location = if canonical.is_empty() {"/".into()} else {String::from_utf8_lossy(canonical).into_owned()};
+        if let Some(query) = &req.query {
+            location.push('?');
+            location.push_str(&String::from_utf8_lossy(query.as_bytes()));
+        }
+
+        req.memorize(CatchWith::new(Response::PermanentRedirect().with_headers(|h| h.Location(location))));
+        self.not_found(req).await
+    }
+
+    /// The router of the first host matching `Host` of `req`. When found,
+    /// the subdomain captured by wildcard is pushed as a param.
+    fn host_router(&self, req: &mut Request) -> Option<&RadixRouter> {
+        if self.hosts.is_empty() {
+            return None
+        }
+
+        let host = req.headers.Host()?;
+        let (router, subdomain) = self.hosts.iter().find_map(|(pattern, router)| {
+            let subdomain = pattern.matches(host)?;
+            Some((router, subdomain.map(|s| Slice::from_bytes(s.as_bytes()))))
+        })?;
+
+        if let Some(subdomain) = subdomain {
+            // SAFETY: `Host` is held in `req` during handling it
+            unsafe {req.path.push_param(subdomain)}
+        }
+        Some(router)
+    }
+
+    /// `405 Method Not Allowed` when some route matches the path, or else `404 Not Found`,
+    /// both through the fangs of `catcher`
+    async fn fallback(&self, catcher: &Node, req: &mut Request) -> Response {
+        match self.allowed_methods(&req.path) {
+            None => catcher.__catch__.call_bite(req).await,
+            Some(allow) => {
+                let mut res = catcher.__method_not_allowed__.call_bite(req).await;
+                /*
+                    Fangs may override the default `405 Method Not Allowed`
+                    response (for example, to customize the body). `Allow`
+                    is set here only when the response is still 405 and
+                    no fang has set it by itself.
+                */
+                if res.status == Status::MethodNotAllowed && res.headers.Allow().is_none() {
+                    res.headers.set().Allow(allow);
                 }
-                return res
+                res
+            }
+        }
+    }
+
+    /// Returns `Allow` header value for the `path` when some route
+    /// matches it, or `None` when no route matches (that is, `404`).
+    /// 
+    /// This is only called when the request has missed the tree of
+    /// its method, so has no cost for successfully routed requests.
+    fn allowed_methods(&self, path: &Path) -> Option<String> {
+        // SAFETY: same as `Node::search`
+        let bytes = unsafe {path.normalized_bytes()};
+
+        /* `OPTIONS` has handlers for all registered routes */
+        if !self.OPTIONS.matches(bytes) {
+            return None
+        }
+
+        let mut methods = Vec::with_capacity(8);
+        for (method, tree) in [
+            (Method::GET,    &self.GET),
+            (Method::PUT,    &self.PUT),
+            (Method::POST,   &self.POST),
+            (Method::PATCH,  &self.PATCH),
+            (Method::DELETE, &self.DELETE),
+            (Method::TRACE,  &self.TRACE),
+        ].into_iter().chain(
+            self.custom.iter().map(|(m, tree)| (Method::Custom(*m), tree))
+        ).chain([
+            (Method::HEAD,   &self.HEAD),
+        ]) {
+            if tree.matches(bytes) {
+                methods.push(method)
             }
-        }).search(&mut req.path).call_bite(req).await
+        }
+        Some(super::available_methods(methods).iter().map(Method::name).collect::<Vec<_>>().join(", "))
     }
 }
 
 impl Node {
+    /// Returns `Ok((proc, node) of the route)`, or `Err(node where the search has stopped)`
+    /// whose `__catch__` or `__method_not_allowed__` handles the request
     #[inline]
     pub(super/* for test */) fn search(&self,
         path: &mut Path
-    ) -> &dyn FangProcCaller {
+    ) -> Result<(&dyn FangProcCaller, &Node), &Node> {
         // SAFETY:
         // 1. `req` must be alive while `search`
         // 2. `Request` DOESN'T have method that mutates `bytes`,
         //    So what `bytes` refers to is NEVER changed by any other process
         //    while `search`
-        let mut bytes = unsafe {path.normalized_bytes()};
-
-        let mut target = self;
+        let bytes = unsafe {path.normalized_bytes()};
 
         #[cfg(feature="DEBUG")]
         println!("[path] '{}'", bytes.escape_ascii());
 
-        loop {
+        match self.find(bytes, path) {
+            Ok(node @ Node { proc: Some(proc), .. }) => Ok((proc, node)),
+            Ok(node) | Err(node) => Err(node),
+        }
+    }
+
+    /// Whether this tree has a route matching `path`,
+    /// without pushing any params
+    #[inline]
+    fn matches(&self, path: &[u8]) -> bool {
+        self.find(path, &mut ()).is_ok()
+    }
+
+    /// `path` with its static parts in the case of the route it matches
+    /// ignoring case, or `None` when it doesn't match any route or is already
+    /// in the route's case
+    fn canonical_case(&self, path: &[u8]) -> Option<Vec<u8>> {
+        let mut fixes = CaseFixes { base: path.as_ptr() as usize, list: Vec::new() };
+        self.find(path, &mut fixes).ok()?;
+
+        (!fixes.list.is_empty()).then(|| {
+            let mut canonical = path.to_vec();
+            for (offset, bytes) in fixes.list {
+                canonical[offset..offset + bytes.len()].copy_from_slice(&bytes);
+            }
+            canonical
+        })
+    }
+
+    /// The deepest node that `path` reaches, without pushing any params
+    #[inline]
+    fn catcher(&self, path: &[u8]) -> &Node {
+        match self.find(path, &mut ()) {
+            Ok(node) | Err(node) => node
+        }
+    }
+
+    /// Search the node having the handler for `bytes`, moving on to the
+    /// next candidate child when a child fails (for example, by a param
+    /// constraint) with rolling back the params it has pushed.
+    /// 
+    /// When no route matches, returns `Err` of the node where the search
+    /// of the first candidate has stopped.
+    fn find<'n>(&'n self,
+        mut bytes: &[u8],
+        params:    &mut impl ParamsSink,
+    ) -> Result<&'n Node, &'n Node> {
+        #[cfg(feature="DEBUG")]
+        println!("[target] {:#?}", self);
+        #[cfg(feature="DEBUG")]
+        println!("[patterns] {:?}", self.patterns);
+
+        for pattern in &*self.patterns {
+            if bytes.is_empty() || unsafe {bytes.get_unchecked(0)} != &b'/' {
+                // At least one `pattern` to match is remaining
+                // but remaining `bytes` doesn't start with '/'
+                return Err(self)
+            }
+
+            bytes = unsafe {bytes.get_unchecked(1..)};
+            
             #[cfg(feature="DEBUG")]
-            println!("[target] {:#?}", target);
+            println!("[bytes striped prefix '/'] '{}'", bytes.escape_ascii());
+    
+            match pattern {
+                Pattern::Static(s) => bytes = match bytes.strip_prefix(&**s) {
+                    Some(remaining) => remaining,
+                    None            => return Err(self),
+                },
+                Pattern::StaticIgnoreCase(s) => bytes = match bytes.get(..s.len()) {
+                    Some(head) if head.eq_ignore_ascii_case(s) => {
+                        params.fix_case(head, s);
+                        unsafe {bytes.get_unchecked(s.len()..)}
+                    }
+                    _ => return Err(self),
+                },
+                Pattern::Param(constraint) => {
+                    let (param, remaining) = split_next_section(bytes);
+                    if constraint.as_ref().is_some_and(|c| !c.check(param)) {
+                        return Err(self)
+                    }
+                    params.push(param);
+                    bytes = remaining;
+                },
+                Pattern::Mixed(mixed, ignore_case) => {
+                    let (segment, remaining) = split_next_section(bytes);
+                    let Some((captures, n)) = mixed.captures(segment, *ignore_case) else {
+                        return Err(self)
+                    };
+                    for param in &captures[..n] {
+                        params.push(param);
+                    }
+                    if *ignore_case {
+                        /* static parts around the captured params */
+                        let prefix = &segment[..mixed.prefix.len()];
+                        params.fix_case(prefix, &mixed.prefix);
+                        for (param, delimiter) in captures[..n].iter().zip(&mixed.delimiters) {
+                            let start = param.as_ptr() as usize - segment.as_ptr() as usize + param.len();
+                            params.fix_case(&segment[start..start + delimiter.len()], delimiter);
+                        }
+                    }
+                    bytes = remaining;
+                },
+            }
+        }
+
+        if bytes.is_empty() {
             #[cfg(feature="DEBUG")]
-            println!("[patterns] {:?}", target.patterns);
+            println!("Found: {self:?}");
     
-            for pattern in target.patterns {
-                if bytes.is_empty() || unsafe {bytes.get_unchecked(0)} != &b'/' {
-                    // At least one `pattern` to match is remaining
-                    // but remaining `bytes` doesn't start with '/'
-                    return &target.__catch__
-                }
+            return if self.proc.is_some() {Ok(self)} else {Err(self)}
+        }
 
-                bytes = unsafe {bytes.get_unchecked(1..)};
-                
-                #[cfg(feature="DEBUG")]
-                println!("[bytes striped prefix '/'] '{}'", bytes.escape_ascii());
-        
-                match pattern {
-                    Pattern::Static(s) => bytes = match bytes.strip_prefix(*s) {
-                        Some(remaining) => remaining,
-                        None            => return &target.__catch__,
-                    },
-                    Pattern::Param => {
-                        let (param, remaining) = split_next_section(bytes);
-                        unsafe {path.push_param(Slice::from_bytes(param))}
-                        bytes = remaining;
-                    },
-                }
+        #[cfg(feature="DEBUG")]
+        println!("not found, searching children: {:#?}", self.children);
+
+        let mut catcher = None;
+        for child in &*self.children {
+            if !child.patterns.first().is_some_and(|p| p.is_matchable_to(bytes)) {
+                continue
             }
 
-            if bytes.is_empty() {
-                #[cfg(feature="DEBUG")]
-                println!("Found: {target:?}");
-        
-                return  &target.proc
-            } else {
-                #[cfg(feature="DEBUG")]
-                println!("not found, searching children: {:#?}", target.children);
-        
-                target = match target.matchable_child(bytes) {
-                    Some(child) => child,
-                    None        => return &target.__catch__,
+            let checkpoint = params.checkpoint();
+            match child.find(bytes, params) {
+                Ok(found) => return Ok(found),
+                Err(node) => {
+                    params.rollback(checkpoint);
+                    catcher.get_or_insert(node);
                 }
             }
         }
+        Err(catcher.unwrap_or(self))
     }
 }
 
 
-/*===== utils =====*/
+/// Where `Node::find` pushes path params
+trait ParamsSink {
+    fn push(&mut self, param: &[u8]);
+    fn checkpoint(&self) -> usize;
+    fn rollback(&mut self, checkpoint: usize);
 
-impl Node {
-    #[inline] fn matchable_child(&self, path: &[u8]) -> Option<&Node> {
-        for child in &*self.children {
-            if child.patterns.first()?.is_matchable_to(path) {
-                return Some(child)
+    /// Called with the part of the path that has matched `canonical`
+    /// of a route ignoring case
+    #[inline(always)] fn fix_case(&mut self, _matched: &[u8], _canonical: &[u8]) {}
+}
+
+/// Parts of a path to be replaced to be in the case of the route,
+/// as offsets from `base` (the start of the path)
+struct CaseFixes {
+    base: usize,
+    list: Vec<(usize, Vec<u8>)>,
+}
+const _: () = {
+    impl ParamsSink for Path {
+        #[inline(always)] fn push(&mut self, param: &[u8]) {
+            unsafe {self.push_param(Slice::from_bytes(param))}
+        }
+        #[inline(always)] fn checkpoint(&self) -> usize {
+            unsafe {self.params_len()}
+        }
+        #[inline(always)] fn rollback(&mut self, checkpoint: usize) {
+            unsafe {self.truncate_params(checkpoint)}
+        }
+    }
+
+    /// Just matching without params
+    impl ParamsSink for () {
+        #[inline(always)] fn push(&mut self, _: &[u8]) {}
+        #[inline(always)] fn checkpoint(&self) -> usize {0}
+        #[inline(always)] fn rollback(&mut self, _: usize) {}
+    }
+
+    impl ParamsSink for CaseFixes {
+        fn push(&mut self, _: &[u8]) {}
+        fn checkpoint(&self) -> usize {
+            self.list.len()
+        }
+        fn rollback(&mut self, checkpoint: usize) {
+            self.list.truncate(checkpoint)
+        }
+        fn fix_case(&mut self, matched: &[u8], canonical: &[u8]) {
+            if matched != canonical {
+                self.list.push((matched.as_ptr() as usize - self.base, canonical.to_vec()))
             }
         }
-        None
     }
-}
+};
+
+
+/*===== utils =====*/
 
 impl Pattern {
     #[inline(always)] fn is_matchable_to(&self, path: &[u8]) -> bool {
         match self {
-            Self::Param     => true,
+            Self::Param(_)  => true,
             Self::Static(s) => (&path[1..]/* skip initial '/' */).starts_with(s),
+            Self::StaticIgnoreCase(s) => path[1..].get(..s.len()).is_some_and(|head| head.eq_ignore_ascii_case(s)),
+            Self::Mixed(m, ignore_case) => path[1..].get(..m.prefix.len()).is_some_and(|head|
+                if *ignore_case {head.eq_ignore_ascii_case(&m.prefix)} else {head == &*m.prefix}
+            ),
         }
     }
 }
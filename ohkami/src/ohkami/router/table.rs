@@ -0,0 +1,88 @@
+use crate::Method;
+
+
+/// Registered routes of an `Ohkami`, returned by `Ohkami::routes`.
+///
+/// `Display` shows them as a table :
+///
+/// ```text
+/// PATH             METHODS      FANGS
+/// /hc              GET          0
+/// /api/users       POST         1
+/// /api/users/:id   GET, PATCH   1
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteTable(pub(crate) Vec<RouteInfo>);
+
+/// A registered route
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteInfo {
+    pub(crate) path:    String,
+    pub(crate) methods: Vec<Method>,
+    pub(crate) fangs:   usize,
+}
+
+impl RouteInfo {
+    /// Route template, like `/api/users/:id`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    /// Methods having a handler registered on this route.
+    /// (`HEAD` and `OPTIONS` handled by default are not included.)
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+    /// Number of fangs applied to this route
+    pub fn fangs(&self) -> usize {
+        self.fangs
+    }
+}
+
+impl RouteTable {
+    pub fn iter(&self) -> impl Iterator<Item = &RouteInfo> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the route info by exact route template like `/api/users/:id`
+    pub fn get(&self, path: &str) -> Option<&RouteInfo> {
+        self.0.iter().find(|info| info.path == path)
+    }
+}
+
+const _: () = {
+    impl IntoIterator for RouteTable {
+        type Item = RouteInfo;
+        type IntoIter = std::vec::IntoIter<RouteInfo>;
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.into_iter()
+        }
+    }
+
+    impl std::fmt::Display for RouteTable {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let rows = self.0.iter()
+                .map(|info| (
+                    &*info.path,
+                    info.methods.iter().map(Method::name).collect::<Vec<_>>().join(", "),
+                    info.fangs,
+                ))
+                .collect::<Vec<_>>();
+
+            let path_width    = rows.iter().map(|(path, _, _)| path.len()).chain([4]).max().unwrap();
+            let methods_width = rows.iter().map(|(_, methods, _)| methods.len()).chain([7]).max().unwrap();
+
+            writeln!(f, "{:path_width$}   {:methods_width$}   FANGS", "PATH", "METHODS")?;
+            for (path, methods, fangs) in rows {
+                writeln!(f, "{path:path_width$}   {methods:methods_width$}   {fangs}")?;
+            }
+            Ok(())
+        }
+    }
+};
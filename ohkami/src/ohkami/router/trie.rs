@@ -1,19 +1,33 @@
 use std::{borrow::Cow, sync::Arc};
-use super::{RouteSection, RouteSections};
+use super::{RouteSection, RouteSections, ParamConstraint, MixedSection, RouteInfo, Urls, HostPattern, PathPolicy, PanicHooks};
 use super::super::build::{Handlers, ByAnother};
+use super::super::guard::{self, Guard};
 use crate::fangs::{BoxedFPC, Fangs, Handler};
+use crate::{Method, CustomMethod};
+use crate::request::{States, Path, RejectionMapper};
 
 
 #[derive(Debug)]
 pub struct TrieRouter {
     pub(super) id:      RouterID,
-    pub(super) routes:  std::collections::HashSet<&'static str>,
+    pub(super) routes:  std::collections::HashSet<String>,
+    pub(super) table:   Vec<RouteInfo>,
+    pub(super) urls:    Urls,
     pub(super) GET:     Node,
     pub(super) PUT:     Node,
     pub(super) POST:    Node,
     pub(super) PATCH:   Node,
     pub(super) DELETE:  Node,
+    pub(super) HEAD:    Node,
     pub(super) OPTIONS: Node,
+    pub(super) TRACE:   Node,
+    pub(super) custom:  Vec<(CustomMethod, Node)>,
+    pub(super) hosts:   Vec<(HostPattern, TrieRouter)>,
+    /// `None` if not set, which must be the case for nested ones
+    pub(crate) path_policy: Option<PathPolicy>,
+    /// `false` if not set, which must be the case for nested ones
+    pub(crate) reject_unknown_hosts: bool,
+    pub(crate) panic_hooks: PanicHooks,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -40,7 +54,13 @@ pub(super) struct Node {
     /// Why Option: root node doesn't have pattern
     pub(super) pattern:    Option<Pattern>,
     pub(super) handler:    Option<Handler>,
+    /// Handlers selected by `when(..)` guards, tried before `handler`
+    pub(super) guarded:    Vec<(Guard, Handler)>,
     pub(super) fangs_list: FangsList,
+    pub(super) states:     States,
+    /// Names of the params of the route, set with `handler` or `guarded`
+    pub(super) param_names: Option<Vec<String>>,
+    pub(super) rejection_mapper: Option<Arc<RejectionMapper>>,
     pub(super) children:   Vec<Node>,
 } const _: () = {
     impl std::fmt::Debug for Node {
@@ -48,6 +68,7 @@ pub(super) struct Node {
             f.debug_struct("")
                 .field("pattern",    &self.pattern)
                 .field("handler",    &self.handler.as_ref().map(|_| '#'))
+                .field("guarded",    &self.guarded.iter().map(|_| '#').collect::<Vec<_>>())
                 .field("fangs_list", &self.fangs_list.iter().map(|_| '#').collect::<Vec<_>>())
                 .field("children",   &self.children)
                 .finish()
@@ -58,15 +79,18 @@ pub(super) struct Node {
 #[derive(Clone)]
 pub(super) enum Pattern {
     Static(Cow<'static, [u8]>),
-    Param,
+    Param(Option<ParamConstraint>),
+    Mixed(MixedSection),
 } const _: () = {
     impl std::fmt::Debug for Pattern {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
-                Self::Param     => f.write_str(":Param"),
-                Self::Static(v) => f.write_str(&format!(
+                Self::Param(None)    => f.write_str(":Param"),
+                Self::Param(Some(c)) => f.write_str(&format!(":Param{c:?}")),
+                Self::Static(v)      => f.write_str(&format!(
                     "'{}'", std::str::from_utf8(&v).unwrap()
                 )),
+                Self::Mixed(m)       => f.write_str(&format!("'{m:?}'")),
             }
         }
     }
@@ -74,8 +98,8 @@ pub(super) enum Pattern {
     impl PartialEq for Pattern {
         fn eq(&self, other: &Self) -> bool {
             match self {
-                Self::Param => match other {
-                    Self::Param => true,
+                Self::Param(this_constraint) => match other {
+                    Self::Param(other_constraint) => this_constraint == other_constraint,
                     _ => false,
                 }
                 Self::Static(this_bytes) => {
@@ -84,6 +108,10 @@ pub(super) enum Pattern {
                         _ => false
                     }
                 }
+                Self::Mixed(this_mixed) => match other {
+                    Self::Mixed(other_mixed) => this_mixed == other_mixed,
+                    _ => false,
+                }
             }
         }
     }
@@ -91,8 +119,9 @@ pub(super) enum Pattern {
     impl From<RouteSection> for Pattern {
         fn from(section: RouteSection) -> Self {
             match section {
-                RouteSection::Param         => Pattern::Param,
-                RouteSection::Static(bytes) => Pattern::Static(Cow::Borrowed(bytes))
+                RouteSection::Param(c)      => Pattern::Param(c),
+                RouteSection::Static(bytes) => Pattern::Static(bytes),
+                RouteSection::Mixed(mixed)  => Pattern::Mixed(mixed),
             }
         }
     }
@@ -161,52 +190,151 @@ impl TrieRouter {
         Self {
             id:      RouterID::new(),
             routes:  Default::default(),
+            table:   Vec::new(),
+            urls:    Urls::default(),
             GET:     Node::root(),
             PUT:     Node::root(),
             POST:    Node::root(),
             PATCH:   Node::root(),
             DELETE:  Node::root(),
+            HEAD:    Node::root(),
             OPTIONS: Node::root(),
+            TRACE:   Node::root(),
+            custom:  Vec::new(),
+            hosts:   Vec::new(),
+            path_policy: None,
+            reject_unknown_hosts: false,
+            panic_hooks: PanicHooks::default(),
         }
     }
 
+    fn custom_mut(&mut self, method: CustomMethod) -> &mut Node {
+        let index = match self.custom.iter().position(|(m, _)| *m == method) {
+            Some(index) => index,
+            None => {
+                self.custom.push((method, Node::root()));
+                self.custom.len() - 1
+            }
+        };
+        &mut self.custom[index].1
+    }
+
     pub(crate) fn id(&self) -> RouterID {
         self.id.clone()
     }
 
     pub(crate) fn register_handlers(&mut self, handlers: Handlers) {
-        let Handlers { route, GET, PUT, POST, PATCH, DELETE } = handlers;
+        let Handlers { route, name, guard, GET, PUT, POST, PATCH, DELETE, HEAD, OPTIONS, TRACE, custom, fangs, method_fangs } = handlers;
+
+        /*
+            Fangs of the route and of each method are applied here, so that
+            they're always inside the ones of the `Ohkami`s applied later
+            by `apply_fangs`
+        */
+        let with_route_fangs = |handler: Handler| fangs.iter()
+            .fold(handler, |h, fangs| h.with_fangs(&**fangs));
+        let with_fangs = |method: Method, handler: Handler| with_route_fangs(
+            match method_fangs.iter().find(|(m, _)| *m == method) {
+                Some((_, fangs)) => handler.with_fangs(&**fangs),
+                None             => handler,
+            }
+        );
+        macro_rules! apply_fangs {
+            ($($method:ident),*) => {$(
+                let $method = $method.map(|h| with_fangs(Method::$method, h));
+            )*};
+        } apply_fangs! { GET, PUT, POST, PATCH, DELETE, HEAD, OPTIONS, TRACE }
+        let custom = custom.into_iter()
+            .map(|(method, h)| (method, with_fangs(Method::Custom(method), h)))
+            .collect::<Vec<_>>();
 
-        let methods = if !self.routes.insert(route.literal()) {
+        /* guarded ones can share the route with each other and an unguarded one */
+        if guard.is_none() && !self.routes.insert(route.literal().to_owned()) {
             panic!("Duplicate routes registration: `{}`", route.literal())
-        } else {
+        }
+        if guard.is_some() && OPTIONS.is_some() {
+            panic!("`OPTIONS` handler can't be guarded: `{}`", route.literal())
+        }
+
+        let methods = {
             macro_rules! allow_methods {
                 ($($method:ident),*) => {{
                     let mut methods = Vec::new();
                     $(
                         if $method.is_some() {
-                            methods.push(stringify!($method))
+                            methods.push(Method::$method)
                         }
                     )*
+                    methods.extend(custom.iter().map(|(m, _)| Method::Custom(*m)));
+                    if HEAD.is_some() {
+                        methods.push(Method::HEAD)
+                    }
                     methods
                 }}
-            } allow_methods! { GET, PUT, POST, PATCH, DELETE }
+            } allow_methods! { GET, PUT, POST, PATCH, DELETE, TRACE }
         };
 
+        let info = match self.table.iter_mut().find(|info| info.path == route.literal()) {
+            Some(info) => info,
+            None => {
+                self.table.push(RouteInfo { path: route.literal().into(), methods: Vec::new(), fangs: 0 });
+                self.table.last_mut().unwrap()
+            }
+        };
+        for method in methods.iter().chain(OPTIONS.is_some().then_some(&Method::OPTIONS)) {
+            if !info.methods.contains(method) {
+                info.methods.push(*method)
+            }
+        }
+        info.fangs = info.fangs.max(fangs.iter().map(|f| f.count()).sum());
+
+        Self::check_params(route.literal());
+
+        if let Some((name, n_params)) = name {
+            if let Some(n_params) = n_params {
+                let route_params = super::url::count_params(route.literal());
+                if n_params != route_params {
+                    panic!("Route name `{name}` expects {n_params} param(s), but `{}` has {route_params}", route.literal())
+                }
+            }
+            if let Err(e) = self.urls.register(name, route.literal().into()) {
+                panic!("{e}: `{}`", route.literal())
+            }
+        }
+
+        let param_names = || super::url::param_names(route.literal());
         macro_rules! register {
             ($( $method:ident ),*) => {$(
                 if let Some(h) = $method {
-                    self.$method.register_handler(route.clone().into_iter(), h).expect("Failed to register handler");
+                    self.$method.register_handler(route.clone().into_iter(), h, guard.clone(), param_names()).expect("Failed to register handler");
                 }
             )*};
-        } register! { GET, PUT, POST, PATCH, DELETE }
+        } register! { GET, PUT, POST, PATCH, DELETE, HEAD, TRACE }
 
-        self.OPTIONS.register_handler(route.into_iter(), Handler::new(move |req| {
-            let mut available_methods = methods.clone();
-            if available_methods.contains(&"GET") {
-                available_methods.push("HEAD")
-            }
-            available_methods.push("OPTIONS");
+        for (method, h) in custom {
+            self.custom_mut(method).register_handler(route.clone().into_iter(), h, guard.clone(), param_names()).expect("Failed to register handler");
+        }
+
+        /*
+            `OPTIONS` always has a handler for every route, explicit one or the default.
+            The default one is replaced every time a guarded one shares the route,
+            to allow all the methods of the route.
+        */
+        if let Some(h) = OPTIONS {
+            return self.OPTIONS.register_with(route.into_iter(), |node| {node.handler = Some(h); Ok(())}).expect("Failed to register handler")
+        }
+
+        let info = self.table.iter().find(|info| info.path == route.literal()).unwrap(/* pushed above */);
+        if info.methods.contains(&Method::OPTIONS) {
+            /* explicit one is already registered */
+            return
+        }
+
+        let available_methods = super::available_methods(info.methods.clone())
+            .iter().map(Method::to_string).collect::<Vec<_>>();
+
+        let default_options = with_route_fangs(Handler::new(move |req| {
+            let available_methods = available_methods.clone();
 
             Box::pin(async move {
                 #[cfg(debug_assertions)] {
@@ -222,7 +350,7 @@ impl TrieRouter {
                             whitch is the default for a valid preflight request,
                             by a successful one in its proc.
                         */
-                        (if available_methods.contains(&method) {
+                        (if available_methods.iter().any(|m| m == method) {
                             crate::Response::NotImplemented()
                         } else {
                             crate::Response::BadRequest()
@@ -246,7 +374,8 @@ impl TrieRouter {
                     }
                 }
             })
-        })).expect("Failed to register handler")
+        }));
+        self.OPTIONS.register_with(route.into_iter(), |node| {node.handler = Some(default_options); Ok(())}).expect("Failed to register handler")
     }
 
     pub(crate) fn apply_fangs(&mut self, id: RouterID, fangs: Arc<dyn Fangs>) {
@@ -256,48 +385,250 @@ impl TrieRouter {
                     self.$method.apply_fangs(id.clone(), fangs.clone());
                 )*
             };
-        } apply_to! { GET, PUT, POST, PATCH, DELETE, OPTIONS }
+        } apply_to! { GET, PUT, POST, PATCH, DELETE, HEAD, OPTIONS, TRACE }
+
+        for (_, node) in &mut self.custom {
+            node.apply_fangs(id.clone(), fangs.clone());
+        }
+
+        for info in &mut self.table {
+            info.fangs += fangs.count();
+        }
+
+        for (_, router) in &mut self.hosts {
+            router.apply_fangs(id.clone(), fangs.clone());
+        }
+    }
+
+    pub(crate) fn apply_states(&mut self, states: &States) {
+        macro_rules! apply_to {
+            ($($method:ident),*) => {
+                $(
+                    self.$method.apply_states(states);
+                )*
+            };
+        } apply_to! { GET, PUT, POST, PATCH, DELETE, HEAD, OPTIONS, TRACE }
+
+        for (_, node) in &mut self.custom {
+            node.apply_states(states);
+        }
+
+        for (_, router) in &mut self.hosts {
+            router.apply_states(states);
+        }
+    }
+
+    pub(crate) fn apply_rejection_mapper(&mut self, mapper: Arc<RejectionMapper>) {
+        macro_rules! apply_to {
+            ($($method:ident),*) => {
+                $(
+                    self.$method.apply_rejection_mapper(&mapper);
+                )*
+            };
+        } apply_to! { GET, PUT, POST, PATCH, DELETE, HEAD, OPTIONS, TRACE }
+
+        for (_, node) in &mut self.custom {
+            node.apply_rejection_mapper(&mapper);
+        }
+
+        for (_, router) in &mut self.hosts {
+            router.apply_rejection_mapper(mapper.clone());
+        }
+    }
+
+    pub(crate) fn route_host(&mut self, host: HostPattern, router: TrieRouter) {
+        if !router.hosts.is_empty() {
+            panic!("Host-based routing can't be nested: `{host}`")
+        }
+        if self.hosts.iter().any(|(h, _)| *h == host) {
+            panic!("Duplicate host registration: `{host}`")
+        }
+        Self::check_host_params(&host, &router);
+        Self::check_nested(&router);
+        self.hosts.push((host, router));
+    }
+
+    /// Params of a request are held in a fixed-size buffer of
+    /// `Path::PARAMS_LIMIT`, so routes are checked not to exceed it
+    fn check_params(path: &str) {
+        let n_params = super::url::count_params(path);
+        if n_params > Path::PARAMS_LIMIT {
+            panic!("A route can have at most {} path params, but found {n_params}: `{path}`", Path::PARAMS_LIMIT)
+        }
+    }
+
+    /// The subdomain captured by wildcard host takes one of the path params
+    fn check_host_params(host: &HostPattern, router: &TrieRouter) {
+        if host.is_wildcard() {
+            for info in &router.table {
+                if super::url::count_params(&info.path) > Path::PARAMS_LIMIT - 1 {
+                    panic!("Routes under wildcard host `{host}` can have at most {} path param: `{}`", Path::PARAMS_LIMIT - 1, info.path)
+                }
+            }
+        }
+    }
+
+    /// Settings only for the `Ohkami` that `howl`s (or `test`s)
+    fn check_nested(router: &TrieRouter) {
+        if router.path_policy.is_some() {
+            panic!("`path_policy` can't be set on a nested `Ohkami`, but only on the one that `howl`s or `test`s")
+        }
+        if router.reject_unknown_hosts {
+            panic!("`reject_unknown_hosts` can't be set on a nested `Ohkami`, but only on the one that `howl`s or `test`s")
+        }
+        if router.panic_hooks.respond.is_some() {
+            panic!("`on_panic` can't be set on a nested `Ohkami`, but only on the one that `howl`s or `test`s")
+        }
+        if router.panic_hooks.report.is_some() {
+            panic!("`report_panic` can't be set on a nested `Ohkami`, but only on the one that `howl`s or `test`s")
+        }
     }
 
     pub(crate) fn merge_another(&mut self, another: ByAnother) {
         let ByAnother { route, ohkami } = another;
-        let another_routes = ohkami.into_router();
+        let mut another_routes = ohkami.into_router();
+        Self::check_nested(&another_routes);
+
+        let prefix_params = super::url::param_names(route.literal());
+        if !prefix_params.is_empty() {
+            macro_rules! prefix {
+                ($( $method:ident ),*) => {$(
+                    another_routes.$method.prefix_param_names(&prefix_params);
+                )*};
+            } prefix! { GET, PUT, POST, PATCH, DELETE, HEAD, OPTIONS, TRACE }
+            for (_, node) in &mut another_routes.custom {
+                node.prefix_param_names(&prefix_params);
+            }
+        }
 
         macro_rules! merge {
             ($( $method:ident ),*) => {$(
                 self.$method.merge_node(route.clone().into_iter(), another_routes.$method).expect("Can't merge Ohkamis");
             )*};
-        } merge! { GET, PUT, POST, PATCH, DELETE, OPTIONS }
+        } merge! { GET, PUT, POST, PATCH, DELETE, HEAD, OPTIONS, TRACE }
+
+        for (method, node) in another_routes.custom {
+            self.custom_mut(method).merge_node(route.clone().into_iter(), node).expect("Can't merge Ohkamis");
+        }
+
+        self.table.extend(another_routes.table.into_iter().map(|info| {
+            let path = super::join_route(route.literal(), &info.path);
+            Self::check_params(&path);
+            RouteInfo { path, ..info }
+        }));
+
+        if let Err(e) = self.urls.merge(route.literal(), another_routes.urls) {
+            panic!("{e}")
+        }
+
+        for (host, router) in another_routes.hosts {
+            let mut prefixed = TrieRouter::new();
+            prefixed.merge_another(ByAnother {
+                route:  route.clone(),
+                ohkami: crate::Ohkami { routes: router, fangs: None, states: States::new(), rejection_mapper: None },
+            });
+            Self::check_host_params(&host, &prefixed);
+
+            match self.hosts.iter_mut().find(|(h, _)| *h == host) {
+                None => self.hosts.push((host, prefixed)),
+                Some((_, router)) => router.merge_another(ByAnother {
+                    route:  RouteSections::from_literal("/"),
+                    ohkami: crate::Ohkami { routes: prefixed, fangs: None, states: States::new(), rejection_mapper: None },
+                }),
+            }
+        }
     }
 
-    pub(crate) fn into_radix(self) -> super::RadixRouter {
+    /// Routes of this router, and then ones of each host prefixed with the host
+    pub(crate) fn table(&self) -> Vec<RouteInfo> {
+        let mut table = self.table.clone();
+        for (host, router) in &self.hosts {
+            table.extend(router.table().into_iter().map(|info| RouteInfo {
+                path: format!("{host}{}", info.path),
+                ..info
+            }));
+        }
+        table
+    }
+
+    pub(crate) fn urls(&self) -> &Urls {
+        &self.urls
+    }
+
+    pub(crate) fn into_radix(mut self) -> super::RadixRouter {
+        let path_policy = self.path_policy.unwrap_or_default();
+        let ignore_case = path_policy.case_insensitive;
+
+        /* exact hosts first, then wildcards */
+        let mut hosts = std::mem::take(&mut self.hosts);
+        hosts.sort_by_key(|(host, _)| host.is_wildcard());
+        let hosts = hosts.into_iter().map(|(host, mut router)| {
+            router.path_policy = Some(path_policy);
+            (host, router.into_radix())
+        }).collect();
+
+        /* for `Request::url_for`, not including `hosts` having their own */
+        let urls = (!self.urls.is_empty()).then(|| Arc::new(std::mem::take(&mut self.urls)));
+        let urls = urls.as_ref();
+
         super::RadixRouter {
-            GET:     self.GET    .into_radix(),
-            PUT:     self.PUT    .into_radix(),
-            POST:    self.POST   .into_radix(),
-            PATCH:   self.PATCH  .into_radix(),
-            DELETE:  self.DELETE .into_radix(),
-            OPTIONS: self.OPTIONS.into_radix(),
+            GET:     self.GET    .into_radix(ignore_case, urls),
+            PUT:     self.PUT    .into_radix(ignore_case, urls),
+            POST:    self.POST   .into_radix(ignore_case, urls),
+            PATCH:   self.PATCH  .into_radix(ignore_case, urls),
+            DELETE:  self.DELETE .into_radix(ignore_case, urls),
+            HEAD:    self.HEAD   .into_radix(ignore_case, urls),
+            OPTIONS: self.OPTIONS.into_radix(ignore_case, urls),
+            TRACE:   self.TRACE  .into_radix(ignore_case, urls),
+            custom:  self.custom.into_iter().map(|(m, node)| (m, node.into_radix(ignore_case, urls))).collect(),
+            hosts,
+            path_policy,
+            reject_unknown_hosts: self.reject_unknown_hosts,
+            panic_hooks: self.panic_hooks,
         }
     }
 }
 
 impl Node {
     fn register_handler(
+        &mut self,
+        route:   <RouteSections as IntoIterator>::IntoIter,
+        handler: Handler,
+        guard:   Option<Guard>,
+        params:  Vec<String>,
+    ) -> Result<(), String> {
+        self.register_with(route, |node| {
+            node.param_names = Some(params);
+            match guard {
+                None        => node.set_handler(handler),
+                Some(guard) => {node.guarded.push((guard, handler)); Ok(())}
+            }
+        })
+    }
+
+    /// Prepend `prefix` to the param names of the routes, on merging into `prefix`
+    fn prefix_param_names(&mut self, prefix: &[String]) {
+        for child in &mut self.children {
+            child.prefix_param_names(prefix)
+        }
+        if let Some(names) = &mut self.param_names {
+            names.splice(0..0, prefix.iter().cloned());
+        }
+    }
+
+    /// Apply `register` to the node of `route`, creating the nodes on the way if needed
+    fn register_with(
         &mut self,
         mut route: <RouteSections as IntoIterator>::IntoIter,
-        handler:   Handler,
+        register:  impl FnOnce(&mut Node) -> Result<(), String>,
     ) -> Result<(), String> {
         match route.next() {
-            None => {
-                self.set_handler(handler)?;
-                Ok(())
-            }
+            None => register(self),
             Some(pattern)   => match self.machable_child_mut(pattern.clone().into()) {
-                Some(child) => child.register_handler(route, handler),
+                Some(child) => child.register_with(route, register),
                 None        => {
                     let mut child = Node::new(pattern.into());
-                    child.register_handler(route, handler)?;
+                    child.register_with(route, register)?;
                     self.append_child(child)?;
                     Ok(())
                 }
@@ -336,30 +667,58 @@ impl Node {
         self.fangs_list.add(id, fangs);
     }
 
-    #[allow(unused_mut)]
-    fn into_radix(self) -> super::radix::Node {
-        let Node { pattern, mut fangs_list, mut handler, mut children } = self;
+    /// MUST be called after all handlers are registered
+    fn apply_states(&mut self, states: &States) {
+        for child in &mut self.children {
+            child.apply_states(states)
+        }
+
+        self.states.extend(states);
+    }
+
+    /// MUST be called after all handlers are registered
+    fn apply_rejection_mapper(&mut self, mapper: &Arc<RejectionMapper>) {
+        for child in &mut self.children {
+            child.apply_rejection_mapper(mapper)
+        }
+
+        /* the one set by an inner `Ohkami` takes precedence */
+        if self.rejection_mapper.is_none() {
+            self.rejection_mapper = Some(mapper.clone());
+        }
+    }
+
+    fn into_radix(self, ignore_case: bool, urls: Option<&Arc<Urls>>) -> super::radix::Node {
+        let Node { pattern, mut fangs_list, mut states, mut param_names, mut rejection_mapper, mut handler, mut guarded, mut children } = self;
 
         let mut patterns = pattern.into_iter().collect::<Vec<_>>();
 
-        /* In Cloudflare Workers, this compression may be nothing more than an overhead... */
-        #[cfg(not(feature="rt_worker"))]
-        while children.len() == 1 && handler.is_none() {
+        while children.len() == 1 && handler.is_none() && guarded.is_empty() {
             let Node {
                 pattern:    child_pattern,
                 fangs_list: child_fangses,
+                states:     mut child_states,
+                param_names:      child_param_names,
+                rejection_mapper: child_rejection_mapper,
                 handler:    child_handler,
+                guarded:    child_guarded,
                 children:   child_children,
             } = children.pop(/* pop the single child */).unwrap(/* `children` is empty here */);
 
             children = child_children;
 
             handler  = child_handler;
+            guarded  = child_guarded;
+            param_names = child_param_names;
+            rejection_mapper = child_rejection_mapper.or(rejection_mapper);
 
             fangs_list.extend(child_fangses);
+
+            child_states.extend(&states);
+            states = child_states;
             
             let child_pattern = child_pattern.unwrap(/* `child` is not root */);
-            if patterns.last().is_some_and(|last| !last.is_param()) && (!child_pattern.is_param()) {
+            if patterns.last().is_some_and(|last| last.to_static().is_some()) && child_pattern.to_static().is_some() {
                 let last_pattern = patterns.pop(/*=== POPing here ===*/).unwrap();
                 let this_static  = last_pattern.to_static().unwrap();
                 let child_static = child_pattern.to_static().unwrap();
@@ -372,18 +731,37 @@ impl Node {
             }
         }
 
-        children.sort_unstable_by(|a, b| match (a.pattern.as_ref().unwrap(), b.pattern.as_ref().unwrap()) {
-            (Pattern::Static(_), Pattern::Param)     => std::cmp::Ordering::Less,
-            (Pattern::Param, Pattern::Static(_))     => std::cmp::Ordering::Greater,
-            (Pattern::Static(a), Pattern::Static(b)) => <[u8]>::cmp(&a, &b).reverse(),
+        /*
+            Static patterns, mixed ones, constrained params, then unconstrained params.
+            Stable sort to try constrained params in registration order.
+        */
+        children.sort_by(|a, b| match (a.pattern.as_ref().unwrap(), b.pattern.as_ref().unwrap()) {
+            (Pattern::Static(_), Pattern::Param(_) | Pattern::Mixed(_)) => std::cmp::Ordering::Less,
+            (Pattern::Param(_) | Pattern::Mixed(_), Pattern::Static(_)) => std::cmp::Ordering::Greater,
+            (Pattern::Mixed(_), Pattern::Param(_))          => std::cmp::Ordering::Less,
+            (Pattern::Param(_), Pattern::Mixed(_))          => std::cmp::Ordering::Greater,
+            (Pattern::Static(a), Pattern::Static(b))        => <[u8]>::cmp(&a, &b).reverse(),
+            (Pattern::Param(Some(_)), Pattern::Param(None)) => std::cmp::Ordering::Less,
+            (Pattern::Param(None), Pattern::Param(Some(_))) => std::cmp::Ordering::Greater,
             _ => std::cmp::Ordering::Equal
         });
 
+        if !guarded.is_empty() {
+            handler = Some(guard::dispatch(guarded, handler));
+        }
+
         super::radix::Node {
-            patterns:  Box::leak(patterns.into_iter().map(Pattern::into_radix).collect()),
-            children:  Box::leak(children.into_iter().map(Node::into_radix).collect::<Box<[_]>>()),
-            proc:      fangs_list.clone().into_proc_with(handler.unwrap_or(Handler::default_not_found())),
-            __catch__: fangs_list.into_proc_with(Handler::default_not_found()),
+            patterns:  patterns.into_iter().map(|p| p.into_radix(ignore_case)).collect(),
+            children:  children.into_iter().map(|c| c.into_radix(ignore_case, urls)).collect(),
+            proc:      handler.map(|h| fangs_list.clone().into_proc_with(h)),
+            states:    (!states.is_empty()).then(|| Arc::new(states)),
+            route:     super::RouteData {
+                param_names: param_names.unwrap_or_default().into(),
+                rejection_mapper,
+                urls: urls.cloned(),
+            },
+            __catch__: fangs_list.clone().into_proc_with(Handler::default_not_found()),
+            __method_not_allowed__: fangs_list.into_proc_with(Handler::default_method_not_allowed()),
         }
     }
 }
@@ -397,7 +775,11 @@ impl Node {
         Self {
             pattern:    Some(pattern),
             handler:    None,
+            guarded:    vec![],
             fangs_list: FangsList::new(),
+            states:     States::new(),
+            param_names: None,
+            rejection_mapper: None,
             children:   vec![],
         }
     }
@@ -405,7 +787,11 @@ impl Node {
         Self {
             pattern:    None,
             handler:    None,
+            guarded:    vec![],
             fangs_list: FangsList::new(),
+            states:     States::new(),
+            param_names: None,
+            rejection_mapper: None,
             children:   vec![],
         }
     }
@@ -451,7 +837,7 @@ impl Node {
     ///     .register_handlers("/api/tasks/:id".GET (get_task));
     /// ```
     fn merge_here(&mut self, another_root: Node) -> Result<(), String> {
-        if self.handler.is_some() {
+        if self.handler.is_some() || !self.guarded.is_empty() {
             return Err(format!(
                 "Can't merge another Ohkami at route that already has handler"
             ))
@@ -460,17 +846,29 @@ impl Node {
         let Node {
             pattern: None, /* another_root must be a root node and has pattern `None` */
             fangs_list: another_root_fangses,
+            states:     another_root_states,
+            param_names:      another_root_param_names,
+            rejection_mapper: another_root_rejection_mapper,
             handler:    another_root_handler,
+            guarded:    another_root_guarded,
             children:   another_root_children,
         } = another_root else {
             panic!("Unexpectedly called `Node::merge_here` where `another_root` is not root node")
         };
         
         self.append_fangs(another_root_fangses);
+        self.states.extend(&another_root_states);
+        if another_root_param_names.is_some() {
+            self.param_names = another_root_param_names;
+        }
+        if self.rejection_mapper.is_none() {
+            self.rejection_mapper = another_root_rejection_mapper;
+        }
 
         if let Some(h) = another_root_handler {
             self.set_handler(h)?;
         }
+        self.guarded.extend(another_root_guarded);
 
         for ac in another_root_children {
             self.append_child(ac)?
@@ -482,18 +880,28 @@ impl Node {
 
 impl Node {
     fn append_child(&mut self, new_child: Node) -> Result<(), String> {
-        match new_child.pattern.as_ref().expect("Invalid child node: Child node must have pattern") {
-            Pattern::Param => {
+        let __position__ = || match &self.pattern {
+            None    => format!("For the first part of route"),
+            Some(p) => format!("After {p:?}"),
+        };
+
+        let new_pattern = new_child.pattern.as_ref().expect("Invalid child node: Child node must have pattern");
+        if let Some(conflicting) = self.children.iter().map(|c| c.pattern.as_ref().unwrap()).find(|p| match (new_pattern, p) {
+            (Pattern::Mixed(_), Pattern::Param(_)) | (Pattern::Param(_), Pattern::Mixed(_)) => true,
+            (Pattern::Mixed(new), Pattern::Mixed(existing)) => new.overlaps(existing),
+            _ => false,
+        }) {
+            return Err(format!("Conflicting route definition: {}, pattern {new_pattern:?} conflicts with {conflicting:?}", __position__()))
+        }
+
+        match new_pattern {
+            Pattern::Param(_) | Pattern::Mixed(_) => {
                 self.children.push(new_child);
                 Ok(())
             }
             Pattern::Static(bytes) => {
                 if self.children.iter().find(|c| c.pattern.as_ref().unwrap().to_static().is_some_and(|p| p == bytes.as_ref())).is_some() {
-                    let __position__ = match &self.pattern {
-                        None    => format!("For the first part of route"),
-                        Some(p) => format!("After {p:?}"),
-                    };
-                    Err(format!("Conflicting route definition: {__position__}, pattern '{}' is registered twice", std::str::from_utf8(&bytes).unwrap()))
+                    Err(format!("Conflicting route definition: {}, pattern '{}' is registered twice", __position__(), std::str::from_utf8(&bytes).unwrap()))
                 } else {
                     self.children.push(new_child);
                     Ok(())
@@ -514,32 +922,28 @@ impl Node {
 }
 
 impl Pattern {
-    fn is_param(&self) -> bool {
-        match self {
-            Self::Param => true,
-            Self::Static(_) => false,
-        }
-    }
-
     fn to_static(&self) -> Option<&[u8]> {
         match self {
-            Self::Param         => None,
+            Self::Param(_) | Self::Mixed(_) => None,
             Self::Static(bytes) => Some(&bytes)
         }
     }
 
     fn matches(&self, another: &Self) -> bool {
-        match self {
-            Self::Param => another.is_param(),
-            Self::Static{..} => self.to_static() == another.to_static(),
-        }
+        self == another
     }
 
-    fn into_radix(self) -> super::radix::Pattern {
+    fn into_radix(self, ignore_case: bool) -> super::radix::Pattern {
         match self {
-            Self::Param                        => super::radix::Pattern::Param,
-            Self::Static(Cow::Borrowed(bytes)) => super::radix::Pattern::Static(bytes),
-            Self::Static(Cow::Owned(vec))      => super::radix::Pattern::Static(vec.leak()),
+            Self::Param(c) => super::radix::Pattern::Param(c),
+            Self::Mixed(m) => super::radix::Pattern::Mixed(m, ignore_case),
+            Self::Static(bytes) => {
+                if ignore_case {
+                    super::radix::Pattern::StaticIgnoreCase(bytes)
+                } else {
+                    super::radix::Pattern::Static(bytes)
+                }
+            }
         }
     }
 }
@@ -0,0 +1,203 @@
+use std::{collections::HashMap, fmt::Display};
+use ohkami_lib::percent_encode;
+
+
+/// Name of a route having `N` path params.
+///
+/// Used with `.name(..)` at registration, the number of params is
+/// checked when building `Ohkami`, and used in `url_for`, the number
+/// of passed params is checked at compile time :
+///
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::RouteName;
+///
+/// const USER: RouteName<1> = RouteName::new("user");
+///
+/// async fn create_user(req: &Request) -> Result<Response, ohkami::UrlError> {
+///     let id = 42;
+///     let location = req.url_for(USER, [&id])?;
+///     Ok(Response::Created().with_headers(|h| h
+///         .Location(location)
+///     ))
+/// }
+///
+/// # async fn show_user(id: u64) -> String {format!("{id}")}
+/// # let _ =
+/// Ohkami::new((
+///     "/users"
+///         .POST(create_user),
+///     "/users/:id"
+///         .GET(show_user)
+///         .name(USER),
+/// ))
+/// # ;
+/// ```
+///
+/// A plain `&'static str` can be used in place of this, without
+/// the checks of the number of params.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RouteName<const N: usize>(&'static str);
+impl<const N: usize> RouteName<N> {
+    pub const fn new(name: &'static str) -> Self {
+        Self(name)
+    }
+    pub const fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+impl<const N: usize> From<&'static str> for RouteName<N> {
+    fn from(name: &'static str) -> Self {
+        Self(name)
+    }
+}
+
+/// `&'static str` or `RouteName<N>`
+pub trait IntoRouteName {
+    /// `(name, expected number of params)`
+    fn into_route_name(self) -> (&'static str, Option<usize>);
+}
+impl IntoRouteName for &'static str {
+    fn into_route_name(self) -> (&'static str, Option<usize>) {
+        (self, None)
+    }
+}
+impl<const N: usize> IntoRouteName for RouteName<N> {
+    fn into_route_name(self) -> (&'static str, Option<usize>) {
+        (self.0, Some(N))
+    }
+}
+
+
+/// Error of `url_for`, responded as `500 Internal Server Error`
+#[derive(Clone, Debug, PartialEq)]
+pub enum UrlError {
+    /// No route is named this
+    UnknownName(&'static str),
+    /// The route has `expected` params, but `given` ones are given
+    WrongParams { name: &'static str, expected: usize, given: usize },
+}
+const _: () = {
+    impl std::fmt::Display for UrlError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::UnknownName(name) => write!(f, "No route is named `{name}`"),
+                Self::WrongParams { name, expected, given } => write!(f, "Route `{name}` has {expected} param(s), but {given} given"),
+            }
+        }
+    }
+    impl std::error::Error for UrlError {}
+
+    impl crate::IntoResponse for UrlError {
+        fn into_response(self) -> crate::Response {
+            crate::warning!("[url_for] {self}");
+            crate::Response::InternalServerError()
+        }
+    }
+};
+
+
+/// Route templates by their names
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Urls(HashMap<&'static str, String>);
+
+impl Urls {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn register(&mut self, name: &'static str, template: String) -> Result<(), String> {
+        if self.0.contains_key(name) {
+            return Err(format!("Duplicate route name `{name}`"))
+        }
+        self.0.insert(name, template);
+        Ok(())
+    }
+
+    pub(crate) fn merge(&mut self, prefix: &str, another: Urls) -> Result<(), String> {
+        for (name, template) in another.0 {
+            self.register(name, super::join_route(prefix, &template))?
+        }
+        Ok(())
+    }
+
+    pub(crate) fn build(&self, name: &'static str, params: &[&dyn Display]) -> Result<String, UrlError> {
+        let template = self.0.get(name)
+            .ok_or(UrlError::UnknownName(name))?;
+
+        let n_params = count_params(template);
+        if n_params != params.len() {
+            return Err(UrlError::WrongParams { name, expected: n_params, given: params.len() })
+        }
+
+        if template == "/" {
+            return Ok(template.clone())
+        }
+
+        let mut url = String::with_capacity(template.len());
+        let mut params = params.iter();
+        for section in template.split('/').skip(1) {
+            url.push('/');
+            if super::is_mixed_section(section) {
+                let mut pieces = section.split(':');
+                url.push_str(pieces.next().unwrap());
+                for piece in pieces {
+                    let name_len = piece.bytes().take_while(|b| *b == b'_' || b.is_ascii_alphanumeric()).count();
+                    url.push_str(&percent_encode(&params.next().unwrap().to_string()));
+                    url.push_str(&piece[name_len..]);
+                }
+            } else if section.starts_with(':') {
+                url.push_str(&percent_encode(&params.next().unwrap().to_string()))
+            } else {
+                url.push_str(section)
+            }
+        }
+        Ok(url)
+    }
+}
+
+/// Names of the params, like `["version", "id"]` for `/v:version/users/:id<u64>`
+pub(super) fn param_names(template: &str) -> Vec<String> {
+    template.split('/').flat_map(|section| section.split(':').skip(1)).map(|piece| {
+        let name_len = piece.bytes().take_while(|b| *b == b'_' || b.is_ascii_alphanumeric()).count();
+        piece[..name_len].to_string()
+    }).collect()
+}
+
+pub(super) fn count_params(template: &str) -> usize {
+    template.split('/').map(|section| match section {
+        s if super::is_mixed_section(s) => s.matches(':').count(),
+        s if s.starts_with(':')         => 1,
+        _                               => 0,
+    }).sum()
+}
+
+
+impl crate::Request {
+    /// Generate the URL path of the route named `name` with `params`,
+    /// each of them percent-encoded.
+    ///
+    /// See `RouteName` for an example.
+    ///
+    /// Available in the handler and fangs of a route. Returns `Err` if no route
+    /// is named `name`, or the route has different number of params (never when
+    /// `name` is a `RouteName` registered by `.name(..)`, checked when building `Ohkami`).
+    pub fn url_for<const N: usize>(&self, name: impl Into<RouteName<N>>, params: [&dyn Display; N]) -> Result<String, UrlError> {
+        let name = name.into();
+        match self.route().and_then(|route| route.urls.as_ref()) {
+            None       => Err(UrlError::UnknownName(name.0)),
+            Some(urls) => urls.build(name.0, &params),
+        }
+    }
+}
+
+impl crate::Ohkami {
+    /// Generate the URL path of the route named `name` in this `Ohkami`.
+    /// (For a nested `Ohkami`, it's relative to the route where it's nested.)
+    ///
+    /// Returns `Err` if no route is named `name`, or the route has different
+    /// number of params.
+    pub fn url_for<const N: usize>(&self, name: impl Into<RouteName<N>>, params: [&dyn Display; N]) -> Result<String, UrlError> {
+        self.routes.urls().build(name.into().0, &params)
+    }
+}
@@ -30,6 +30,17 @@ macro_rules! assert_parse {
     };
 }
 
+#[test]
+fn parse_method() {
+    assert_eq!(Method::from_bytes(b"GET"),   Some(Method::GET));
+    assert_eq!(Method::from_bytes(b"TRACE"), Some(Method::TRACE));
+    assert_eq!(Method::from_bytes(b"PROPFIND").map(|m| m.to_string()), Some(String::from("PROPFIND")));
+    assert_eq!(Method::from_bytes(b"CONNECT"), None);
+    assert_eq!(Method::from_bytes(b""), None);
+    assert_eq!(Method::from_bytes(b"GET/"), None);
+    assert_eq!(Method::from_bytes(b"A_VERY_LONG_EXTENSION_METHOD"), None);
+}
+
 fn metadataize(input: &str) -> Box<[u8; BUF_SIZE]> {
     let mut buf = [0; BUF_SIZE];
     buf[..input.len().min(BUF_SIZE)]
@@ -79,6 +90,8 @@ fn parse_path() {
         ], None),
         payload: None,
         store:   Store::init(),
+        states:  None,
+        route:   None,
     });
 
 
@@ -109,6 +122,8 @@ fn parse_path() {
             br#"{"name":"kanarus","age":20}"#
         ))),
         store: Store::init(),
+        states: None,
+        route:  None,
     });
 
     {
@@ -154,6 +169,8 @@ fn parse_path() {
             ),
             payload: Some(CowSlice::Own(Vec::from("first_name=John&last_name=Doe&action=Submit").into())),
             store:   Store::init(),
+            states:  None,
+            route:   None,
             // #[cfg(feature="websocket")] upgrade_id: None,
         });
     }
@@ -58,6 +58,63 @@ const _: () = {
         }
     }
 };
+
+/// `FromRequest` but asynchronous, to extract something by async operations
+/// like looking up a session store or verifying a token by an external service :
+/// 
+/// ---
+/// *example.rs*
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::AsyncFromRequest;
+/// 
+/// struct User {
+///     name: String,
+/// }
+/// 
+/// async fn find_user_by_token(token: &str) -> Option<User> {
+///     // ...
+/// # Some(User { name: token.into() })
+/// }
+/// 
+/// impl<'req> AsyncFromRequest<'req> for User {
+///     type Error = Response;
+///     async fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+///         let token = req.headers.Authorization()?.strip_prefix("Bearer ")?;
+///         Some(find_user_by_token(token).await.ok_or_else(Response::Unauthorized))
+///     }
+/// }
+/// 
+/// async fn profile(user: User) -> String {
+///     format!("Hello, {}!", user.name)
+/// }
+/// ```
+/// ---
+/// 
+/// This can be used in handler args together with `FromRequest` items,
+/// with the same semantics : `None` is `400 Bad Request`, and `Err` is
+/// responded as `IntoResponse`.
+/// 
+/// NOTE: *MUST NOT impl both `FromRequest` and `AsyncFromRequest`*.
+pub trait AsyncFromRequest<'req>: Sized {
+    /// If this extraction never fails, `std::convert::Infallible` is recomended.
+    type Error: IntoResponse;
+
+    fn from_request(req: &'req Request) -> impl std::future::Future<Output = Option<Result<Self, Self::Error>>> + Send;
+}
+const _: () = {
+    impl<'req, AFR: AsyncFromRequest<'req>> AsyncFromRequest<'req> for Option<AFR> {
+        type Error = AFR::Error;
+        #[inline]
+        async fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+            match AFR::from_request(req).await {
+                None      => Some(Ok(None)),
+                Some(afr) => Some(afr.map(Some))
+            }
+        }
+    }
+};
+
 #[cfg(feature="rt_worker")]
 const _: () = {
     impl<'req> FromRequest<'req> for &'req ::worker::Env {
@@ -182,6 +182,18 @@ macro_rules! Header {
                     .get(&Slice::from_bytes(name.as_bytes()))?;
                 Some(std::str::from_utf8(unsafe {value.as_bytes()}).expect("Header value is not UTF-8"))
             }
+
+            /// Get the value of header `name`, standard or custom, case-insensitively
+            pub fn by_name(&self, name: &str) -> Option<&str> {
+                let standard = Header::from_bytes(name.as_bytes())
+                    .or_else(|| Header::from_bytes(name.to_ascii_lowercase().as_bytes()));
+                match standard {
+                    Some(header) => self.get(header),
+                    None => self.custom.as_ref()?.iter()
+                        .find(|(k, _)| unsafe {k.as_bytes()}.eq_ignore_ascii_case(name.as_bytes()))
+                        .map(|(_, v)| std::str::from_utf8(unsafe {v.as_bytes()}).expect("Header value is not UTF-8"))
+                }
+            }
         }
 
         // =================================================
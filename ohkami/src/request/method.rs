@@ -1,4 +1,4 @@
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Method {
     GET,
     PUT,
@@ -7,12 +7,45 @@ pub enum Method {
     DELETE,
     HEAD,
     OPTIONS,
+    TRACE,
+    /// Extension method like `PROPFIND`, `MKCOL` (WebDAV) or `QUERY`
+    Custom(CustomMethod),
+}
+
+/// Name of an extension HTTP method, stored inline
+/// (at most `CustomMethod::MAX_LEN` bytes)
+///
+/// On `rt_worker`, Workers only pass standard methods to the worker,
+/// so routes of custom methods are registered but never reached there.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomMethod {
+    len:  u8,
+    name: [u8; CustomMethod::MAX_LEN],
+}
+impl CustomMethod {
+    pub const MAX_LEN: usize = 24;
+
+    #[inline] pub fn as_str(&self) -> &str {
+        // SAFETY: `name[..len]` is validated as a `token` of RFC 9110 in `Method::from_bytes`
+        unsafe {std::str::from_utf8_unchecked(self.name.get_unchecked(..self.len as usize))}
+    }
 }
 
 impl Method {
-    #[cfg(any(
-        all(feature="testing",feature="rt_worker"),
-        feature="rt_tokio",feature="rt_async-std"))]
+    /// Returns `None` if `name` is `CONNECT` (not supported),
+    /// not a valid method name, or longer than `CustomMethod::MAX_LEN`
+    ///
+    /// ```
+    /// use ohkami::Method;
+    ///
+    /// assert_eq!(Method::from_name("GET"), Some(Method::GET));
+    /// assert_eq!(Method::from_name("PROPFIND").unwrap().name(), "PROPFIND");
+    /// assert_eq!(Method::from_name("CONNECT"), None);
+    /// ```
+    pub const fn from_name(name: &str) -> Option<Self> {
+        Self::from_bytes(name.as_bytes())
+    }
+
     #[inline(always)] pub(crate) const fn from_bytes(bytes: &[u8]) -> Option<Self> {
         match bytes {
             b"GET"     => Some(Self::GET),
@@ -22,7 +55,21 @@ impl Method {
             b"DELETE"  => Some(Self::DELETE),
             b"HEAD"    => Some(Self::HEAD),
             b"OPTIONS" => Some(Self::OPTIONS),
-            _ => None
+            b"TRACE"   => Some(Self::TRACE),
+            b"CONNECT" => None,
+            _ => {
+                if bytes.is_empty() || bytes.len() > CustomMethod::MAX_LEN {
+                    return None
+                }
+
+                let mut name = [0; CustomMethod::MAX_LEN];
+                let mut i = 0; while i < bytes.len() {
+                    if !is_tchar(bytes[i]) {return None}
+                    name[i] = bytes[i];
+                    i += 1;
+                }
+                Some(Self::Custom(CustomMethod { len: bytes.len() as u8, name }))
+            }
         }
     }
     #[cfg(feature="rt_worker")]
@@ -35,11 +82,25 @@ impl Method {
             ::worker::Method::Delete  => Some(Self::DELETE),
             ::worker::Method::Head    => Some(Self::HEAD),
             ::worker::Method::Options => Some(Self::OPTIONS),
+            ::worker::Method::Trace   => Some(Self::TRACE),
             _ => None
         }
     }
 
-    #[inline] pub const fn as_str(&self) -> &'static str {
+    /// Same as `name`
+    #[inline] pub fn as_str(&self) -> &str {
+        self.name()
+    }
+
+    /// Name of the method including extension ones
+    ///
+    /// ```
+    /// use ohkami::Method;
+    ///
+    /// assert_eq!(Method::GET.name(), "GET");
+    /// assert_eq!(Method::from_name("MKCOL").unwrap().name(), "MKCOL");
+    /// ```
+    #[inline] pub fn name(&self) -> &str {
         match self {
             Self::GET     => "GET",
             Self::PUT     => "PUT",
@@ -48,6 +109,8 @@ impl Method {
             Self::DELETE  => "DELETE",
             Self::HEAD    => "HEAD",
             Self::OPTIONS => "OPTIONS",
+            Self::TRACE   => "TRACE",
+            Self::Custom(c) => c.as_str(),
         }
     }
 }
@@ -73,34 +136,35 @@ impl Method {
     pub const fn isOPTIONS(&self) -> bool {
         matches!(self, Method::OPTIONS)
     }
+    pub const fn isTRACE(&self) -> bool {
+        matches!(self, Method::TRACE)
+    }
+}
+
+/// `tchar` of RFC 9110
+const fn is_tchar(b: u8) -> bool {
+    matches!(b,
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' |
+        b'^' | b'_' | b'`' | b'|' | b'~' | b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z'
+    )
 }
 
 const _: () = {
     impl std::fmt::Display for Method {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            f.write_str(match self {
-                Self::GET     => "GET",
-                Self::PUT     => "PUT",
-                Self::POST    => "POST",
-                Self::PATCH   => "PATCH",
-                Self::DELETE  => "DELETE",
-                Self::HEAD    => "HEAD",
-                Self::OPTIONS => "OPTIONS",
-            })
+            f.write_str(self.name())
         }
     }
-    
+
     impl std::fmt::Debug for Method {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            f.write_str(match self {
-                Self::GET     => "GET",
-                Self::PUT     => "PUT",
-                Self::POST    => "POST",
-                Self::PATCH   => "PATCH",
-                Self::DELETE  => "DELETE",
-                Self::HEAD    => "HEAD",
-                Self::OPTIONS => "OPTIONS",
-            })
+            f.write_str(self.name())
+        }
+    }
+
+    impl std::fmt::Debug for CustomMethod {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.as_str())
         }
     }
 };
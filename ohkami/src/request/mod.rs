@@ -1,5 +1,5 @@
 mod method;
-pub use method::Method;
+pub use method::{Method, CustomMethod};
 
 mod path;
 pub(crate) use path::Path;
@@ -16,9 +16,19 @@ mod memory;
 pub(crate) use memory::Store;
 pub use memory::Memory;
 
+mod state;
+pub(crate) use state::States;
+pub use state::State;
+
 mod from_request; 
 pub use from_request::*;
 
+mod rejection;
+pub use rejection::{Rejection, RejectionKind};
+pub(crate) use rejection::Tracked;
+#[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+pub(crate) use rejection::RejectionMapper;
+
 #[cfg(test)] mod _test_parse;
 #[cfg(test)] mod _test_extract;
 #[cfg(test)] mod _test_headers;
@@ -115,9 +125,10 @@ pub struct Request {
     /// 
     /// ---
     /// 
-    /// **Note** : In current version, custom HTTP methods are *not supported*,
-    /// in other words, now Ohkami just knows `GET`, `PUT`, `POST`, `PATCH`,
-    /// `DELETE`, `HEAD`, `OPTIONS`.
+    /// Extension methods like `PROPFIND` are `Method::Custom`.
+    /// 
+    /// **Note** : `CONNECT` is *not supported*, and on `rt_worker`
+    /// extension methods are not supported either.
     pub method: Method,
 
     /// Request path of this request
@@ -140,10 +151,10 @@ pub struct Request {
     /// 
     /// ---
     /// 
-    /// **Note** : Ohkami doesn't support multiple same query keys having each value
-    /// like `?ids=1&ids=17&ids=42`.
-    /// Please use, for instance, comma-separated format like
-    /// `?ids=1,17,42` ( URL-encoded to `?ids=1%2C17%2C42` )
+    /// **Note** : `.parse()` accepts sequences as repeated keys `?ids=1&ids=17&ids=42`,
+    /// bracketed keys `?ids[]=1&ids[]=17` / `?ids[0]=1&ids[1]=17`, or comma-separated
+    /// `?ids=1,17,42`, nested structs as `?user[name]=ohkami`, and a valueless key
+    /// like `?verbose` as `true`.
     pub query: Option<QueryParams>,
 
     /// Headers of this request
@@ -163,6 +174,10 @@ pub struct Request {
 
     pub(crate) payload: Option<CowSlice>,
     store: Store,
+    pub(crate) states: Option<std::sync::Arc<States>>,
+    /// set while the router handles this request
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    pub(crate) route: Option<crate::ohkami::router::RouteRef>,
 }
 
 impl Request {
@@ -186,9 +201,19 @@ impl Request {
             headers: RequestHeaders::init(),
             payload: None,
             store:   Store::init(),
+            states:  None,
+            route:   None,
         }
     }
 
+    /// Data of the route this request is routed to, while the router handles it
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    #[inline]
+    pub(crate) fn route(&self) -> Option<&crate::ohkami::router::RouteData> {
+        // SAFETY: `route` is set only while the router handles this request
+        self.route.map(|route| unsafe {route.get()})
+    }
+
     #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
     #[inline]
     pub(crate) async fn read(
@@ -217,10 +242,8 @@ impl Request {
             Slice::from_bytes(&*self.__buf__).as_bytes()
         });
 
-        match Method::from_bytes(r.read_while(|b| b != &b' ')) {
-            None => return Ok(None),
-            Some(method) => self.method = method
-        }
+        self.method = Method::from_bytes(r.read_while(|b| b != &b' '))
+            .ok_or_else(Response::NotImplemented)?;
 
         r.next_if(|b| *b==b' ').ok_or_else(Response::BadRequest)?;
         
@@ -308,10 +331,8 @@ impl Request {
 
         let mut r = Reader::new(raw_bytes);
 
-        match Method::from_bytes(r.read_while(|b| b != &b' ')) {
-            None => return Ok(None),
-            Some(method) => self.method = method
-        }
+        self.method = Method::from_bytes(r.read_while(|b| b != &b' '))
+            .ok_or_else(Response::NotImplemented)?;
 
         r.next_if(|b| *b==b' ').ok_or_else(Response::BadRequest)?;
         
@@ -327,6 +348,8 @@ impl Request {
             self.path.init_with_request_bytes(path)?;
         }
 
+        r.next_if(|b| *b==b' ').ok_or_else(Response::BadRequest)?;
+
         r.consume("HTTP/1.1\r\n").ok_or_else(Response::HTTPVersionNotSupported)?;
 
         while r.consume("\r\n").is_none() {
@@ -363,7 +386,7 @@ impl Request {
         self.ctx.write(ctx);
 
         self.method = Method::from_worker(req.method())
-            .ok_or_else(|| Response::NotImplemented().with_text("ohkami doesn't support `CONNECT`, `TRACE` method"))?;
+            .ok_or_else(|| Response::NotImplemented().with_text("ohkami doesn't support `CONNECT` method"))?;
 
         self.__url__.write(req.url()
             .map_err(|_| Response::BadRequest().with_text("Invalid request URL"))?
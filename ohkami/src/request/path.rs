@@ -3,11 +3,18 @@ use ohkami_lib::{percent_decode_utf8, Slice};
 
 
 pub struct Path(
-    MaybeUninit<PathInner>
+    MaybeUninit<PathInner>,
+    /// Canonical path set by `PathPolicy`, owned here instead of
+    /// being written into the request buffer
+    #[allow(unused/* without rt_* features */)]
+    Option<Box<[u8]>>,
 );
 pub(crate) struct PathInner {
     raw:    Slice,
     params: Params,
+    /// whether the trailing '/' has been stripped from `raw`
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    trailing_slash: bool,
 }
 struct Params {
     next: usize,
@@ -16,6 +23,12 @@ struct Params {
 impl Params {
     const LIMIT: usize = 2;
 }
+#[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+impl Path {
+    /// At most `PARAMS_LIMIT` params, including a subdomain captured
+    /// by wildcard host, can be held for a request
+    pub(crate) const PARAMS_LIMIT: usize = Params::LIMIT;
+}
 
 const _: () = {
     impl Params {
@@ -98,7 +111,7 @@ const _: () = {
     impl Path {
         #[inline]
         pub(crate) fn uninit() -> Self {
-            Self(MaybeUninit::uninit())
+            Self(MaybeUninit::uninit(), None)
         }
 
         #[inline(always)]
@@ -116,18 +129,28 @@ const _: () = {
             returns `b"/"` if that bytes is `b"/"`.
             */
             let mut len = bytes.len();
+            let trailing_slash = unsafe {*bytes.get_unchecked(len-1) == b'/'} && len > 1;
             if unsafe {*bytes.get_unchecked(len-1) == b'/'} {len -= 1};
 
             #[allow(unused_unsafe/* I don't know why but rustc sometimes put warnings to this unsafe as unnecessary */)]
             Ok({self.0.write(PathInner {
                 raw:    unsafe {Slice::new_unchecked(bytes.as_ptr(), len)},
                 params: Params::init(),
+                trailing_slash,
             });})
         }
 
         #[inline] pub(crate) unsafe fn push_param(&mut self, param: Slice) {
             self.0.assume_init_mut().params.push(param)
         }
+        #[inline] pub(crate) unsafe fn params_len(&self) -> usize {
+            self.0.assume_init_ref().params.next
+        }
+        /// Drop params pushed after `len` params, mainly for backtracking in routing
+        #[inline] pub(crate) unsafe fn truncate_params(&mut self, len: usize) {
+            let params = &mut self.0.assume_init_mut().params;
+            params.next = params.next.min(len);
+        }
         #[inline] pub(crate) unsafe fn assume_one_param<'p>(&self) -> &'p [u8] {
             self.0.assume_init_ref().params.list.get_unchecked(0).assume_init_ref().as_bytes()
         }
@@ -138,6 +161,18 @@ const _: () = {
         #[inline] pub(crate) unsafe fn normalized_bytes<'req>(&self) -> &'req [u8] {
             self.0.assume_init_ref().raw.as_bytes()
         }
+        #[inline] pub(crate) unsafe fn has_trailing_slash(&self) -> bool {
+            self.0.assume_init_ref().trailing_slash
+        }
+        /// Replace the path with `canonical`, held by this `Path` itself.
+        /// 
+        /// SAFETY: no params are pushed yet
+        #[inline] pub(crate) unsafe fn set_canonical(&mut self, canonical: Vec<u8>) {
+            let canonical = self.1.insert(canonical.into_boxed_slice());
+            let inner = self.0.assume_init_mut();
+            inner.raw = Slice::new_unchecked(canonical.as_ptr(), canonical.len());
+            inner.trailing_slash = false;
+        }
     }
 };
 
@@ -147,6 +182,7 @@ const _: () = {
         Self(MaybeUninit::new(PathInner {
             raw:    Slice::from_bytes(literal.as_bytes()),
             params: Params::init(),
-        }))
+            trailing_slash: false,
+        }), None)
     }
 }
@@ -17,6 +17,12 @@ impl QueryParams {
         Self(Slice::from_bytes(bytes))
     }
 
+    /// raw bytes without leading '?'
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    #[inline(always)] pub(crate) fn as_bytes(&self) -> &[u8] {
+        unsafe {self.0.as_bytes()}
+    }
+
     #[inline(always)] pub fn parse<'q, T: serde::Deserialize<'q>>(
         &'q self
     ) -> Result<T, impl serde::de::Error> {
@@ -36,12 +42,14 @@ impl QueryParams {
 
         unsafe {self.0.as_bytes()}
             .split(|b| b==&b'&')
-            .map(|kv| {
-                let eq = kv.iter().position(|b| b==&b'=').expect("invalid query params: missing `=`");
-                (
+            .filter(|kv| !kv.is_empty())
+            .map(|kv| match kv.iter().position(|b| b==&b'=') {
+                Some(eq) => (
                     decoded_utf8(unsafe {kv.get_unchecked(..eq)}),
                     decoded_utf8(unsafe {kv.get_unchecked(eq+1..)})
-                )
+                ),
+                /* valueless flag like `?verbose` */
+                None => (decoded_utf8(kv), Cow::Borrowed(""))
             })
     }
 }
@@ -74,3 +82,14 @@ const _: () = {
         }
     }
 };
+
+impl super::Request {
+    /// Query params parsed into `T`, rejected with the field where it failed, for `#[Query]`
+    #[doc(hidden)]
+    pub fn __query<'q, T: serde::Deserialize<'q>>(
+        &'q self
+    ) -> Option<Result<T, crate::Rejection>> {
+        let query = self.query.as_ref()?;
+        Some(query.parse::<super::Tracked<T>>().map(|super::Tracked(this)| this).map_err(crate::Rejection::query))
+    }
+}
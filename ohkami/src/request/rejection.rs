@@ -0,0 +1,283 @@
+use crate::{IntoResponse, Response};
+use crate::typed::validate::ValidationErrors;
+
+
+/// Information of a failed extraction of a handler's argument.
+///
+/// Without a mapper, `response` is just responded. `Ohkami::map_rejection`
+/// sets a mapper building the response from this, for example, to render
+/// every rejection in the app's own error format :
+///
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::{Rejection, RejectionKind};
+///
+/// fn to_json_error(r: Rejection) -> Response {
+///     let code = match r.kind {
+///         RejectionKind::Missing => "missing",
+///         RejectionKind::Param   => "invalid_param",
+///         RejectionKind::Query   => "invalid_query",
+///         RejectionKind::Payload => "invalid_payload",
+///         RejectionKind::Header  => "invalid_header",
+///         RejectionKind::Validation => "validation_failed",
+///         RejectionKind::Custom  => return r.response,
+///     };
+///     Response::BadRequest().with_json(ohkami::serde::json::json!({
+///         "code":    code,
+///         "field":   r.field,
+///         "message": r.message,
+///     }))
+/// }
+///
+/// # let _ =
+/// Ohkami::new((
+///     "/".GET(|| async {"Hello!"}),
+/// )).map_rejection(to_json_error)
+/// # ;
+/// ```
+#[derive(Debug)]
+pub struct Rejection {
+    /// Type name of the extractor, like `Option<my_app::UserQuery>`
+    pub extractor: &'static str,
+    pub kind:      RejectionKind,
+    /// Name of the field, param or header, if known
+    pub field:     Option<String>,
+    /// Reason of the rejection, like the message of the serde error
+    pub message:   String,
+    /// Errors of `#[validate(...)]`, empty unless `kind` is `Validation`
+    pub errors:    ValidationErrors,
+    /// Response responded by default
+    pub response:  Response,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionKind {
+    /// The extractor found nothing to extract
+    Missing,
+    /// Invalid path param
+    Param,
+    /// Invalid query params
+    Query,
+    /// Invalid request payload
+    Payload,
+    /// Missing or invalid request header
+    Header,
+    /// Failed `#[validate(...)]` of a `#[Payload]` or `#[Query]` type
+    Validation,
+    /// Error of a user-defined extractor
+    Custom,
+}
+
+impl Rejection {
+    fn new(kind: RejectionKind, field: Option<String>, message: String, response: Response) -> Self {
+        Self { extractor: "", kind, field, message, errors: ValidationErrors::new(), response }
+    }
+
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    pub(crate) fn missing() -> Self {
+        const MESSAGE: &str = "missing something expected in request";
+        Self::new(RejectionKind::Missing, None, MESSAGE.into(), Response::BadRequest().with_text(MESSAGE))
+    }
+
+    pub fn query(error: impl std::fmt::Display) -> Self {
+        let (path, message) = split_path(error.to_string());
+        let response = Response::BadRequest().with_text(format!("Unexpected query parameters: {message}"));
+        Self::new(RejectionKind::Query, field_at(path, &message), message, response)
+    }
+
+    pub fn payload(error: impl std::fmt::Display) -> Self {
+        let (path, message) = split_path(error.to_string());
+        let response = Response::BadRequest().with_text(message.clone());
+        Self::new(RejectionKind::Payload, field_at(path, &message), message, response)
+    }
+
+    pub fn header(name: &str, error: impl std::fmt::Display) -> Self {
+        Self::header_with(name, format!("Invalid header `{name}`: {error}"))
+    }
+
+    pub fn missing_header(name: &str) -> Self {
+        Self::header_with(name, format!("Missing header `{name}`"))
+    }
+
+    fn header_with(name: &str, message: String) -> Self {
+        let response = Response::BadRequest().with_text(message.clone());
+        Self::new(RejectionKind::Header, Some(name.into()), message, response)
+    }
+
+    pub fn validation(errors: ValidationErrors) -> Self {
+        let field = errors.iter().next().map(|e| e.field.into());
+        let message = errors.to_string();
+        let response = errors.clone().into_response();
+        Self { errors, ..Self::new(RejectionKind::Validation, field, message, response) }
+    }
+
+    /// Set `extractor` to the type name of `R` if not set yet
+    #[doc(hidden)]
+    pub fn __of<R>(mut self) -> Self {
+        if self.extractor.is_empty() {
+            self.extractor = std::any::type_name::<R>();
+        }
+        self
+    }
+
+    /// `name` of the param in the route, if known
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    pub(crate) fn of_param(mut self, name: Option<&str>) -> Self {
+        self.kind = RejectionKind::Param;
+        if self.field.is_none() {
+            self.field = name.map(String::from);
+        }
+        self
+    }
+
+    /// Rejection of a user-defined extractor's error
+    pub(crate) fn custom(message: String, response: Response) -> Self {
+        Self::new(RejectionKind::Custom, None, message, response)
+    }
+
+    /// Rejection of an extractor's `error`, kept as it is if `error` is
+    /// a `Rejection`, or validation errors and message if it has them
+    #[doc(hidden)]
+    #[cold] #[inline(never)]
+    pub fn __from<E: IntoResponse + 'static>(error: E) -> Self {
+        fn take<T: 'static>(error: &mut dyn std::any::Any) -> Option<T> {
+            error.downcast_mut::<Option<T>>()?.take()
+        }
+
+        let mut error = Some(error);
+        if let Some(rejection) = take::<Self>(&mut error) {
+            return rejection
+        }
+        if let Some(errors) = take::<ValidationErrors>(&mut error) {
+            return Self::validation(errors)
+        }
+        if let Some(message) = take::<crate::utils::ErrorMessage>(&mut error) {
+            return Self::custom(message.0.clone(), message.into_response())
+        }
+        Self::custom(String::new(), error.unwrap(/* not taken above */).into_response())
+    }
+}
+
+impl IntoResponse for Rejection {
+    #[inline]
+    fn into_response(self) -> Response {
+        self.response
+    }
+}
+
+/// Deserialize `T` by `serde_path_to_error`, prefixing the message of the error
+/// with the path to where it failed, which `Rejection::{query, payload}` split off
+pub(crate) struct Tracked<T>(pub(crate) T);
+
+const PATH_PREFIX: &str = "at `";
+const PATH_SUFFIX: &str = "`: ";
+
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Tracked<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_path_to_error::deserialize(deserializer).map(Self).map_err(|e| {
+            let path = e.path().to_string();
+            match path.as_str() {
+                "." => e.into_inner(),
+                _ => serde::de::Error::custom(format_args!(
+                    "{PATH_PREFIX}{path}{PATH_SUFFIX}{}", e.into_inner()
+                )),
+            }
+        })
+    }
+}
+
+/// Split the message of a `Tracked` error into the path and the original message
+fn split_path(message: String) -> (Option<String>, String) {
+    match message.strip_prefix(PATH_PREFIX).and_then(|rest| rest.split_once(PATH_SUFFIX)) {
+        Some((path, message)) => (Some(path.into()), message.into()),
+        None => (None, message),
+    }
+}
+
+/// `path` to the failed value, or else the field of serde's messages like
+/// "unknown field `nmae`". "missing field `name`" is reported on the struct
+/// having the field, so it's joined to the `path`.
+fn field_at(path: Option<String>, message: &str) -> Option<String> {
+    let field_of = |prefix: &str| message.split_once(prefix)
+        .and_then(|(_, rest)| rest.split_once('`'))
+        .map(|(field, _)| field);
+
+    match path {
+        Some(path) => Some(match field_of("missing field `") {
+            Some(field) => format!("{path}.{field}"),
+            None        => path,
+        }),
+        None => ["missing field `", "unknown field `", "duplicate field `"].into_iter()
+            .find_map(field_of)
+            .map(String::from),
+    }
+}
+
+/// Mapper set by `Ohkami::map_rejection`, held by the routes
+#[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+pub(crate) struct RejectionMapper(
+    pub(crate) Box<dyn Fn(Rejection) -> Response + Send + Sync>
+);
+
+#[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+impl crate::Request {
+    /// Name of the `index`-th param in the route. Params before the route's
+    /// ones, like the subdomain captured by wildcard host, have no name.
+    pub(crate) fn param_name(&self, index: usize) -> Option<&str> {
+        let names = &self.route()?.param_names;
+        // SAFETY: params are set in routing before this is called
+        let n_params = unsafe {self.path.params_len()};
+        names.get(names.len().checked_sub(n_params.checked_sub(index)?)?).map(String::as_str)
+    }
+
+    /// Response of the rejection, mapped by the mapper of the route if any
+    #[cold] #[inline(never)]
+    pub(crate) fn reject(&self, rejection: Rejection) -> Response {
+        match self.route().and_then(|route| route.rejection_mapper.as_ref()) {
+            Some(mapper) => (mapper.0)(rejection),
+            None => rejection.response,
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[test] fn test_tracked_field() {
+    #[derive(serde::Deserialize)]
+    #[allow(unused)]
+    struct User {
+        name:    String,
+        age:     u8,
+        address: Address,
+        tags:    Vec<Tag>,
+    }
+    #[derive(serde::Deserialize)]
+    #[allow(unused)]
+    struct Address {
+        city: String,
+    }
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(unused)]
+    struct Tag {
+        id: u64,
+    }
+
+    fn field_of(json: &str) -> Option<String> {
+        Rejection::payload(serde_json::from_str::<Tracked<User>>(json).err().unwrap()).field
+    }
+
+    assert_eq!(field_of(r#"{"name":"ohkami","age":"x","address":{"city":"Tokyo"},"tags":[]}"#).as_deref(), Some("age"));
+    assert_eq!(field_of(r#"{"name":"ohkami","age":300,"address":{"city":"Tokyo"},"tags":[]}"#).as_deref(), Some("age"));
+    assert_eq!(field_of(r#"{"name":"ohkami","age":2,"address":{"city":42},"tags":[]}"#).as_deref(), Some("address.city"));
+    assert_eq!(field_of(r#"{"name":"ohkami","age":2,"address":{"city":"Tokyo"},"tags":[{"id":1},{"id":-1}]}"#).as_deref(), Some("tags[1].id"));
+    assert_eq!(field_of(r#"{"age":2,"address":{"city":"Tokyo"},"tags":[]}"#).as_deref(), Some("name"));
+    assert_eq!(field_of(r#"{"name":"ohkami","age":2,"address":{},"tags":[]}"#).as_deref(), Some("address.city"));
+    assert_eq!(field_of(r#"{"name":"ohkami","age":2,"address":{"city":"Tokyo"},"tags":[{"id":1,"nmae":""}]}"#).as_deref(), Some("tags[0].nmae"));
+    assert_eq!(field_of(r#"[]"#), None);
+
+    /* untracked errors */
+    assert_eq!(Rejection::payload("missing field `name` at line 1 column 2").field.as_deref(), Some("name"));
+    assert_eq!(Rejection::query("unknown field `nmae`, expected `name`").field.as_deref(), Some("nmae"));
+    assert_eq!(Rejection::payload("invalid type: string \"x\", expected u8").field, None);
+}
@@ -0,0 +1,119 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+
+/// States of a route, set by `Ohkami::with_state`.
+///
+/// Just a few states are expected, so this is a list and not a map.
+#[derive(Clone)]
+pub(crate) struct States(
+    Vec<(TypeId, Arc<dyn Any + Send + Sync>)>
+);
+impl States {
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    pub(crate) fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Set `state`, replacing the one of the same type
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    pub(crate) fn insert<T: Send + Sync + 'static>(&mut self, state: T) {
+        let id = TypeId::of::<T>();
+        match self.0.iter_mut().find(|(t, _)| *t == id) {
+            Some((_, s)) => *s = Arc::new(state),
+            None => self.0.push((id, Arc::new(state))),
+        }
+    }
+
+    /// Add the states of types not set in `self`, so that the ones
+    /// already set ( by inner `Ohkami`s ) take precedence
+    #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_worker"))]
+    pub(crate) fn extend(&mut self, another: &States) {
+        for (id, state) in &another.0 {
+            if self.0.iter().all(|(t, _)| t != id) {
+                self.0.push((*id, state.clone()));
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        let id = TypeId::of::<T>();
+        self.0.iter()
+            .find(|(t, _)| *t == id)
+            .and_then(|(_, s)| s.downcast_ref())
+    }
+}
+
+
+/// # Router-level shared state
+///
+/// Retrieve the state set by `Ohkami::with_state`. The state is shared
+/// among all requests without per-request cost like `Memory`.
+///
+/// States are scoped : an `Ohkami` nested by `.By()` has the states of
+/// the outer one, and its own states of the same type override them.
+///
+/// When the state of the type is not set for the route, the handler
+/// responds `500 Internal Server Error`.
+///
+/// <br>
+///
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::State; // <--
+///
+/// #[derive(Clone)]
+/// struct Config {
+///     name: &'static str,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     Ohkami::new((
+///         "/hello".GET(hello),
+///     )).with_state(Config { name: "ohkami" }) // <--
+///         .howl("0.0.0.0:8080").await
+/// }
+///
+/// async fn hello(config: State<'_, Config>) -> String {
+///     /* `*{State<'_, T>}` is just `&'_ T` */
+///     let name = config.name;
+///
+///     format!("Hello, {name}!")
+/// }
+/// ```
+pub struct State<'req, T: Send + Sync + 'static>(&'req T);
+
+impl<'req, T: Send + Sync + 'static>
+super::FromRequest<'req> for State<'req, T> {
+    type Error = crate::Response;
+
+    #[inline]
+    fn from_request(req: &'req crate::Request) -> Option<Result<Self, Self::Error>> {
+        match req.states.as_ref().and_then(|states| states.get::<T>()) {
+            Some(state) => Some(Ok(State(state))),
+            None => {
+                crate::warning!(
+                    "`State` of type `{}` is not set for `{}`",
+                    std::any::type_name::<T>(),
+                    req.path.str()
+                );
+                Some(Err(crate::Response::InternalServerError()))
+            }
+        }
+    }
+}
+impl<'req, T: Send + Sync + 'static> std::ops::Deref for State<'req, T> {
+    type Target = &'req T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -173,7 +173,8 @@ macro_rules! Header {
             }
         }
     };
-} Header! {45;
+} Header! {46;
+    AcceptEncoding:                  b"Accept-Encoding",
     AcceptRanges:                    b"Accept-Ranges",
     AccessControlAllowCredentials:   b"Access-Control-Allow-Credentials",
     AccessControlAllowHeaders:       b"Access-Control-Allow-Headers",
@@ -189,7 +190,7 @@ macro_rules! Header {
     CDNCacheControl:                 b"CDN-Cache-Control",
     Connection:                      b"Connection",
     ContentDisposition:              b"Content-Disposition",
-    ContentEncoding:                 b"Content-Ecoding",
+    ContentEncoding:                 b"Content-Encoding",
     ContentLanguage:                 b"Content-Language",
     ContentLength:                   b"Content-Length",
     ContentLocation:                 b"Content-Location",
@@ -266,18 +267,21 @@ const _: () = {
             value: impl Into<Cow<'static, str>>,
             directives: impl FnOnce(SetCookieBuilder)->SetCookieBuilder
         ) -> Self {
-            let setcookie: Cow<'static, str> = directives(SetCookieBuilder::new(name, value)).build().into();
-            self.0.size += "Set-Cookie: ".len() + setcookie.len() + "\r\n".len();
-            match self.0.setcookie.as_mut() {
-                None             => self.0.setcookie = Some(Box::new(vec![setcookie])),
-                Some(setcookies) => setcookies.push(setcookie),
-            }
+            self.0.push_setcookie(directives(SetCookieBuilder::new(name, value)).build().into());
             self
         }
     }
 };
 
 impl Headers {
+    pub(crate) fn push_setcookie(&mut self, setcookie: Cow<'static, str>) {
+        self.size += "Set-Cookie: ".len() + setcookie.len() + "\r\n".len();
+        match self.setcookie.as_mut() {
+            None             => self.setcookie = Some(Box::new(vec![setcookie])),
+            Some(setcookies) => setcookies.push(setcookie),
+        }
+    }
+
     #[inline(always)]
     pub(crate) fn insert(&mut self, name: Header, value: Cow<'static, str>) {
         let (name_len, value_len) = (name.len(), value.len());
@@ -108,6 +108,19 @@ pub struct Response {
     pub headers: ResponseHeaders,
 
     pub(crate) content: Content,
+
+    /// Encoder of `Content::Stream` like `builtin::fang::Compress`, applied to each chunk in sending
+    #[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+    pub(crate) stream_encoder: Option<Box<dyn StreamEncoder>>,
+}
+
+/// Encoder of `Content::Stream` applied to each chunk in sending
+#[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+pub(crate) trait StreamEncoder: Send {
+    /// Encode `chunk`, returning the encoded bytes to be sent now (maybe empty)
+    fn encode(&mut self, chunk: &[u8]) -> Vec<u8>;
+    /// Finish encoding, returning the rest bytes
+    fn finish(self: Box<Self>) -> Vec<u8>;
 }
 
 impl Response {
@@ -117,6 +130,8 @@ impl Response {
             status,
             headers: ResponseHeaders::new(),
             content: Content::None,
+            #[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+            stream_encoder: None,
         }
     }
 
@@ -219,6 +234,27 @@ impl Response {
                 conn.write_all(&buf).await.expect("Failed to send response");
                 conn.flush().await.expect("Failed to flush connection");
 
+                async fn send_chunk(
+                    conn:        &mut (impl AsyncWriter + Unpin + 'static),
+                    mut message: Vec<u8>,
+                ) {
+                    /* an empty chunk means the end of the body */
+                    if message.is_empty() {return}
+
+                    let size_hex_bytes = ohkami_lib::num::hexized_bytes(message.len());
+
+                    let mut chunk = Vec::from(&size_hex_bytes[size_hex_bytes.iter().position(|b| *b!=b'0').unwrap()..]);
+                    chunk.extend_from_slice(b"\r\n");
+                    chunk.append(&mut message);
+                    chunk.extend_from_slice(b"\r\n");
+
+                    #[cfg(feature="DEBUG")]
+                    println!("\n[sending chunk]\n{}", chunk.escape_ascii());
+
+                    conn.write_all(&chunk).await.expect("Failed to send response");
+                    conn.flush().await.expect("Failed to flush connection");
+                }
+
                 while let Some(chunk) = stream.next().await {
                     match chunk {
                         Err(msg)  => {
@@ -237,21 +273,16 @@ impl Response {
                             }
                             message.push(b'\n');
 
-                            let size_hex_bytes = ohkami_lib::num::hexized_bytes(message.len());
-
-                            let mut chunk = Vec::from(&size_hex_bytes[size_hex_bytes.iter().position(|b| *b!=b'0').unwrap()..]);
-                            chunk.extend_from_slice(b"\r\n");
-                            chunk.append(&mut message);
-                            chunk.extend_from_slice(b"\r\n");
-
-                            #[cfg(feature="DEBUG")]
-                            println!("\n[sending chunk]\n{}", chunk.escape_ascii());
-
-                            conn.write_all(&chunk).await.expect("Failed to send response");
-                            conn.flush().await.expect("Failed to flush connection");
+                            if let Some(encoder) = &mut self.stream_encoder {
+                                message = encoder.encode(&message);
+                            }
+                            send_chunk(conn, message).await;
                         }
                     }
                 }
+                if let Some(encoder) = self.stream_encoder.take() {
+                    send_chunk(conn, encoder.finish()).await;
+                }
                 conn.write_all(b"0\r\n\r\n").await.expect("Failed to send response");
                 conn.flush().await.expect("Failed to flush connection");
 
@@ -283,6 +314,15 @@ impl Response {
         self
     }
 
+    /// Apply `Set-Cookie`s added to `jar`
+    #[cfg(feature="cookie")]
+    pub fn with_cookies(mut self, jar: crate::cookie::CookieJar<'_>) -> Self {
+        for setcookie in jar.setcookies {
+            self.headers.push_setcookie(setcookie.into());
+        }
+        self
+    }
+
     pub fn drop_content(&mut self) -> Content {
         let old_content = self.content.take();
         self.headers.set()
@@ -451,7 +491,9 @@ const _: () = {
                         crate::ws::Config::default(),
                         Box::new(|_| Box::pin(async {/* dummy handler */}))
                     )),
-                }
+                },
+                #[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+                stream_encoder: None,
             };
             this.complete();
 
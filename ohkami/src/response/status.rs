@@ -41,6 +41,8 @@ macro_rules! status {
                         status:  Status::$name,
                         headers: ResponseHeaders::new(),
                         content: Content::None,
+                        #[cfg(all(feature="sse", any(feature="rt_tokio",feature="rt_async-std")))]
+                        stream_encoder: None,
                     }
                 }
             )*
@@ -1,12 +1,11 @@
 #![cfg(any(feature="rt_tokio",feature="rt_async-std"))]
 
-use std::{any::Any, pin::Pin, sync::Arc, time::Duration};
-use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::{pin::Pin, time::Duration};
 use crate::__rt__::TcpStream;
 use crate::response::Upgrade;
 use crate::utils::timeout_in;
-use crate::ohkami::router::RadixRouter;
-use crate::{Request, Response};
+use crate::ohkami::RouterHandle;
+use crate::Request;
 
 
 mod env {
@@ -36,12 +35,12 @@ mod env {
 }
 
 pub(crate) struct Session {
-    router:     Arc<RadixRouter>,
+    router:     RouterHandle,
     connection: TcpStream,
 }
 impl Session {
     pub(crate) fn new(
-        router:      Arc<RadixRouter>,
+        router:      RouterHandle,
         connection:  TcpStream,
     ) -> Self {
         Self {
@@ -51,18 +50,6 @@ impl Session {
     }
 
     pub(crate) async fn manage(mut self) {
-        #[cold] #[inline(never)]
-        fn panicking(panic: Box<dyn Any + Send>) -> Response {
-            if let Some(msg) = panic.downcast_ref::<String>() {
-                crate::warning!("[Panicked]: {msg}");
-            } else if let Some(msg) = panic.downcast_ref::<&str>() {
-                crate::warning!("[Panicked]: {msg}");
-            } else {
-                crate::warning!("[Panicked]");
-            }
-            crate::Response::InternalServerError()
-        }
-
         match timeout_in(Duration::from_secs(env::OHKAMI_KEEPALIVE_TIMEOUT()), async {
             loop {
                 let mut req = Request::init();
@@ -71,10 +58,10 @@ impl Session {
                     Ok(Some(())) => {
                         let close = matches!(req.headers.Connection(), Some("close" | "Close"));
 
-                        let res = match catch_unwind(AssertUnwindSafe(|| self.router.handle(req.get_mut()))) {
-                            Ok(future) => future.await,
-                            Err(panic) => panicking(panic),
-                        };
+                        /* loaded for each request to follow `RouterHandle::replace`,
+                           and catches panics in handling by itself */
+                        let router = self.router.load();
+                        let res = router.handle(req.get_mut()).await;
                         let upgrade = res.send(&mut self.connection).await;
                         if !upgrade.is_none() {
                             break upgrade
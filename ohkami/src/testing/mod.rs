@@ -27,13 +27,11 @@
 //! }
 //! ```
 
-use crate::{Response, Request, Ohkami, Status, Method};
-use crate::ohkami::router::RadixRouter;
+use crate::{Response, Request, Ohkami, RouterHandle, Status, Method};
 use crate::response::ResponseHeader;
 
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::Arc;
 use std::{pin::Pin, future::Future, format as f};
 
 
@@ -41,18 +39,25 @@ pub trait Testing {
     fn test(self) -> TestingOhkami;
 }
 
-pub struct TestingOhkami(Arc<RadixRouter>);
+pub struct TestingOhkami(RouterHandle);
 
 impl Testing for Ohkami {
     fn test(self) -> TestingOhkami {
-        TestingOhkami(Arc::new(self.into_router().into_radix()))
+        TestingOhkami(RouterHandle::new(self))
+    }
+}
+
+/// Test the current routes, following `replace`s of the handle
+impl Testing for RouterHandle {
+    fn test(self) -> TestingOhkami {
+        TestingOhkami(self)
     }
 }
 
 impl TestingOhkami {
     #[must_use]
     pub fn oneshot(&self, req: TestRequest) -> Oneshot {
-        let router = self.0.clone();
+        let router = self.0.load();
         
         let res = async move {
             let mut request = Request::init();
@@ -105,7 +110,7 @@ pub struct TestRequest {
             });
 
         [
-            method.as_str().as_bytes(), b" ", path.as_bytes(), &queries, b" HTTP/1.1\r\n",
+            method.name().as_bytes(), b" ", path.as_bytes(), &queries, b" HTTP/1.1\r\n",
             &headers,
             b"\r\n",
             &content.unwrap_or(Cow::Borrowed(b""))
@@ -129,7 +134,20 @@ macro_rules! new_test_request {
         }
     )*};
 } new_test_request! {
-    GET PUT POST PATCH DELETE HEAD OPTIONS
+    GET PUT POST PATCH DELETE HEAD OPTIONS TRACE
+}
+
+impl TestRequest {
+    /// Request by method name, mainly for extension methods like `PROPFIND`
+    pub fn method(method: &str, path: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            method:  Method::from_name(method).unwrap_or_else(|| panic!("Invalid method: `{method}`")),
+            path:    path.into(),
+            queries: HashMap::new(),
+            headers: HashMap::new(),
+            content: None,
+        }
+    }
 }
 
 impl TestRequest {
@@ -196,6 +214,11 @@ impl TestResponse {
         self.0.headers.iter()
     }
 
+    /// Raw bytes of the body, like compressed ones
+    pub fn content(&self) -> Option<&[u8]> {
+        self.0.content.as_bytes()
+    }
+
     pub fn text(&self) -> Option<&str> {
         if self.0.headers.ContentType()?.starts_with("text/plain") {
             let body = self.0.content.as_bytes()?;
@@ -3,9 +3,11 @@ pub mod status;
 mod payload;
 pub use payload::{Payload, PayloadType};
 
+pub mod validate;
+
 #[cfg(feature="sse")]
 mod stream;
 #[cfg(feature="sse")]
 pub use stream::DataStream;
 
-pub use ohkami_macros::{Payload, Query};
+pub use ohkami_macros::{Payload, Query, Headers};
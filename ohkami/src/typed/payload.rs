@@ -1,5 +1,7 @@
-use crate::{FromRequest, IntoResponse, Request, Response};
+use crate::{FromRequest, IntoResponse, Request, Response, Rejection};
 use crate::response::Content;
+use crate::request::Tracked;
+use super::validate::ValidationErrors;
 use serde::{Serialize, Deserialize};
 
 
@@ -78,8 +80,8 @@ pub trait Payload: Sized {
         Some(if req.headers.ContentType().is_some_and(|ct|
             ct.starts_with(<Self::Type>::MIME_TYPE)
         ) {
-            match <Self::Type>::parse::<Self>(unsafe {bytes.as_bytes()}) {
-                Ok(this) => 'validation: {
+            match <Self::Type>::parse::<Tracked<Self>>(unsafe {bytes.as_bytes()}) {
+                Ok(Tracked(this)) => 'validation: {
                     if let Err(e) = this.validate() {
                         break 'validation Err((|| crate::serde::de::Error::custom(e.to_string()))())
                     }
@@ -105,6 +107,7 @@ pub trait Payload: Sized {
     fn inject(&self, res: &mut Response) -> Result<(), impl crate::serde::ser::Error>
     where Self: Serialize {
         self.validate().map_err(|e| crate::serde::ser::Error::custom(e.to_string()))?;
+        self.validate_fields().map_err(|e| crate::serde::ser::Error::custom(e.to_string()))?;
         match <Self::Type>::bytes(self) {
             Err(err)  => Err(err),
             Ok(bytes) => Ok({
@@ -120,6 +123,16 @@ pub trait Payload: Sized {
     fn validate(&self) -> Result<(), impl std::fmt::Display> {
         Result::<(), std::convert::Infallible>::Ok(())
     }
+
+    /// Validations of the fields, generated by `#[Payload]` from
+    /// `#[validate(...)]` attributes. See `ohkami::typed::validate`.
+    /// 
+    /// Failing in a request, this is responded as `422 Unprocessable Entity`
+    /// with the list of the errors.
+    #[inline(always)]
+    fn validate_fields(&self) -> Result<(), ValidationErrors> {
+        Ok(())
+    }
 }
 
 pub trait PayloadType {
@@ -169,13 +182,15 @@ const _: () = {
     where
         P: Payload + Deserialize<'req> + 'req
     {
-        type Error = Response;
+        type Error = Rejection;
 
         #[inline(always)]
         fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
-            Self::extract(req).map(|result| result.map_err(|e| {
-                Response::BadRequest().with_text(e.to_string())
-            }))
+            let this = match Self::extract(req)? {
+                Ok(this) => this,
+                Err(e)   => return Some(Err(Rejection::payload(e))),
+            };
+            Some(this.validate_fields().map(|()| this).map_err(Rejection::validation))
         }
     }
 
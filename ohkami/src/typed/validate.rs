@@ -0,0 +1,252 @@
+//! Field validations by `#[validate(...)]` attributes of `#[Payload]` and `#[Query]` types.
+//!
+//! ```
+//! use ohkami::prelude::*;
+//! use ohkami::typed::{Payload, Query};
+//! use ohkami::builtin::payload::JSON;
+//!
+//! fn not_reserved(name: &String) -> Result<(), &'static str> {
+//!     (name != "admin").then_some(()).ok_or("is reserved")
+//! }
+//!
+//! #[Payload(JSON/D)]
+//! struct CreateUser {
+//!     #[validate(length(min = 1, max = 64), custom = not_reserved)]
+//!     name:  String,
+//!     #[validate(email)]
+//!     email: String,
+//!     #[validate(range(0..=150))]
+//!     age:   Option<u8>,
+//!     #[validate(regex = "[a-z]{2}(-[A-Z]{2})?")]
+//!     lang:  Option<String>,
+//! }
+//!
+//! #[Query]
+//! struct Pagination {
+//!     #[validate(range(min = 1, max = 100))]
+//!     per_page: usize,
+//! }
+//!
+//! async fn create_user(_: CreateUser) -> Status {
+//!     Status::Created
+//! }
+//! async fn list_users(_: Pagination) -> Status {
+//!     Status::OK
+//! }
+//!
+//! # #[tokio::main] async fn main() {
+//! # use ohkami::testing::*;
+//! # use ohkami::serde::json::{json, Value};
+//! let t = Ohkami::new((
+//!     "/users"
+//!         .GET(list_users)
+//!         .POST(create_user),
+//! )).test();
+//!
+//! let req = TestRequest::POST("/users")
+//!     .json_lit(r#"{"name":"admin","email":"admin@","age":200,"lang":"ja-JP"}"#);
+//! let res = t.oneshot(req).await;
+//! assert_eq!(res.status(), Status::UnprocessableEntity);
+//! assert_eq!(res.json::<Value>().unwrap().unwrap(), json!({"errors": [
+//!     {"field": "name",  "code": "custom", "message": "is reserved"},
+//!     {"field": "email", "code": "email",  "message": "must be an email address"},
+//!     {"field": "age",   "code": "range",  "message": "must be 0 to 150"},
+//! ]}));
+//!
+//! let req = TestRequest::GET("/users?per_page=1000");
+//! let res = t.oneshot(req).await;
+//! assert_eq!(res.status(), Status::UnprocessableEntity);
+//! assert_eq!(res.json::<Value>().unwrap().unwrap(), json!({"errors": [
+//!     {"field": "per_page", "code": "range", "message": "must be 1 to 100"},
+//! ]}));
+//!
+//! let req = TestRequest::GET("/users?per_page=20");
+//! let res = t.oneshot(req).await;
+//! assert_eq!(res.status(), Status::OK);
+//! # }
+//! ```
+//!
+//! - `length(min = _, max = _)` : number of characters of a string, or elements of a collection
+//! - `email` : a string like an email address
+//! - `range({range})` : a value in the range, like `0..=150`, `1..` or `..1.0`
+//! - `regex = "{pattern}"` : a string matching the **whole** pattern (see `ohkami_lib::regex` for the syntax),
+//!   in linear time of the string. An invalid pattern is a compile error :
+//!   ```compile_fail
+//!   # use ohkami::typed::Payload;
+//!   # use ohkami::builtin::payload::JSON;
+//!   #[Payload(JSON/D)]
+//!   struct Invalid {
+//!       #[validate(regex = "[a-z")]
+//!       name: String,
+//!   }
+//!   ```
+//! - `custom = {function}` : `fn(&{field type}) -> Result<(), impl Display>`
+//!
+//! Rules for an `Option` field are applied only when it's `Some`.
+//!
+//! Every failed rule of every field is collected into `ValidationErrors`,
+//! responded as `422 Unprocessable Entity` with JSON like :
+//!
+//! ```json
+//! {"errors":[{"field":"name","code":"length","message":"must be 1 to 64 in length"}]}
+//! ```
+
+use crate::{IntoResponse, Response};
+use std::ops::{Bound, RangeBounds};
+
+#[doc(hidden)]
+pub use ohkami_lib::regex::Regex;
+
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ValidationError {
+    pub field:   &'static str,
+    /// `length`, `email`, `range`, `regex` or `custom`
+    pub code:    &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<ValidationError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, field: &'static str, code: &'static str, message: impl std::fmt::Display) {
+        self.0.push(ValidationError { field, code, message: message.to_string() })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ValidationError> {
+        self.0.iter()
+    }
+
+    /// `Ok(())` if no error
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {Ok(())} else {Err(self)}
+    }
+}
+
+const _: () = {
+    impl std::fmt::Display for ValidationErrors {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for (i, ValidationError { field, message, .. }) in self.0.iter().enumerate() {
+                if i > 0 {f.write_str(", ")?}
+                write!(f, "`{field}` {message}")?
+            }
+            Ok(())
+        }
+    }
+    impl std::error::Error for ValidationErrors {}
+
+    impl IntoResponse for ValidationErrors {
+        fn into_response(self) -> Response {
+            Response::UnprocessableEntity().with_json(::serde_json::json!({
+                "errors": self.0
+            }))
+        }
+    }
+};
+
+
+/// Something having a length, checked by `length(...)`
+pub trait Length {
+    fn length(&self) -> usize;
+}
+const _: () = {
+    impl Length for str {
+        fn length(&self) -> usize {self.chars().count()}
+    }
+    impl Length for String {
+        fn length(&self) -> usize {self.as_str().length()}
+    }
+    impl Length for std::borrow::Cow<'_, str> {
+        fn length(&self) -> usize {(**self).length()}
+    }
+    impl<T> Length for [T] {
+        fn length(&self) -> usize {self.len()}
+    }
+    impl<T> Length for Vec<T> {
+        fn length(&self) -> usize {self.len()}
+    }
+    impl<K, V, S> Length for std::collections::HashMap<K, V, S> {
+        fn length(&self) -> usize {self.len()}
+    }
+    impl<T: Length + ?Sized> Length for &T {
+        fn length(&self) -> usize {(**self).length()}
+    }
+};
+
+pub fn length(value: &(impl Length + ?Sized), min: Option<usize>, max: Option<usize>) -> Result<(), String> {
+    let len = value.length();
+    match (min, max) {
+        (Some(min), Some(max)) if !(min..=max).contains(&len) => Err(format!("must be {min} to {max} in length")),
+        (Some(min), None) if len < min => Err(format!("must be at least {min} in length")),
+        (None, Some(max)) if len > max => Err(format!("must be at most {max} in length")),
+        _ => Ok(())
+    }
+}
+
+pub fn email(value: &impl AsRef<str>) -> Result<(), String> {
+    fn is_email(s: &str) -> bool {
+        let Some((local, domain)) = s.split_once('@') else {return false};
+        !local.is_empty()
+        && !domain.contains('@')
+        && domain.split('.').count() >= 2
+        && domain.split('.').all(|label| !label.is_empty() && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'))
+        && local.bytes().all(|b| b.is_ascii_graphic() && !b"()<>[]:;@\\,\"".contains(&b))
+    }
+    is_email(value.as_ref()).then_some(()).ok_or_else(|| String::from("must be an email address"))
+}
+
+pub fn range<T: PartialOrd + std::fmt::Display>(value: &T, range: impl RangeBounds<T>) -> Result<(), String> {
+    if range.contains(value) {
+        return Ok(())
+    }
+    Err(match (range.start_bound(), range.end_bound()) {
+        (Bound::Included(s), Bound::Included(e)) => format!("must be {s} to {e}"),
+        (Bound::Included(s), Bound::Excluded(e)) => format!("must be {s} or more and less than {e}"),
+        (Bound::Included(s), Bound::Unbounded)   => format!("must be {s} or more"),
+        (_, Bound::Included(e))                  => format!("must be {e} or less"),
+        (_, Bound::Excluded(e))                  => format!("must be less than {e}"),
+        _                                        => String::from("must be in the range"),
+    })
+}
+
+pub fn regex(value: &impl AsRef<str>, regex: &Regex) -> Result<(), String> {
+    regex.is_match(value.as_ref().as_bytes()).then_some(())
+        .ok_or_else(|| format!("must match `{}`", regex.as_str()))
+}
+
+
+#[cfg(test)]
+#[test] fn test_rules() {
+    assert!(length("おおかみ", Some(1), Some(4)).is_ok());
+    assert!(length("", Some(1), None).is_err());
+    assert!(length(&vec![1, 2, 3], None, Some(2)).is_err());
+
+    for valid in ["a@example.com", "first.last+tag@sub.example.co.jp"] {
+        assert!(email(&valid).is_ok(), "{valid}")
+    }
+    for invalid in ["", "a", "@example.com", "a@", "a@example", "a@@example.com", "a b@example.com", "a@exa mple.com", "a@.com"] {
+        assert!(email(&invalid).is_err(), "{invalid}")
+    }
+
+    assert!(range(&150_u8, 0..=150).is_ok());
+    assert_eq!(range(&151_u8, 0..=150), Err(String::from("must be 0 to 150")));
+    assert_eq!(range(&0.5_f64, 1.0..), Err(String::from("must be 1 or more")));
+
+    let lang = Regex::new("[a-z]{2}(-[A-Z]{2})?").unwrap();
+    assert!(regex(&"ja-JP", &lang).is_ok());
+    assert!(regex(&"japanese", &lang).is_err());
+
+    /* a long payload string doesn't blow up matching */
+    let word = Regex::new("[a-z]+").unwrap();
+    assert!(regex(&"a".repeat(1 << 20), &word).is_ok());
+    assert!(regex(&format!("{}!", "a".repeat(1 << 20)), &word).is_err());
+}
@@ -4,6 +4,8 @@ pub mod mime;
 
 pub mod num;
 
+pub mod regex;
+
 pub mod time;
 pub use time::imf_fixdate;
 
@@ -0,0 +1,466 @@
+//! Minimal regular expression, used for path param constraints.
+//!
+//! A pattern is compiled into a program of a Thompson NFA and matched by
+//! simulating it (so-called Pike VM), in `O(input length × pattern size)` time
+//! without backtracking nor recursion over the input.
+//!
+//! Supported syntax (byte-oriented) :
+//!
+//! - literals and `.` (any byte)
+//! - escapes `\d`, `\w`, `\s`, `\D`, `\W`, `\S` and `\` + any punctuation
+//! - classes `[abc]`, `[a-z0-9_]`, `[^...]`
+//! - groups `(...)`, `(?:...)` and alternation `|`
+//! - quantifiers `*`, `+`, `?`, `{n}`, `{n,}`, `{n,m}` (optionally followed by `?`)
+//! - anchors `^` at the start and `$` at the end (patterns are always matched against the whole input)
+
+#[derive(Clone)]
+pub struct Regex {
+    source:  String,
+    program: Box<[Inst]>,
+}
+
+/// At most `MAX_PROGRAM` instructions, bounding the cost of matching
+const MAX_PROGRAM: usize = 10000;
+/// At most `MAX_NEST` nested groups
+const MAX_NEST: usize = 64;
+
+#[derive(Clone, Debug)]
+enum Node {
+    Byte(u8),
+    Any,
+    Class { ranges: Vec<(u8, u8)>, negated: bool },
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat { node: Box<Node>, min: usize, max: Option<usize> },
+}
+
+#[derive(Clone, Debug)]
+enum Inst {
+    Byte(u8),
+    Any,
+    Class { ranges: Box<[(u8, u8)]>, negated: bool },
+    /// Continue from both
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let mut p = Parser { src: pattern.as_bytes(), pos: 0, depth: 0 };
+
+        if p.src.first() == Some(&b'^') {p.pos += 1}
+        let node = p.alternate()?;
+        if p.src.get(p.pos) == Some(&b'$') && p.pos + 1 == p.src.len() {p.pos += 1}
+
+        if p.pos != p.src.len() {
+            return Err(format!("Unexpected `{}` at {} in regex `{pattern}`", p.src[p.pos] as char, p.pos))
+        }
+
+        let mut program = Vec::new();
+        compile(&node, &mut program).and_then(|_| push(&mut program, Inst::Match))
+            .map_err(|_| format!("Too large regex `{pattern}`: more than {MAX_PROGRAM} instructions"))?;
+
+        Ok(Self { source: pattern.into(), program: program.into() })
+    }
+
+    /// Whether the **whole** `input` matches this pattern
+    pub fn is_match(&self, input: &[u8]) -> bool {
+        thread_local! {
+            /* reused not to allocate for each match */
+            static SCRATCH: std::cell::RefCell<Scratch> = const {std::cell::RefCell::new(Scratch::new())};
+        }
+        SCRATCH.with_borrow_mut(|scratch| self.run(input, scratch))
+    }
+
+    fn run(&self, input: &[u8], Scratch { current, next, stack }: &mut Scratch) -> bool {
+        current.prepare(self.program.len());
+        next.prepare(self.program.len());
+
+        current.add(&self.program, 0, stack);
+        for b in input {
+            if current.list.is_empty() {
+                break
+            }
+            next.clear();
+            for &pc in &current.list {
+                let step = match &self.program[pc] {
+                    Inst::Byte(expected) => b == expected,
+                    Inst::Any            => true,
+                    Inst::Class { ranges, negated } => ranges.iter().any(|(start, end)| (start..=end).contains(&b)) != *negated,
+                    Inst::Match | Inst::Split(..) | Inst::Jump(_) => false,
+                };
+                if step {
+                    next.add(&self.program, pc + 1, stack);
+                }
+            }
+            std::mem::swap(current, next);
+        }
+        let matched = current.list.iter().any(|pc| matches!(self.program[*pc], Inst::Match));
+
+        /* leave `added` all `false` for the next use */
+        current.clear();
+        next.clear();
+
+        matched
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+const _: () = {
+    impl std::fmt::Debug for Regex {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.source)
+        }
+    }
+
+    impl PartialEq for Regex {
+        fn eq(&self, other: &Self) -> bool {
+            self.source == other.source
+        }
+    }
+};
+
+
+struct Parser<'p> {
+    src:   &'p [u8],
+    pos:   usize,
+    /// nesting depth of groups
+    depth: usize,
+}
+impl<'p> Parser<'p> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Some(b)
+    }
+    fn error(&self, message: &str) -> String {
+        format!("{message} at {} in regex `{}`", self.pos, String::from_utf8_lossy(self.src))
+    }
+
+    fn alternate(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.peek() == Some(b'|') {
+            self.pos += 1;
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 {branches.pop().unwrap()} else {Node::Alternate(branches)})
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while let Some(b) = self.peek() {
+            match b {
+                b'|' | b')' => break,
+                b'$' if self.pos + 1 == self.src.len() => break,
+                _ => {
+                    let atom = self.atom()?;
+                    nodes.push(self.quantified(atom)?);
+                }
+            }
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some(b'*') => {self.pos += 1; (0, None)}
+            Some(b'+') => {self.pos += 1; (1, None)}
+            Some(b'?') => {self.pos += 1; (0, Some(1))}
+            Some(b'{') => {
+                self.pos += 1;
+                let min = self.number().ok_or_else(|| self.error("Expected a number"))?;
+                let max = match self.next() {
+                    Some(b'}') => Some(min),
+                    Some(b',') => match self.peek() {
+                        Some(b'}') => {self.pos += 1; None}
+                        _ => {
+                            let max = self.number().ok_or_else(|| self.error("Expected a number"))?;
+                            (self.next() == Some(b'}')).then_some(Some(max)).ok_or_else(|| self.error("Expected `}`"))?
+                        }
+                    }
+                    _ => return Err(self.error("Expected `}` or `,`"))
+                };
+                if max.is_some_and(|max| max < min) {
+                    return Err(self.error("Invalid repetition range"))
+                }
+                (min, max)
+            }
+            _ => return Ok(atom)
+        };
+
+        /* lazy quantifiers accept the same inputs in whole matching */
+        if self.peek() == Some(b'?') {self.pos += 1}
+
+        Ok(Node::Repeat { node: Box::new(atom), min, max })
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {self.pos += 1}
+        std::str::from_utf8(&self.src[start..self.pos]).ok()?.parse().ok()
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.next().unwrap(/* called after `peek` */) {
+            b'.' => Ok(Node::Any),
+            b'(' => {
+                if self.depth == MAX_NEST {
+                    return Err(self.error(&format!("More than {MAX_NEST} nested groups")))
+                }
+                if self.src[self.pos..].starts_with(b"?:") {self.pos += 2}
+                self.depth += 1;
+                let node = self.alternate()?;
+                self.depth -= 1;
+                (self.next() == Some(b')')).then_some(node).ok_or_else(|| self.error("Unclosed group"))
+            }
+            b'[' => self.class(),
+            b'\\' => self.escape().map(|(ranges, negated)| Node::Class { ranges, negated }),
+            b @ (b'*' | b'+' | b'?' | b'{' | b')' | b']' | b'}' | b'^' | b'$') => {
+                self.pos -= 1;
+                Err(self.error(&format!("Unexpected `{}`", b as char)))
+            }
+            b => Ok(Node::Byte(b)),
+        }
+    }
+
+    fn escape(&mut self) -> Result<(Vec<(u8, u8)>, bool), String> {
+        const DIGIT: &[(u8, u8)] = &[(b'0', b'9')];
+        const WORD:  &[(u8, u8)] = &[(b'0', b'9'), (b'A', b'Z'), (b'_', b'_'), (b'a', b'z')];
+        const SPACE: &[(u8, u8)] = &[(b'\t', b'\r'), (b' ', b' ')];
+
+        match self.next() {
+            Some(b'd') => Ok((DIGIT.into(), false)),
+            Some(b'w') => Ok((WORD.into(),  false)),
+            Some(b's') => Ok((SPACE.into(), false)),
+            Some(b'D') => Ok((DIGIT.into(), true)),
+            Some(b'W') => Ok((WORD.into(),  true)),
+            Some(b'S') => Ok((SPACE.into(), true)),
+            Some(b) if b.is_ascii_punctuation() => Ok((vec![(b, b)], false)),
+            _ => Err(self.error("Invalid escape")),
+        }
+    }
+
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.peek() == Some(b'^');
+        if negated {self.pos += 1}
+
+        let mut ranges = Vec::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("Unclosed class")),
+                Some(b']') if !ranges.is_empty() => break,
+                Some(b'\\') => {
+                    let (escaped, escaped_negated) = self.escape()?;
+                    if escaped_negated {
+                        return Err(self.error("Negated escape in class is not supported"))
+                    }
+                    ranges.extend(escaped)
+                }
+                Some(start) => {
+                    if self.peek() == Some(b'-') && self.src.get(self.pos + 1).is_some_and(|b| *b != b']') {
+                        self.pos += 1;
+                        let end = self.next().unwrap();
+                        if end < start {
+                            return Err(self.error("Invalid class range"))
+                        }
+                        ranges.push((start, end))
+                    } else {
+                        ranges.push((start, start))
+                    }
+                }
+            }
+        }
+
+        Ok(Node::Class { ranges, negated })
+    }
+}
+
+
+fn push(program: &mut Vec<Inst>, inst: Inst) -> Result<usize, ()> {
+    if program.len() == MAX_PROGRAM {
+        return Err(())
+    }
+    program.push(inst);
+    Ok(program.len() - 1)
+}
+
+/// Append the instructions matching `node`, continuing to the next one
+fn compile(node: &Node, program: &mut Vec<Inst>) -> Result<(), ()> {
+    match node {
+        Node::Byte(b) => {push(program, Inst::Byte(*b))?;}
+        Node::Any     => {push(program, Inst::Any)?;}
+        Node::Class { ranges, negated } => {
+            push(program, Inst::Class { ranges: ranges.as_slice().into(), negated: *negated })?;
+        }
+        Node::Concat(nodes) => for node in nodes {
+            compile(node, program)?
+        }
+        Node::Alternate(branches) => {
+            /* split to each branch, each jumping to the end */
+            let mut jumps = Vec::with_capacity(branches.len());
+            for (i, branch) in branches.iter().enumerate() {
+                if i + 1 == branches.len() {
+                    compile(branch, program)?;
+                } else {
+                    let split = push(program, Inst::Split(0, 0))?;
+                    compile(branch, program)?;
+                    jumps.push(push(program, Inst::Jump(0))?);
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+            }
+            for jump in jumps {
+                program[jump] = Inst::Jump(program.len());
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            /* not to loop so many times for a node taking no instruction like `(){4000000000}` */
+            if *min > MAX_PROGRAM {
+                return Err(())
+            }
+            for _ in 0..*min {
+                compile(node, program)?
+            }
+            match max {
+                None => {
+                    let split = push(program, Inst::Split(0, 0))?;
+                    compile(node, program)?;
+                    push(program, Inst::Jump(split))?;
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    /* each repetition takes at least one instruction */
+                    if max - min > MAX_PROGRAM {
+                        return Err(())
+                    }
+                    let mut splits = Vec::with_capacity(max - min);
+                    for _ in *min..*max {
+                        splits.push(push(program, Inst::Split(0, 0))?);
+                        compile(node, program)?;
+                    }
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, program.len());
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Buffers for `Regex::is_match`
+struct Scratch {
+    current: Threads,
+    next:    Threads,
+    stack:   Vec<usize>,
+}
+impl Scratch {
+    const fn new() -> Self {
+        Self { current: Threads::new(), next: Threads::new(), stack: Vec::new() }
+    }
+}
+
+/// Set of instructions where the threads of NFA are, in the order added
+struct Threads {
+    list:  Vec<usize>,
+    added: Vec<bool>,
+}
+impl Threads {
+    const fn new() -> Self {
+        Self { list: Vec::new(), added: Vec::new() }
+    }
+
+    /// Make room for a program of `n` instructions, expecting `list` is empty
+    fn prepare(&mut self, n: usize) {
+        if self.added.len() < n {
+            self.added.resize(n, false);
+        }
+    }
+
+    fn clear(&mut self) {
+        for pc in self.list.drain(..) {
+            self.added[pc] = false;
+        }
+    }
+
+    /// Add `pc` and ones reachable from it without consuming input
+    fn add(&mut self, program: &[Inst], pc: usize, stack: &mut Vec<usize>) {
+        stack.push(pc);
+        while let Some(pc) = stack.pop() {
+            if std::mem::replace(&mut self.added[pc], true) {
+                continue
+            }
+            /* also `Jump`s and `Split`s, to be cleared later */
+            self.list.push(pc);
+            match program[pc] {
+                Inst::Jump(to)    => stack.push(to),
+                Inst::Split(a, b) => {stack.push(b); stack.push(a)}
+                _ => ()
+            }
+        }
+    }
+}
+
+
+#[cfg(test)] mod test {
+    use super::Regex;
+
+    #[test] fn test_regex() {
+        for (pattern, matching, not_matching) in [
+            ("abc",           &["abc"][..],                          &["ab", "abcd", ""][..]),
+            ("[a-z]+\\.png",  &["a.png", "image.png"],               &[".png", "A.png", "a.jpg", "a-png"]),
+            ("\\d{4}-\\d{2}", &["2024-01"],                          &["24-01", "2024-1", "2024-012"]),
+            ("(foo|bar)s?",   &["foo", "bars"],                      &["baz", "foos2"]),
+            ("^v[0-9]+$",     &["v1", "v20"],                        &["v", "1", "v1a"]),
+            ("[^/]*",         &["", "anything.txt"],                 &["a/b"]),
+            ("a{2,}b?",       &["aa", "aaab"],                       &["a", "ab"]),
+            ("(?:ab)*c",      &["c", "ababc"],                       &["abc2", "aabc"]),
+            ("[\\w-]{1,8}",   &["user_01", "a-b"],                   &["", "too_long_name"]),
+            (".*\\.(jpe?g)",  &["x.jpg", "a.b.jpeg"],                &["x.png", "jpg"]),
+        ] {
+            let regex = Regex::new(pattern).unwrap();
+            for m in matching {
+                assert!(regex.is_match(m.as_bytes()), "`{pattern}` should match `{m}`")
+            }
+            for n in not_matching {
+                assert!(!regex.is_match(n.as_bytes()), "`{pattern}` shouldn't match `{n}`")
+            }
+        }
+    }
+
+    #[test] fn test_invalid_regex() {
+        for pattern in ["(ab", "[a-", "a{3,1}", "*a", "a)", "\\q", "[z-a]"] {
+            assert!(Regex::new(pattern).is_err(), "`{pattern}` should be invalid")
+        }
+
+        /* too large or too deeply nested */
+        assert!(Regex::new("a{1000}{1000}").is_err());
+        assert!(Regex::new("a{0,4000000000}").is_err());
+        assert!(Regex::new("(?:){4000000000}").is_err());
+        assert!(Regex::new(&format!("{}a{}", "(".repeat(100), ")".repeat(100))).is_err());
+    }
+
+    #[test] fn test_adversarial_input() {
+        /* exponential for backtracking */
+        let regex = Regex::new("(a+)+b").unwrap();
+        assert!(!regex.is_match("a".repeat(10000).as_bytes()));
+        let regex = Regex::new("(a|aa)*c").unwrap();
+        assert!(!regex.is_match("a".repeat(10000).as_bytes()));
+        let regex = Regex::new("(a*)*").unwrap();
+        assert!(regex.is_match("a".repeat(10000).as_bytes()));
+
+        /* deep recursion for recursive matchers */
+        let long = "a".repeat(1 << 20);
+        let regex = Regex::new("[a-z]+\\.png").unwrap();
+        assert!(regex.is_match(format!("{long}.png").as_bytes()));
+        assert!(!regex.is_match(long.as_bytes()));
+        let regex = Regex::new(".*").unwrap();
+        assert!(regex.is_match(long.as_bytes()));
+    }
+}
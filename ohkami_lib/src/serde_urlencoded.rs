@@ -12,15 +12,16 @@ pub fn to_string(value: &impl serde::Serialize) -> Result<String, Error> {
     Ok(s.output())
 }
 
+/// Deserialize `key=value&...`, also accepting
+/// 
+/// - repeated keys ( `ids=1&ids=2` ) or `ids[]=1&ids[]=2`, `ids[0]=1&ids[1]=2` as a sequence
+/// - `user[name]=ohkami&user[age]=4` as a nested struct or map
+/// - key without `=` ( `?verbose` ) as a flag, `true` for `bool`
+/// 
+/// in addition to comma-separated sequence like `ids=1,17,42`.
 #[inline(always)]
 pub fn from_bytes<'de, D: serde::Deserialize<'de>>(input: &'de [u8]) -> Result<D, Error> {
-    let mut d = de::URLEncodedDeserializer::new(input);
-    let t = D::deserialize(&mut d)?;
-    if d.remaining().is_empty() {
-        Ok(t)
-    } else {
-        Err((||serde::de::Error::custom(format!("Unexpected trailing charactors: {}", d.remaining().escape_ascii())))())
-    }
+    D::deserialize(de::Node::parse(input)?)
 }
 
 
@@ -52,12 +53,12 @@ const _: () = {
         type Ok    = ();
         type Error = Error;
 
-        fn serialize_field<T: ?Sized>(
+        fn serialize_field<T>(
             &mut self,
             _key: &'static str,
             _value: &T,
         ) -> Result<(), Self::Error>
-        where T: serde::Serialize {
+        where T: ?Sized + serde::Serialize {
             match *self {}
         }
 
@@ -70,8 +71,8 @@ const _: () = {
         type Ok    = ();
         type Error = Error;
 
-        fn serialize_field<T: ?Sized>(&mut self, _: &T) -> Result<(), Self::Error>
-        where T: serde::Serialize {
+        fn serialize_field<T>(&mut self, _: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {
             match *self {}
         }
 
@@ -79,4 +80,57 @@ const _: () = {
             match self {}
         }
     }
+
+    impl serde::ser::SerializeMap for Infallible {
+        type Ok    = ();
+        type Error = Error;
+
+        fn serialize_key<T>(&mut self, _: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {match *self {}}
+
+        fn serialize_value<T>(&mut self, _: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {match *self {}}
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {match self {}}
+    }
+
+    impl serde::ser::SerializeStruct for Infallible {
+        type Ok    = ();
+        type Error = Error;
+
+        fn serialize_field<T>(&mut self, _: &'static str, _: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {match *self {}}
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {match self {}}
+    }
+
+    impl serde::ser::SerializeSeq for Infallible {
+        type Ok    = ();
+        type Error = Error;
+
+        fn serialize_element<T>(&mut self, _: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {match *self {}}
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {match self {}}
+    }
+
+    impl serde::ser::SerializeTuple for Infallible {
+        type Ok    = ();
+        type Error = Error;
+
+        fn serialize_element<T>(&mut self, _: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {match *self {}}
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {match self {}}
+    }
+
+    impl serde::ser::SerializeTupleStruct for Infallible {
+        type Ok    = ();
+        type Error = Error;
+
+        fn serialize_field<T>(&mut self, _: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {match *self {}}
+
+        fn end(self) -> Result<Self::Ok, Self::Error> {match self {}}
+    }
 };
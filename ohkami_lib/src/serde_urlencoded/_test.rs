@@ -175,3 +175,107 @@ struct URLRequest<'req> {
         ).unwrap()
     );
 }
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Filter {
+    tags:    Vec<String>,
+    ids:     Vec<usize>,
+    owner:   Owner,
+    archived: bool,
+}
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Owner {
+    name: String,
+    age:  Option<u8>,
+}
+
+#[test] fn deserialize_repeated_and_bracketed() {
+    let expected = Filter {
+        tags:     vec![String::from("rust"), String::from("web framework")],
+        ids:      vec![1, 2, 3],
+        owner:    Owner { name: String::from("ohkami"), age: Some(2) },
+        archived: true,
+    };
+
+    assert_eq!(expected, serde_urlencoded::from_bytes(
+        b"tags=rust&tags=web%20framework&ids=1&ids=2&ids=3&owner[name]=ohkami&owner[age]=2&archived"
+    ).unwrap());
+    assert_eq!(expected, serde_urlencoded::from_bytes(
+        b"tags[]=rust&tags[]=web%20framework&ids[]=1&ids[]=2&ids[]=3&owner%5Bname%5D=ohkami&owner%5Bage%5D=2&archived=true"
+    ).unwrap());
+    assert_eq!(expected, serde_urlencoded::from_bytes(
+        b"tags[1]=web%20framework&tags[0]=rust&ids=1,2,3&archived&&owner[age]=2&owner[name]=ohkami"
+    ).unwrap());
+
+    assert_eq!(
+        Filter {
+            tags:     vec![String::from("rust")],
+            ids:      vec![],
+            owner:    Owner { name: String::new(), age: None },
+            archived: false,
+        },
+        serde_urlencoded::from_bytes(
+            b"tags=rust&ids=&owner[name]=&owner[age]=&archived=false"
+        ).unwrap()
+    );
+
+    assert!(serde_urlencoded::from_bytes::<Filter>(
+        b"tags=rust&ids=1&owner=ohkami&owner[name]=ohkami&archived"
+    ).is_err());
+    assert!(serde_urlencoded::from_bytes::<Filter>(
+        b"tags=rust&ids=one&owner[name]=ohkami&archived"
+    ).is_err());
+}
+
+#[test] fn serialize_repeated_and_bracketed() {
+    let filter = Filter {
+        tags:     vec![String::from("rust"), String::from("web framework")],
+        ids:      vec![1, 2],
+        owner:    Owner { name: String::from("ohkami"), age: None },
+        archived: false,
+    };
+    let encoded = serde_urlencoded::to_string(&filter).unwrap();
+    assert_eq!(encoded,
+        "tags=rust&tags=web%20framework&ids=1&ids=2&owner[name]=ohkami&owner[age]=&archived=false"
+    );
+    assert_eq!(filter, serde_urlencoded::from_bytes(encoded.as_bytes()).unwrap());
+
+    #[derive(Serialize)]
+    struct Nested {
+        owners: Vec<Owner>,
+    }
+    assert_eq!(
+        serde_urlencoded::to_string(&Nested { owners: vec![
+            Owner { name: String::from("a"), age: Some(1) },
+            Owner { name: String::from("b"), age: Some(2) },
+        ] }).unwrap(),
+        "owners[0][name]=a&owners[0][age]=1&owners[1][name]=b&owners[1][age]=2"
+    );
+}
+
+#[test] fn deserialize_limits() {
+    use std::collections::HashMap;
+
+    /* nesting depth */
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(untagged)]
+    enum Tree {
+        Leaf(String),
+        Node(HashMap<String, Tree>),
+    }
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Deep {
+        #[allow(unused)]
+        a: Tree,
+    }
+
+    let key = |depth: usize| format!("a{}", "[x]".repeat(depth));
+    assert!(serde_urlencoded::from_bytes::<Deep>(format!("{}=1", key(32)).as_bytes()).is_ok());
+    assert!(serde_urlencoded::from_bytes::<Deep>(format!("{}=1", key(33)).as_bytes()).is_err());
+    assert!(serde_urlencoded::from_bytes::<Deep>(format!("{}=1", key(20000)).as_bytes()).is_err());
+
+    /* number of pairs */
+    let pairs = |n: usize| (0..n).map(|i| format!("k{i}=v")).collect::<Vec<_>>().join("&");
+    assert_eq!(serde_urlencoded::from_bytes::<HashMap<String, String>>(pairs(10000).as_bytes()).unwrap().len(), 10000);
+    assert!(serde_urlencoded::from_bytes::<HashMap<String, String>>(pairs(10001).as_bytes()).is_err());
+}
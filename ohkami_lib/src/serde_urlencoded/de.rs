@@ -1,195 +1,266 @@
-use serde::de::IntoDeserializer as _;
+use serde::de::{Error as _, IntoDeserializer, Visitor};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
 
 use crate::{percent_decode_utf8, percent_decode};
-use std::borrow::Cow;
-
-
-pub(crate) struct URLEncodedDeserializer<'de> {
-    input: &'de [u8],
-    side:  ParsingSide,
-}
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum ParsingSide {
-    Key,
-    Value,
+use std::{borrow::Cow, collections::HashMap};
+use super::Error;
+
+
+/// At most `MAX_PAIRS` `key=value` pairs in an input
+const MAX_PAIRS: usize = 10000;
+/// At most `MAX_DEPTH` bracketed segments in a key like `a[b][c]`
+const MAX_DEPTH: usize = 32;
+
+
+/// `key=value` pairs parsed into a tree :
+///
+/// - repeated keys ( `ids=1&ids=2` ) and `ids[]=1&ids[]=2` into a sequence
+/// - `user[name]=ohkami&user[age]=4` into a nested map
+/// - `ids[0]=1&ids[1]=2` into a map of indexes, which is also deserialized as a sequence
+/// - key without `=` ( `?verbose` ) into a flag
+pub(crate) enum Node<'de> {
+    Value(Scalar<'de>),
+    Flag,
+    Seq(Vec<Node<'de>>),
+    Map(Entries<'de>),
 }
 
-impl<'de> URLEncodedDeserializer<'de> {
-    pub(crate) fn new(input: &'de [u8]) -> Self {
-        Self { input, side: ParsingSide::Key }
+/// Keys in their input order, with an index for lookup
+pub(crate) struct Entries<'de> {
+    list:  Vec<(Cow<'de, str>, Node<'de>)>,
+    index: HashMap<Cow<'de, str>, usize>,
+}
+impl<'de> Entries<'de> {
+    fn new() -> Self {
+        Self { list: Vec::new(), index: HashMap::new() }
     }
-    pub(crate) fn remaining(&self) -> &'de [u8] {
-        &self.input
+    fn get_mut(&mut self, key: &str) -> Option<&mut Node<'de>> {
+        self.index.get(key).map(|i| &mut self.list[*i].1)
     }
-}
-
-impl<'de> URLEncodedDeserializer<'de> {
-    #[inline(always)]
-    fn peek(&self) -> Result<&u8, super::Error> {
-        self.input.first().ok_or_else(|| serde::de::Error::custom("can't peek: unexpected end of input"))
+    fn push(&mut self, key: Cow<'de, str>, node: Node<'de>) {
+        self.index.insert(key.clone(), self.list.len());
+        self.list.push((key, node));
     }
-    #[inline(always)]
-    fn next(&mut self) -> Result<u8, super::Error> {
-        let next = self.peek()?.clone();
-        self.input = &self.input[1..];
-        Ok(next)
+    fn is_empty(&self) -> bool {
+        self.list.is_empty()
     }
-    #[inline(always)]
-    unsafe fn take_unchecked_n(&mut self, n: usize) -> &'de [u8] {
-        use std::slice::from_raw_parts;
+}
+impl<'de> IntoIterator for Entries<'de> {
+    type Item     = (Cow<'de, str>, Node<'de>);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
 
-        let len = self.input.len();
-        let ptr = self.input.as_ptr();
+pub(crate) enum Scalar<'de> {
+    /// percent-encoded value
+    Raw(&'de [u8]),
+    /// already decoded, like a key
+    Decoded(Cow<'de, str>),
+}
 
-        // SAFETY: Caller has to check that `0 <= mid <= self.len()`
-        let (take, remaining) = (from_raw_parts(ptr, n), from_raw_parts(ptr.add(n), len - n));
+enum Segment<'de> {
+    /// `[]`
+    Push,
+    /// `[name]`, `[0]`
+    Key(Cow<'de, str>),
+}
 
-        self.input = remaining;
-        take
+impl<'de> Node<'de> {
+    pub(crate) fn parse(input: &'de [u8]) -> Result<Self, Error> {
+        let mut root = Entries::new();
+        for (i, pair) in input.split(|b| *b == b'&').filter(|pair| !pair.is_empty()).enumerate() {
+            if i == MAX_PAIRS {
+                return Err(Error::custom(format!("Too many pairs: more than {MAX_PAIRS}")))
+            }
+            let (key, value) = match pair.iter().position(|b| *b == b'=') {
+                Some(eq) => (&pair[..eq], Node::Value(Scalar::Raw(&pair[eq+1..]))),
+                None     => (pair, Node::Flag),
+            };
+            let key = percent_decode_utf8(key).map_err(|e| Error::custom(format!(
+                "Expected to be decoded to an UTF-8, but got `{}`: {e}", key.escape_ascii()
+            )))?;
+            let (name, path) = split_key(key)?;
+            match root.get_mut(&name) {
+                Some(node) => Node::insert(node, path.into_iter(), value)
+                    .map_err(|_| Error::custom(format!("Conflicting plain and nested values of `{name}`")))?,
+                None => root.push(name, Node::nested(path.into_iter(), value)),
+            }
+        }
+        Ok(Node::Map(root))
+    }
+
+    /// new node having `value` at `path`
+    fn nested(mut path: std::vec::IntoIter<Segment<'de>>, value: Node<'de>) -> Node<'de> {
+        match path.next() {
+            None                    => value,
+            Some(Segment::Push)     => Node::Seq(vec![Node::nested(path, value)]),
+            Some(Segment::Key(key)) => {
+                let mut map = Entries::new();
+                map.push(key, Node::nested(path, value));
+                Node::Map(map)
+            }
+        }
     }
-    #[inline(always)]
-    fn next_section(&mut self) -> Result<&'de [u8], super::Error> {
-        match &self.side {
-            ParsingSide::Key => {
-                let len = self.input.iter().position(|b| b==&b'=').ok_or_else(|| serde::de::Error::custom("can't get a key: unexpected end of input"))?;
-                (len > 0).then_some(unsafe {
-                    self.take_unchecked_n(len)
-                }).ok_or_else(|| serde::de::Error::custom("empty key"))
+
+    fn insert(&mut self, mut path: std::vec::IntoIter<Segment<'de>>, value: Node<'de>) -> Result<(), ()> {
+        match (self, path.next()) {
+            (Node::Seq(seq), None | Some(Segment::Push)) => {
+                seq.push(Node::nested(path, value));
+            }
+            (this @ (Node::Value(_) | Node::Flag), None | Some(Segment::Push)) => {
+                let first = std::mem::replace(this, Node::Flag);
+                *this = Node::Seq(vec![first, Node::nested(path, value)]);
             }
-            ParsingSide::Value => {
-                let len = self.input.iter().position(|b| b==&b'&').unwrap_or(self.input.len());
-                Ok(unsafe {self.take_unchecked_n(len)})
+            (Node::Map(map), Some(Segment::Key(key))) => match map.get_mut(&key) {
+                Some(node) => node.insert(path, value)?,
+                None => map.push(key, Node::nested(path, value)),
             }
+            _ => return Err(())
+        }
+        Ok(())
+    }
+
+    fn unexpected(&self) -> Error {
+        match self {
+            Node::Flag   => Error::custom("Expected a value, but got a key without value"),
+            Node::Seq(_) => Error::custom("Expected a single value, but got multiple values"),
+            Node::Map(_) => Error::custom("Expected a single value, but got nested keys"),
+            Node::Value(_) => Error::custom("Unexpected value"),
         }
     }
 }
 
-impl<'u, 'de> serde::Deserializer<'de> for &'u mut URLEncodedDeserializer<'de> {
-    type Error = super::Error;
+/// `name[a][]` into `name` and `[Key(a), Push]`, or just `name`
+/// when it's not well-bracketed
+fn split_key<'k>(key: Cow<'k, str>) -> Result<(Cow<'k, str>, Vec<Segment<'k>>), Error> {
+    fn bracketed(key: &str) -> Option<(usize, Vec<std::ops::Range<usize>>)> {
+        let name_len = key.find('[').filter(|i| *i > 0)?;
+        let mut segments = Vec::new();
+        let mut pos = name_len;
+        while pos < key.len() {
+            if key.as_bytes()[pos] != b'[' {return None}
+            let close = pos + 1 + key[pos+1..].find(']')?;
+            segments.push(pos+1..close);
+            pos = close + 1;
+        }
+        Some((name_len, segments))
+    }
+
+    if key.is_empty() {
+        return Err(Error::custom("empty key"))
+    }
+    let Some((name_len, segments)) = bracketed(&key) else {
+        return Ok((key, Vec::new()))
+    };
+    if segments.len() > MAX_DEPTH {
+        return Err(Error::custom(format!("Too deeply nested key: more than {MAX_DEPTH} brackets")))
+    }
+    let segment = |s: Cow<'k, str>| if s.is_empty() {Segment::Push} else {Segment::Key(s)};
+    Ok(match key {
+        Cow::Borrowed(key) => (
+            Cow::Borrowed(&key[..name_len]),
+            segments.into_iter().map(|r| segment(Cow::Borrowed(&key[r]))).collect()
+        ),
+        Cow::Owned(key) => (
+            Cow::Owned(key[..name_len].to_owned()),
+            segments.into_iter().map(|r| segment(Cow::Owned(key[r].to_owned()))).collect()
+        ),
+    })
+}
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        self.deserialize_map(visitor)
+impl<'de> IntoDeserializer<'de, Error> for Node<'de> {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
     }
-    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        self.deserialize_any(visitor)
+}
+impl<'de> IntoDeserializer<'de, Error> for Scalar<'de> {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
     }
+}
 
-    #[inline(always)]
-    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Key);
+macro_rules! forward_to_scalar {
+    ($( $method:ident )*) => {$(
+        #[inline]
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de> {
+            match self {
+                Node::Value(scalar) => scalar.$method(visitor),
+                other => Err(other.unexpected()),
+            }
         }
+    )*};
+}
 
-        visitor.visit_map(AmpersandSeparated::new(self))
-    }
-    #[inline(always)]
-    fn deserialize_struct<V>(
-        self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Key);
-        }
+impl<'de> serde::Deserializer<'de> for Node<'de> {
+    type Error = Error;
 
-        self.deserialize_map(visitor)
+    forward_to_scalar! {
+        deserialize_char deserialize_bytes deserialize_byte_buf
+        deserialize_f32 deserialize_f64
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
     }
 
-    #[inline]
-    fn deserialize_enum<V>(
-        self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        visitor.visit_enum(Enum::new(self))
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        match self {
+            Node::Value(scalar) => scalar.deserialize_any(visitor),
+            Node::Flag          => visitor.visit_unit(),
+            Node::Seq(_)        => self.deserialize_seq(visitor),
+            Node::Map(_)        => self.deserialize_map(visitor),
+        }
+    }
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        visitor.visit_unit()
     }
 
-    #[inline(always)]
-    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        /*
-            Here we don't put
-
-            ```
-            #[cfg(debug_assertions)] {
-                assert!(self.side == ParsingSide::Key);
-            }
-            ```
-            because `deserialize_identifier` can be called by value-place enums
-            like `enum Gender { Male, Female, Oter }`.
-        */
-
-        self.deserialize_str(visitor)
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        match self {
+            Node::Value(scalar) => scalar.deserialize_bool(visitor),
+            Node::Flag          => visitor.visit_bool(true),
+            other               => Err(other.unexpected()),
+        }
     }
-    #[inline(always)]
+
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        let section = self.next_section()?;
-        match percent_decode_utf8(section).map_err(|e|
-            serde::de::Error::custom(format!("Expected to be decoded to an UTF-8, but got `{}`: {e}", section.escape_ascii()))
-        )? {
-            Cow::Borrowed(str) => visitor.visit_borrowed_str(str),
-            Cow::Owned(string) => visitor.visit_string(string),
+    where V: Visitor<'de> {
+        match self {
+            Node::Value(scalar) => scalar.deserialize_str(visitor),
+            Node::Flag          => visitor.visit_borrowed_str(""),
+            other               => Err(other.unexpected()),
         }
     }
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
+    where V: Visitor<'de> {
         self.deserialize_str(visitor)
     }
-    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        let section = self.next_section()?;
-        let decoded = percent_decode_utf8(section).map_err(|e|
-            serde::de::Error::custom(format!("Expected to be decoded to an UTF-8, but got `{}`: {e}", section.escape_ascii()))
-        )?;
-        let mut chars = decoded.chars();
-        let (Some(ch), None) = (chars.next(), chars.next()) else {
-            return Err((|| serde::de::Error::custom(
-                format!("Expected a single charactor, but got `{}`", section.escape_ascii())
-            ))())
-        };
-        visitor.visit_char(ch)
-    }
-
-    #[inline]
-    fn deserialize_newtype_struct<V>(
-        self,
-        _name: &'static str,
-        visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        visitor.visit_newtype_struct(self)
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_str(visitor)
     }
 
-    #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        if self.input.iter().position(|b| b==&b'&').unwrap_or(self.input.len()) == 0 {
-            visitor.visit_none()
-        } else {
-            visitor.visit_some(self)
+    where V: Visitor<'de> {
+        match &self {
+            Node::Value(scalar) if scalar.is_empty() => visitor.visit_none(),
+            Node::Map(map) if map.is_empty()         => visitor.visit_none(),
+            _ => visitor.visit_some(self)
         }
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        if self.input.iter().position(|b| b==&b'&').unwrap_or(self.input.len()) == 0 {
-            visitor.visit_unit()
-        } else {
-            Err((|| serde::de::Error::custom(format!(
-                "Expected an empty value for an unit, but got `{}`",
-                match self.next_section() {
-                    Ok(section) => section.escape_ascii().to_string(),
-                    Err(e) => e.to_string()
-                }
-            )))())
+    where V: Visitor<'de> {
+        match self {
+            Node::Value(scalar) => scalar.deserialize_unit(visitor),
+            Node::Flag          => visitor.visit_unit(),
+            other               => Err(other.unexpected()),
         }
     }
     fn deserialize_unit_struct<V>(
@@ -197,16 +268,38 @@ impl<'u, 'de> serde::Deserializer<'de> for &'u mut URLEncodedDeserializer<'de> {
         _name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
+    where V: Visitor<'de> {
         self.deserialize_unit(visitor)
     }
 
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        visitor.visit_seq(CommaSeparated::new(self))
+    where V: Visitor<'de> {
+        match self {
+            Node::Value(scalar) => scalar.deserialize_seq(visitor),
+            Node::Seq(seq) => visitor.visit_seq(SeqDeserializer::new(seq.into_iter())),
+            Node::Map(map) => {
+                let mut indexed = map.into_iter()
+                    .map(|(key, node)| key.parse::<usize>().map(|i| (i, node)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| Error::custom("Expected a sequence, but got nested keys"))?;
+                indexed.sort_by_key(|(i, _)| *i);
+                visitor.visit_seq(SeqDeserializer::new(indexed.into_iter().map(|(_, node)| node)))
+            }
+            Node::Flag => Err(Node::Flag.unexpected()),
+        }
     }
     fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
+    where V: Visitor<'de> {
         self.deserialize_seq(visitor)
     }
     fn deserialize_tuple_struct<V>(
@@ -215,354 +308,243 @@ impl<'u, 'de> serde::Deserializer<'de> for &'u mut URLEncodedDeserializer<'de> {
         _len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
+    where V: Visitor<'de> {
         self.deserialize_seq(visitor)
     }
 
-    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        match percent_decode(self.next_section().unwrap()) {
-            Cow::Borrowed(slice) => visitor.visit_bytes(slice),
-            Cow::Owned(byte_vec) => visitor.visit_byte_buf(byte_vec),
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        match self {
+            Node::Map(map) => visitor.visit_map(MapDeserializer::new(
+                map.into_iter().map(|(key, node)| (Scalar::Decoded(key), node))
+            )),
+            _ => Err(Error::custom("Expected nested keys like `key[name]=value`"))
         }
     }
-    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        self.deserialize_bytes(visitor)
+    #[inline(always)]
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_map(visitor)
     }
 
-    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        match self.next_section().unwrap() {
-            b"true"  => visitor.visit_bool(true),
-            b"false" => visitor.visit_bool(false),
-            other   => Err(serde::de::Error::custom(format!(
-                "Expected `true` or `false`, but got `{}`",
-                other.escape_ascii()
-            )))
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        match self {
+            Node::Value(scalar) => scalar.deserialize_enum(name, variants, visitor),
+            other => Err(other.unexpected()),
         }
     }
+}
 
-    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a f32, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_f32(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a f32, but got `{section}`")
-            ))?
-        )
-    }
-    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a f64, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_f64(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a f64, but got `{section}`")
-            ))?
-        )
-    }
-
-    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a i8, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_i8(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a i8, but got `{section}`")
-            ))?
-        )
-    }
-    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a i16, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_i16(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a i16, but got `{section}`")
-            ))?
-        )
-    }
-    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
-
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a i32, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_i32(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a i32, but got `{section}`")
-            ))?
-        )
-    }
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
-        }
 
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a i64, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_i64(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a i64, but got `{section}`")
-            ))?
-        )
-    }
-
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
+impl<'de> Scalar<'de> {
+    fn is_empty(&self) -> bool {
+        match self {
+            Scalar::Raw(raw)         => raw.is_empty(),
+            Scalar::Decoded(decoded) => decoded.is_empty(),
         }
+    }
 
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a u8, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_u8(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a u8, but got `{section}`")
-            ))?
-        )
-    }
-    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
+    fn decoded(self) -> Result<Cow<'de, str>, Error> {
+        match self {
+            Scalar::Decoded(decoded) => Ok(decoded),
+            Scalar::Raw(raw) => percent_decode_utf8(raw).map_err(|e| Error::custom(format!(
+                "Expected to be decoded to an UTF-8, but got `{}`: {e}", raw.escape_ascii()
+            ))),
         }
+    }
 
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a u16, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_u16(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a u16, but got `{section}`")
-            ))?
-        )
-    }
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
+    /// comma-separated elements like `1,17,42`
+    fn split_comma(self) -> Vec<Scalar<'de>> {
+        match self {
+            _ if self.is_empty() => Vec::new(),
+            Scalar::Raw(raw) => raw.split(|b| *b == b',').map(Scalar::Raw).collect(),
+            Scalar::Decoded(Cow::Borrowed(s)) => s.split(',').map(|e| Scalar::Decoded(Cow::Borrowed(e))).collect(),
+            Scalar::Decoded(Cow::Owned(s)) => s.split(',').map(|e| Scalar::Decoded(Cow::Owned(e.to_owned()))).collect(),
         }
+    }
+}
 
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a u32, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_u32(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a u32, but got `{section}`")
-            ))?
-        )
-    }
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where V: serde::de::Visitor<'de> {
-        #[cfg(debug_assertions)] {
-            assert!(self.side == ParsingSide::Value);
+macro_rules! deserialize_number {
+    ($( $method:ident => $visit:ident : $t:ty ),*) => {$(
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de> {
+            let decoded = self.decoded()?;
+            visitor.$visit(decoded.parse::<$t>().map_err(|_| Error::custom(
+                format!(concat!("Expected a ", stringify!($t), ", but got `{}`"), decoded)
+            ))?)
         }
-
-        let section = self.next_section().unwrap();
-        let section = std::str::from_utf8(section)
-            .map_err(|_| serde::de::Error::custom(
-                format!("Expected a u64, but got `{}`", section.escape_ascii())
-            ))?;
-        visitor.visit_u64(
-            section.parse().map_err(|_| serde::de::Error::custom(
-                format!("Expected a u64, but got `{section}`")
-            ))?
-        )
-    }
+    )*};
 }
 
+impl<'de> serde::Deserializer<'de> for Scalar<'de> {
+    type Error = Error;
 
-struct AmpersandSeparated<'amp, 'de:'amp> {
-    de:    &'amp mut URLEncodedDeserializer<'de>,
-    first: bool,
-}
-impl<'amp, 'de> AmpersandSeparated<'amp, 'de> {
-    fn new(de: &'amp mut URLEncodedDeserializer<'de>) -> Self {
-        Self { de, first: true }
+    deserialize_number! {
+        deserialize_f32 => visit_f32 : f32,
+        deserialize_f64 => visit_f64 : f64,
+        deserialize_i8  => visit_i8  : i8,
+        deserialize_i16 => visit_i16 : i16,
+        deserialize_i32 => visit_i32 : i32,
+        deserialize_i64 => visit_i64 : i64,
+        deserialize_u8  => visit_u8  : u8,
+        deserialize_u16 => visit_u16 : u16,
+        deserialize_u32 => visit_u32 : u32,
+        deserialize_u64 => visit_u64 : u64
     }
-}
-const _: () = {
-    impl<'amp, 'de> serde::de::MapAccess<'de> for AmpersandSeparated<'amp, 'de> {
-        type Error = super::Error;
 
-        #[inline]
-        fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
-        where K: serde::de::DeserializeSeed<'de> {
-            if self.de.input.is_empty() {
-                return Ok(None)
-            }
-            if !self.first && self.de.next()? != b'&' {
-                return Err((|| serde::de::Error::custom("missing `&`"))())
-            }
-            self.first = false;
-
-            self.de.side = ParsingSide::Key;
-            seed.deserialize(
-                // self.de.next_key()?.into_deserializer()
-                &mut *self.de
-            ).map(Some)
-        }
-        #[inline]
-        fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
-        where V: serde::de::DeserializeSeed<'de> {
-            if self.de.next()? != b'=' {
-                return Err((|| serde::de::Error::custom("missing `=`"))())
-            }
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_str(visitor)
+    }
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        visitor.visit_unit()
+    }
 
-            self.de.side = ParsingSide::Value;
-            seed.deserialize(
-                &mut *self.de
-                // self.de.next_value().into_deserializer()
-            )
+    #[inline(always)]
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        match self.decoded()? {
+            Cow::Borrowed(str) => visitor.visit_borrowed_str(str),
+            Cow::Owned(string) => visitor.visit_string(string),
         }
     }
-
-    struct ValueDeserializer<'de> {
-        input: &'de str
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_str(visitor)
     }
-};
-
-struct Enum<'e, 'de:'e> {
-    de: &'e mut URLEncodedDeserializer<'de>
-}
-impl<'e, 'de> Enum<'e, 'de> {
-    fn new(de: &'e mut URLEncodedDeserializer<'de>) -> Self {
-        Self { de }
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_str(visitor)
     }
-}
-const _: () = {
-    impl<'e, 'de> serde::de::EnumAccess<'de> for Enum<'e, 'de> {
-        type Variant = Self;
-        type Error   = super::Error;
-
-        fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
-        where V: serde::de::DeserializeSeed<'de> {
-            Ok((
-                seed.deserialize(self.de.next_section().unwrap().into_deserializer())?,
-                self,
-            ))
-        }
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        let decoded = self.decoded()?;
+        let mut chars = decoded.chars();
+        let (Some(ch), None) = (chars.next(), chars.next()) else {
+            return Err(Error::custom(format!("Expected a single charactor, but got `{decoded}`")))
+        };
+        visitor.visit_char(ch)
     }
 
-    impl<'e, 'de> serde::de::VariantAccess<'de> for Enum<'e, 'de> {
-        type Error = super::Error;
-
-        fn unit_variant(self) -> Result<(), Self::Error> {
-            Ok(())
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        match self {
+            Scalar::Raw(raw) => match percent_decode(raw) {
+                Cow::Borrowed(slice) => visitor.visit_borrowed_bytes(slice),
+                Cow::Owned(byte_vec) => visitor.visit_byte_buf(byte_vec),
+            },
+            Scalar::Decoded(Cow::Borrowed(str)) => visitor.visit_borrowed_bytes(str.as_bytes()),
+            Scalar::Decoded(Cow::Owned(string)) => visitor.visit_byte_buf(string.into_bytes()),
         }
+    }
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_bytes(visitor)
+    }
 
-        fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Self::Error>
-        where T: serde::de::DeserializeSeed<'de> {
-            // seed.deserialize(self.de)
-            
-            Err(serde::de::Error::custom("ohkami's builtin urlencoded deserializer doesn't support enum with newtype variants !"))
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        match &*self.decoded()? {
+            "true"  => visitor.visit_bool(true),
+            "false" => visitor.visit_bool(false),
+            other   => Err(Error::custom(format!(
+                "Expected `true` or `false`, but got `{other}`"
+            )))
         }
+    }
 
-        fn struct_variant<V>(
-            self,
-            _fields: &'static [&'static str],
-            _visitor: V,
-        ) -> Result<V::Value, Self::Error>
-        where V: serde::de::Visitor<'de> {
-            Err(serde::de::Error::custom("ohkami's builtin urlencoded deserializer doesn't support enum with struct variants !"))
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        if self.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
         }
+    }
 
-        fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
-        where V: serde::de::Visitor<'de> {
-            Err(serde::de::Error::custom("ohkami's builtin urlencoded deserializer doesn't support enum with tuple variants !"))
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        if self.is_empty() {
+            visitor.visit_unit()
+        } else {
+            Err(Error::custom(format!(
+                "Expected an empty value for an unit, but got `{}`", self.decoded()?
+            )))
         }
     }
-};
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_unit(visitor)
+    }
 
-struct CommaSeparated<'de> {
-    section: &'de [u8],
-    first:   bool,
-}
-impl<'de> CommaSeparated<'de> {
-    fn new(de: &mut URLEncodedDeserializer<'de>) -> Self {
-        Self {
-            section: de.next_section().unwrap(),
-            first:   true,
-        }
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
     }
-}
-const _: () = {
-    impl<'de> serde::de::SeqAccess<'de> for CommaSeparated<'de> {
-        type Error = super::Error;
-
-        fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
-        where T: serde::de::DeserializeSeed<'de> {
-            if self.section.is_empty() {
-                return Ok(None)
-            }
-            if !self.first && self.section.first() == Some(&b',') {
-                return Err(serde::de::Error::custom("missing ,"))
-            }
-            self.first = false;
 
-            let size = self.section.iter().position(|b| b==&b',').unwrap_or(self.section.len());
-            let (element, remaining) = self.section.split_at(size);
-            self.section = remaining;
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        visitor.visit_seq(SeqDeserializer::new(self.split_comma().into_iter()))
+    }
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_seq(visitor)
+    }
 
-            seed.deserialize(element.into_deserializer()).map(Some)
-        }
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        Err(Error::custom("Expected nested keys like `key[name]=value`"))
+    }
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_map(visitor)
     }
-};
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        /* only unit variants, like `enum Gender { Male, Female, Other }` */
+        visitor.visit_enum(self.decoded()?.into_deserializer())
+    }
+}
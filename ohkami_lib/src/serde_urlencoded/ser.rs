@@ -1,19 +1,18 @@
-use serde::Serialize as _;
+use serde::ser::Error as _;
 use crate::percent_encode;
 
 
+/// Serializes a map-like value into `key=value&...`,
+/// where a sequence is serialized as repeated keys ( `ids=1&ids=2` )
+/// and a nested map as bracketed keys ( `user[name]=ohkami` )
 pub(crate) struct URLEncodedSerializer {
     output: String,
-
-    /// To forbid nesting maps
-    init: bool,
 }
 impl URLEncodedSerializer {
     #[inline]
     pub(crate) const fn new() -> Self {
         Self {
             output: String::new(),
-            init:   true,
         }
     }
 
@@ -21,47 +20,96 @@ impl URLEncodedSerializer {
     pub(crate) fn output(self) -> String {
         self.output
     }
+
+    fn push_pair(&mut self, key: &str, value: &str) {
+        if !self.output.is_empty() {
+            self.output.push('&');
+        }
+        self.output.push_str(key);
+        self.output.push('=');
+        self.output.push_str(value);
+    }
+}
+
+/// Serializer of a value at the key
+struct ValueSerializer<'s> {
+    ser: &'s mut URLEncodedSerializer,
+    /// key for a scalar value, repeated for each element of a sequence
+    key: String,
+    /// key for a map or sequence, like `{key}[0]` for an element of a sequence
+    nested_key: String,
+}
+impl<'s> ValueSerializer<'s> {
+    fn new(ser: &'s mut URLEncodedSerializer, key: String) -> Self {
+        Self { ser, nested_key: key.clone(), key }
+    }
+    fn scalar(self, value: &str) -> Result<(), super::Error> {
+        self.ser.push_pair(&self.key, value);
+        Ok(())
+    }
+}
+
+/// Serializer of a map or a sequence of the `key`, `None` for the top level
+pub(crate) struct Compound<'s> {
+    ser:   &'s mut URLEncodedSerializer,
+    key:   Option<String>,
+    /// for a map : the key of the next value
+    next:  Option<String>,
+    /// for a sequence : the index of the next element
+    index: usize,
+}
+impl<'s> Compound<'s> {
+    fn child_key(&self, name: &str) -> String {
+        match &self.key {
+            None      => name.to_owned(),
+            Some(key) => format!("{key}[{name}]"),
+        }
+    }
+    fn field<T: ?Sized + serde::Serialize>(&mut self, key: String, value: &T) -> Result<(), super::Error> {
+        value.serialize(ValueSerializer::new(self.ser, key))
+    }
+    fn element<T: ?Sized + serde::Serialize>(&mut self, value: &T) -> Result<(), super::Error> {
+        let key = self.key.clone().expect("sequence at the top level");
+        let nested_key = format!("{key}[{}]", self.index);
+        self.index += 1;
+        value.serialize(ValueSerializer { ser: self.ser, key, nested_key })
+    }
 }
 
 const _: () = {
-    impl serde::ser::SerializeMap for &mut URLEncodedSerializer {
-        type Ok = ();
+    impl serde::ser::SerializeMap for Compound<'_> {
+        type Ok    = ();
         type Error = super::Error;
 
-        fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
-        where T: serde::Serialize {
-            if !self.output.is_empty() {
-                self.output.push('&');
-            }
-            key.serialize(&mut **self)
+        fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {
+            let mut name = String::new();
+            key.serialize(KeySerializer(&mut name))?;
+            self.next = Some(self.child_key(&name));
+            Ok(())
         }
-        fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-        where T: serde::Serialize {
-            self.output.push('=');
-            value.serialize(&mut **self)
+        fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {
+            let key = self.next.take().ok_or_else(|| super::Error::custom("`serialize_value` before `serialize_key`"))?;
+            self.field(key, value)
         }
         fn end(self) -> Result<Self::Ok, Self::Error> {
             Ok(())
         }
     }
-    impl serde::ser::SerializeStruct for &mut URLEncodedSerializer {
+    impl serde::ser::SerializeStruct for Compound<'_> {
         type Ok    = ();
         type Error = super::Error;
 
         #[inline(always)]
-        fn serialize_field<T: ?Sized>(
+        fn serialize_field<T>(
             &mut self,
             key: &'static str,
             value: &T,
         ) -> Result<(), Self::Error>
-        where T: serde::Serialize {
-            if !self.output.is_empty() {
-                self.output.push('&');
-            }
-            key.serialize(&mut **self)?;
-            self.output.push('=');
-            value.serialize(&mut **self)?;
-            Ok(())
+        where T: ?Sized + serde::Serialize {
+            let key = self.child_key(&percent_encode(key));
+            self.field(key, value)
         }
         #[inline(always)]
         fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -69,172 +117,233 @@ const _: () = {
         }
     }
 
-    impl serde::ser::SerializeSeq for &mut URLEncodedSerializer {
+    impl serde::ser::SerializeSeq for Compound<'_> {
         type Ok    = ();
         type Error = super::Error;
 
-        fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-        where T: serde::Serialize {
-            if !self.output.ends_with('=') {
-                self.output.push(',');
-            }
-            value.serialize(&mut **self)
+        fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {
+            self.element(value)
         }
         fn end(self) -> Result<Self::Ok, Self::Error> {
             Ok(())
         }
     }
-    impl serde::ser::SerializeTuple for &mut URLEncodedSerializer {
+    impl serde::ser::SerializeTuple for Compound<'_> {
         type Ok    = ();
         type Error = super::Error;
 
-        fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-        where T: serde::Serialize {
-            if !self.output.ends_with('=') {
-                self.output.push(',');
-            }
-            value.serialize(&mut **self)
+        fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {
+            self.element(value)
         }
         fn end(self) -> Result<Self::Ok, Self::Error> {
             Ok(())
         }
     }
-    impl serde::ser::SerializeTupleStruct for &mut URLEncodedSerializer {
+    impl serde::ser::SerializeTupleStruct for Compound<'_> {
         type Ok    = ();
         type Error = super::Error;
 
-        fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-        where T: serde::Serialize {
-            if !self.output.ends_with('=') {
-                self.output.push(',');
-            }
-            value.serialize(&mut **self)
+        fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+        where T: ?Sized + serde::Serialize {
+            self.element(value)
         }
         fn end(self) -> Result<Self::Ok, Self::Error> {
             Ok(())
         }
     }
-    impl serde::ser::SerializeTupleVariant for &mut URLEncodedSerializer {
-        type Ok    = ();
-        type Error = super::Error;
+};
 
-        fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-        where T: serde::Serialize {
-            if !self.output.ends_with('=') {
-                self.output.push(',');
-            }
-            value.serialize(&mut **self)
+macro_rules! serialize_to_string {
+    ($( $method:ident : $t:ty ),*) => {$(
+        fn $method(self, v: $t) -> Result<Self::Ok, Self::Error> {
+            self.scalar(&v.to_string())
         }
-        fn end(self) -> Result<Self::Ok, Self::Error> {
-            Ok(())
+    )*};
+}
+
+macro_rules! top_level_only_map {
+    ($( $method:ident $(: $t:ty)? ),*) => {$(
+        fn $method(self $(, _: $t)?) -> Result<Self::Ok, Self::Error> {
+            Err(super::Error::custom("ohkami's builtin urlencoded serializer supports only map-like values at the top level !"))
         }
-    }
-};
+    )*};
+}
 
-impl serde::Serializer for &mut URLEncodedSerializer {
+impl<'s> serde::Serializer for &'s mut URLEncodedSerializer {
     type Ok    = ();
     type Error = super::Error;
 
-    type SerializeMap           = Self;
-    type SerializeStruct        = Self;
+    type SerializeMap           = Compound<'s>;
+    type SerializeStruct        = Compound<'s>;
 
-    type SerializeSeq           = Self;
-    type SerializeTuple         = Self;
-    type SerializeTupleStruct   = Self;
+    type SerializeSeq           = super::Infallible;
+    type SerializeTuple         = super::Infallible;
+    type SerializeTupleStruct   = super::Infallible;
 
     type SerializeTupleVariant  = super::Infallible;
     type SerializeStructVariant = super::Infallible;
 
-    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(if v {"true"} else {"false"});
-        Ok(())
+    top_level_only_map! {
+        serialize_bool: bool, serialize_char: char, serialize_str: &str, serialize_bytes: &[u8],
+        serialize_f32: f32, serialize_f64: f64,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64,
+        serialize_unit_struct: &'static str
     }
 
-    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> {
-        Err(serde::ser::Error::custom("ohkami's builtin urlencoded serializer doesn't support raw byte data !"))
+    #[inline]
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where T: ?Sized + serde::Serialize {
+        value.serialize(self)
     }
 
-    #[inline(always)]
-    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&percent_encode(v));
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where T: ?Sized + serde::Serialize {
+        value.serialize(self)
+    }
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
         Ok(())
     }
-    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        self.output.push(v);
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
         Ok(())
     }
 
-    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+    #[inline(always)]
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Compound { ser: self, key: None, next: None, index: 0 })
     }
-    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+    #[inline(always)]
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
     }
 
-    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(super::Error::custom("ohkami's builtin urlencoded serializer supports only map-like values at the top level !"))
     }
-    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
     }
-    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where T: ?Sized + serde::Serialize {
+        value.serialize(ValueSerializer::new(self, percent_encode(variant).into_owned()))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(serde::ser::Error::custom("ohkami's builtin urlencoded serializer doesn't support enum with struct variants !"))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(serde::ser::Error::custom("ohkami's builtin urlencoded serializer doesn't support enum with tuple variants !"))
     }
-    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())        
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(super::Error::custom("ohkami's builtin urlencoded serializer supports only map-like values at the top level !"))
     }
+}
 
-    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+impl<'s> serde::Serializer for ValueSerializer<'s> {
+    type Ok    = ();
+    type Error = super::Error;
+
+    type SerializeMap           = Compound<'s>;
+    type SerializeStruct        = Compound<'s>;
+
+    type SerializeSeq           = Compound<'s>;
+    type SerializeTuple         = Compound<'s>;
+    type SerializeTupleStruct   = Compound<'s>;
+
+    type SerializeTupleVariant  = super::Infallible;
+    type SerializeStructVariant = super::Infallible;
+
+    serialize_to_string! {
+        serialize_bool: bool,
+        serialize_f32: f32, serialize_f64: f64,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64
     }
-    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(serde::ser::Error::custom("ohkami's builtin urlencoded serializer doesn't support raw byte data !"))
     }
-    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())
+
+    #[inline(always)]
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.scalar(&percent_encode(v))
     }
-    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        self.output.push_str(&v.to_string());
-        Ok(())        
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     #[inline]
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
-    where T: serde::Serialize {
+    where T: ?Sized + serde::Serialize {
         value.serialize(self)
     }
 
     #[inline]
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
-    where T: serde::Serialize {
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where T: ?Sized + serde::Serialize {
         value.serialize(self)
     }
     #[inline]
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.scalar("")
     }
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.scalar("")
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
     }
 
     #[inline(always)]
-    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        let _ = len;
-        self.init.then_some({self.init = false; self})
-            .ok_or_else(||serde::ser::Error::custom("ohkami's builtin urlencoded serializer doesn't support nested maps or map-like structs !"))
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Compound { ser: self.ser, key: Some(self.nested_key), next: None, index: 0 })
     }
     #[inline(always)]
     fn serialize_struct(
@@ -244,6 +353,13 @@ impl serde::Serializer for &mut URLEncodedSerializer {
     ) -> Result<Self::SerializeStruct, Self::Error> {
         self.serialize_map(Some(len))
     }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(Compound { ser: self.ser, key: Some(self.nested_key), next: None, index: 0 })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
@@ -251,33 +367,18 @@ impl serde::Serializer for &mut URLEncodedSerializer {
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
         self.serialize_seq(Some(len))
     }
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
-        self.serialize_unit()
-    }
-
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        Ok(self)
-    }
-    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        self.serialize_seq(Some(len))
-    }
 
     #[inline]
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
-    where T: serde::Serialize {
-        if !self.output.is_empty() {
-            self.output.push('&');
-        }
-        variant.serialize(&mut *self)?;
-        self.output.push('=');
-        value.serialize(&mut *self)?;
-        Ok(())
+    where T: ?Sized + serde::Serialize {
+        let key = format!("{}[{}]", self.nested_key, percent_encode(variant));
+        value.serialize(ValueSerializer::new(self.ser, key))
     }
     fn serialize_struct_variant(
         self,
@@ -303,6 +404,101 @@ impl serde::Serializer for &mut URLEncodedSerializer {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        variant.serialize(self)
+        self.serialize_str(variant)
+    }
+}
+
+/// Serializes a map key into the percent-encoded string
+struct KeySerializer<'k>(&'k mut String);
+
+macro_rules! key_to_string {
+    ($( $method:ident : $t:ty ),*) => {$(
+        fn $method(self, v: $t) -> Result<Self::Ok, Self::Error> {
+            *self.0 = v.to_string();
+            Ok(())
+        }
+    )*};
+}
+
+macro_rules! unsupported_key {
+    ($( $method:ident $(: $t:ty)? ),*) => {$(
+        fn $method(self $(, _: $t)?) -> Result<Self::Ok, Self::Error> {
+            Err(super::Error::custom("ohkami's builtin urlencoded serializer supports only string, number or unit variant as a map key !"))
+        }
+    )*};
+}
+
+impl serde::Serializer for KeySerializer<'_> {
+    type Ok    = ();
+    type Error = super::Error;
+
+    type SerializeMap           = super::Infallible;
+    type SerializeStruct        = super::Infallible;
+    type SerializeSeq           = super::Infallible;
+    type SerializeTuple         = super::Infallible;
+    type SerializeTupleStruct   = super::Infallible;
+    type SerializeTupleVariant  = super::Infallible;
+    type SerializeStructVariant = super::Infallible;
+
+    key_to_string! {
+        serialize_bool: bool,
+        serialize_f32: f32, serialize_f64: f64,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64
+    }
+
+    unsupported_key! {
+        serialize_bytes: &[u8], serialize_none, serialize_unit, serialize_unit_struct: &'static str
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        *self.0 = percent_encode(v).into_owned();
+        Ok(())
+    }
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where T: ?Sized + serde::Serialize {
+        value.serialize(self)
+    }
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
+    where T: ?Sized + serde::Serialize {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<Self::Ok, Self::Error>
+    where T: ?Sized + serde::Serialize {
+        Err(super::Error::custom("ohkami's builtin urlencoded serializer supports only string, number or unit variant as a map key !"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(super::Error::custom("ohkami's builtin urlencoded serializer supports only string, number or unit variant as a map key !"))
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(None)
+    }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(None)
+    }
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.serialize_seq(None)
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.serialize_seq(None)
+    }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_seq(None)
+    }
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.serialize_seq(None)
     }
 }
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, ptr::NonNull};
+use std::{borrow::Cow, ptr::NonNull, sync::Arc};
 
 
 /// A byte slice with **MANUALLY HANDLE** the *lifetime*
@@ -64,13 +64,16 @@ const _: () = {
 pub enum CowSlice {
     Ref(Slice),
     Own(Box<[u8]>),
+    /// shared among clones without copying, like a static file content
+    Shared(Arc<[u8]>),
 }
 impl CowSlice {
     #[inline(always)]
     pub unsafe fn as_bytes(&self) -> &[u8] {
         match self {
-            Self::Own(array) => &array,
-            Self::Ref(slice) => unsafe {slice.as_bytes()},
+            Self::Own(array)     => &array,
+            Self::Ref(slice)     => unsafe {slice.as_bytes()},
+            Self::Shared(shared) => shared,
         }
     }
 
@@ -91,14 +94,20 @@ impl CowSlice {
                 vec.extend_from_slice(bytes);
                 *self = Self::Own(vec.into_boxed_slice());
             }
+            Self::Shared(shared) => {
+                let mut vec: Vec<_> = shared.to_vec();
+                vec.extend_from_slice(bytes);
+                *self = Self::Own(vec.into_boxed_slice());
+            }
         }
     }
 
     #[inline]
     pub unsafe fn into_cow_static_bytes_uncheked(self) -> Cow<'static, [u8]> {
         match self {
-            Self::Own(array) => Cow::Owned(array.into()),
-            Self::Ref(slice) => Cow::Borrowed(slice.as_bytes()),
+            Self::Own(array)     => Cow::Owned(array.into()),
+            Self::Ref(slice)     => Cow::Borrowed(slice.as_bytes()),
+            Self::Shared(shared) => Cow::Owned(shared.to_vec()),
         }
     }
 }
@@ -107,8 +116,9 @@ const _: () = {
         #[inline]
         fn as_ref(&self) -> &[u8] {
             match self {
-                Self::Own(array) => array,
-                Self::Ref(slice) => unsafe {slice.as_bytes()},
+                Self::Own(array)     => array,
+                Self::Ref(slice)     => unsafe {slice.as_bytes()},
+                Self::Shared(shared) => shared,
             }
         }
     }
@@ -148,11 +158,18 @@ const _: () = {
         #[inline]
         fn into(self) -> Vec<u8> {
             match self {
-                Self::Own(array) => array.into(),
-                Self::Ref(slice) => Vec::from(unsafe {slice.as_bytes()}),
+                Self::Own(array)     => array.into(),
+                Self::Ref(slice)     => Vec::from(unsafe {slice.as_bytes()}),
+                Self::Shared(shared) => shared.to_vec(),
             }
         }
     }
+    impl From<Arc<[u8]>> for CowSlice {
+        #[inline]
+        fn from(shared: Arc<[u8]>) -> Self {
+            Self::Shared(shared)
+        }
+    }
     impl From<&'static [u8]> for CowSlice {
         #[inline]
         fn from(slice: &'static [u8]) -> Self {
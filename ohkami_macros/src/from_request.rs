@@ -31,7 +31,7 @@ pub(super) fn derive_from_request(target: TokenStream) -> Result<TokenStream> {
                     match <#ty as ::ohkami::FromRequest>::from_request(req)? {
                         ::std::result::Result::Ok(field) => field,
                         ::std::result::Result::Err(err)  => return Some(::std::result::Result::Err(
-                            ::ohkami::IntoResponse::into_response(err)
+                            ::ohkami::Rejection::__from(err).__of::<#ty>()
                         )),
                     }
                 }
@@ -45,7 +45,7 @@ pub(super) fn derive_from_request(target: TokenStream) -> Result<TokenStream> {
                     match <#ty as ::ohkami::FromRequest>::from_request(req)? {
                         ::std::result::Result::Ok(field) => field,
                         ::std::result::Result::Err(err)  => return Some(::std::result::Result::Err(
-                            ::ohkami::IntoResponse::into_response(err)
+                            ::ohkami::Rejection::__from(err).__of::<#ty>()
                         )),
                     }
                 }
@@ -57,7 +57,7 @@ pub(super) fn derive_from_request(target: TokenStream) -> Result<TokenStream> {
         impl<#generics_params_l> ::ohkami::FromRequest<#impl_lifetime> for #name<#generics_params_r>
             #generics_where
         {
-            type Error = ::ohkami::Response;
+            type Error = ::ohkami::Rejection;
             fn from_request(req: &#impl_lifetime ::ohkami::Request) -> ::std::option::Option<::std::result::Result<Self, Self::Error>> {
                 ::std::option::Option::Some(::std::result::Result::Ok(#build))
             }
@@ -0,0 +1,173 @@
+use quote::{quote, ToTokens};
+use proc_macro2::{TokenStream, Span};
+use syn::{Error, Field, GenericParam, ItemStruct, Lifetime, LifetimeDef, Lit, Meta, NestedMeta, PathArguments, GenericArgument, Result, Type};
+
+
+#[allow(non_snake_case)]
+pub(super) fn Headers(target: TokenStream) -> Result<TokenStream> {
+    let mut target: ItemStruct = syn::parse2(target)?;
+    if target.semi_token.is_some() {
+        return Err(Error::new(Span::call_site(), "#[Headers] doesn't support unit / tuple struct !"))
+    }
+
+    let name = &target.ident;
+    let generics_params = &target.generics.params;
+    let generics_where  = &target.generics.where_clause;
+
+    let (
+        from_request_lifetime,
+        from_request_impl_additional_lifetime
+    ) = match &target.generics.lifetimes().count() {
+        0 => (
+            GenericParam::Lifetime(LifetimeDef::new(
+                Lifetime::new("'__from_request", Span::call_site())
+            )),
+            Some(quote!{
+                '__from_request ,
+            })
+        ),
+        1 => (
+            target.generics.params.first().unwrap().clone(),
+            None
+        ),
+        _ => return Err(syn::Error::new(Span::call_site(), "#[Headers] doesn't support multiple lifetime params"))
+    };
+
+    let mut extract_fields = Vec::with_capacity(target.fields.len());
+    for field in &mut target.fields {
+        let HeaderAttr { name: header_name, serde } = header_attr(field)?;
+        field.attrs.retain(|a| !a.path.is_ident("header"));
+
+        let ident = field.ident.as_ref().unwrap();
+        let (ty, optional) = match option_inner(&field.ty) {
+            Some(inner) => (inner, true),
+            None        => (&field.ty, false),
+        };
+
+        let parse = if serde {quote! {
+            match ::ohkami::serde::json::from_str::<#ty>(value) {
+                ::std::result::Result::Ok(value) => value,
+                ::std::result::Result::Err(e) => return ::std::option::Option::Some(::std::result::Result::Err(
+                    ::ohkami::Rejection::header(#header_name, e)
+                )),
+            }
+        }} else {match value_kind(ty) {
+            ValueKind::Str => quote! {
+                value
+            },
+            ValueKind::Cow => quote! {
+                ::std::borrow::Cow::Borrowed(value)
+            },
+            ValueKind::FromStr => quote! {
+                match <#ty as ::std::str::FromStr>::from_str(value.trim()) {
+                    ::std::result::Result::Ok(value) => value,
+                    ::std::result::Result::Err(e) => return ::std::option::Option::Some(::std::result::Result::Err(
+                        ::ohkami::Rejection::header(#header_name, e)
+                    )),
+                }
+            },
+        }};
+
+        extract_fields.push(if optional {quote! {
+            #ident: match req.headers.by_name(#header_name) {
+                ::std::option::Option::None => ::std::option::Option::None,
+                ::std::option::Option::Some(value) => ::std::option::Option::Some(#parse),
+            },
+        }} else {quote! {
+            #ident: match req.headers.by_name(#header_name) {
+                ::std::option::Option::Some(value) => #parse,
+                ::std::option::Option::None => return ::std::option::Option::Some(::std::result::Result::Err(
+                    ::ohkami::Rejection::missing_header(#header_name)
+                )),
+            },
+        }});
+    }
+
+    Ok(quote!{
+        #target
+
+        const _: () = {
+            impl<
+                #from_request_impl_additional_lifetime
+                #generics_params
+            > ::ohkami::FromRequest<#from_request_lifetime> for #name<#generics_params>
+            where
+                #generics_where
+            {
+                type Error = ::ohkami::Rejection;
+
+                #[inline]
+                fn from_request(req: &#from_request_lifetime ::ohkami::Request) -> ::std::option::Option<::std::result::Result<Self, Self::Error>> {
+                    ::std::option::Option::Some(::std::result::Result::Ok(Self {
+                        #( #extract_fields )*
+                    }))
+                }
+            }
+        };
+    })
+}
+
+struct HeaderAttr {
+    name:  String,
+    /// parse the value as JSON by `serde`, instead of by `FromStr`
+    serde: bool,
+}
+
+/// `#[header(rename = "...", serde)]`, where the name defaults to
+/// the field name in `Train-Case` ( `x_request_id` → `X-Request-Id` )
+fn header_attr(field: &Field) -> Result<HeaderAttr> {
+    let mut rename = None;
+    let mut serde  = false;
+
+    for attr in field.attrs.iter().filter(|a| a.path.is_ident("header")) {
+        let Meta::List(list) = attr.parse_meta()? else {
+            return Err(Error::new_spanned(attr, "expected `#[header(rename = \"...\")]` or `#[header(serde)]`"))
+        };
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rename") => match &nv.lit {
+                    Lit::Str(s) => rename = Some(s.value()),
+                    other => return Err(Error::new_spanned(other, "expected a string literal")),
+                },
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("serde") => serde = true,
+                other => return Err(Error::new_spanned(other, "unknown `#[header]` attribute, expected `rename = \"...\"` or `serde`")),
+            }
+        }
+    }
+
+    let name = rename.unwrap_or_else(|| {
+        let ident = field.ident.as_ref().unwrap().to_string();
+        ident.trim_start_matches("r#").split('_').filter(|w| !w.is_empty()).map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|head| head.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        }).collect::<Vec<_>>().join("-")
+    });
+
+    Ok(HeaderAttr { name, serde })
+}
+
+pub(super) fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {return None};
+    let last = path.path.segments.last()?;
+    if last.ident != "Option" {return None}
+    let PathArguments::AngleBracketed(args) = &last.arguments else {return None};
+    match args.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None
+    }
+}
+
+enum ValueKind {
+    /// `&str`
+    Str,
+    /// `Cow<str>`
+    Cow,
+    FromStr,
+}
+fn value_kind(ty: &Type) -> ValueKind {
+    match ty {
+        Type::Reference(r) if r.elem.to_token_stream().to_string() == "str" => ValueKind::Str,
+        Type::Path(path) if path.path.segments.last().is_some_and(|s| s.ident == "Cow") => ValueKind::Cow,
+        _ => ValueKind::FromStr,
+    }
+}
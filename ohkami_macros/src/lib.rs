@@ -1,7 +1,9 @@
 mod serde;
 mod query;
+mod headers;
 mod payload;
 mod from_request;
+mod validate;
 
 #[cfg(feature="worker")]
 mod worker;
@@ -149,6 +151,8 @@ pub fn consume_struct(_: proc_macro::TokenStream, _: proc_macro::TokenStream) ->
 /// `#[Query]` supports `#[serde]`-conpatible `#[query]` attributes for struct fields.
 /// ( They are used in internal parsing process based on [ohkami_lib](https://crates.io/crates/ohkami_lib)'s `serde_urlencoded`. )
 /// 
+/// Fields can also have `#[validate(...)]` attributes, same as `#[Payload]`.
+/// 
 /// <br/>
 /// 
 /// *example.rs*
@@ -180,6 +184,58 @@ pub fn Query(_: proc_macro::TokenStream, data: proc_macro::TokenStream) -> proc_
 }
 
 
+/// ## Request headers
+/// 
+/// _NOTE_: NOT available for tuple struct ( like `struct S(usize, usize);` ) or unit struct ( like `struct X;` ).
+/// 
+/// Each field is read from the header of the field name in `Train-Case`
+/// ( `x_request_id` for `X-Request-Id` ), or `#[header(rename = "...")]`.
+/// Header names are case-insensitive.
+/// 
+/// - `&str` or `Cow<str>` field borrows the value
+/// - other type is parsed by `FromStr` ( whose error is `Display` )
+/// - `#[header(serde)]` field is parsed as JSON by `serde`
+/// - `Option<_>` field is optional
+/// 
+/// A missing or invalid header results in `400 Bad Request` with the reason.
+/// 
+/// <br/>
+/// 
+/// *example.rs*
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::typed::Headers; // <--
+/// 
+/// #[Headers]
+/// struct TenantHeaders<'h> {
+///     #[header(rename = "X-Tenant")]
+///     tenant:        &'h str,
+///     authorization: Option<&'h str>,
+///     x_page_size:   Option<usize>,
+///     #[header(serde)]
+///     x_features:    Option<Vec<String>>,
+/// }
+/// 
+/// async fn list_items(headers: TenantHeaders<'_>) -> String {
+///     let TenantHeaders { tenant, x_page_size, .. } = headers;
+/// 
+///     format!("{} items of {tenant}", x_page_size.unwrap_or(20))
+/// }
+/// 
+/// # let _ =
+/// Ohkami::new((
+///     "/items".GET(list_items),
+/// ))
+/// # ;
+/// ```
+#[proc_macro_attribute] #[allow(non_snake_case)]
+pub fn Headers(_: proc_macro::TokenStream, data: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    headers::Headers(data.into())
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+
 /// ## Request / Response payload
 /// 
 /// <br>
@@ -281,6 +337,20 @@ pub fn Query(_: proc_macro::TokenStream, data: proc_macro::TokenStream) -> proc_
 ///     }
 /// }
 /// ```
+/// 
+/// Or, fields can have `#[validate(...)]` attributes, and every failed one is
+/// listed in `422 Unprocessable Entity` response
+/// ( see `ohkami::typed::validate` for available rules ) :
+/// 
+/// ```ignore
+/// #[Payload(JSON/D)]
+/// struct HelloRequest<'req> {
+///     #[validate(length(min = 1, max = 32))]
+///     name: &'req str,
+///     #[validate(range(1..100))]
+///     n:    usize
+/// }
+/// ```
 #[proc_macro_attribute] #[allow(non_snake_case)]
 pub fn Payload(format: proc_macro::TokenStream, data: proc_macro::TokenStream) -> proc_macro::TokenStream {
     payload::Payload(format.into(), data.into())
@@ -92,8 +92,16 @@ struct Validation {
 
 #[allow(non_snake_case)]
 pub(super) fn Payload(format: TokenStream, target: TokenStream) -> Result<TokenStream> {
-    let format: PayloadFormat = syn::parse2(format)?;
-    let target: ItemStruct    = syn::parse2(target)?;
+    let format: PayloadFormat  = syn::parse2(format)?;
+    let mut target: ItemStruct = syn::parse2(target)?;
+
+    let validate_fields = super::validate::validate_fields(&target.fields)?.map(|validate_fields| quote! {
+        #[inline]
+        fn validate_fields(&self) -> ::std::result::Result<(), ::ohkami::typed::validate::ValidationErrors> {
+            #validate_fields
+        }
+    });
+    super::validate::strip_validate_attrs(&mut target.fields);
 
     let name            = &target.ident;
     let generics_params = &target.generics.params;
@@ -114,6 +122,8 @@ pub(super) fn Payload(format: TokenStream, target: TokenStream) -> Result<TokenS
             type Type = #payload_type;
 
             #validation
+
+            #validate_fields
         }
     })
 }
@@ -36,6 +36,9 @@ pub(super) fn Query(target: TokenStream) -> Result<TokenStream> {
         _ => return Err(syn::Error::new(Span::call_site(), "#[Query] doesn't support multiple lifetime params"))
     };
 
+    let validate_fields = super::validate::validate_fields(&target.fields)?;
+    super::validate::strip_validate_attrs(&mut target.fields);
+
     let target_cloned = {
         let mut just_cloned = ItemStruct {
             ident: format_ident!("{}__cloned", target.ident),
@@ -70,6 +73,25 @@ pub(super) fn Query(target: TokenStream) -> Result<TokenStream> {
     };
     let cloned_name = &target_cloned.ident;
 
+    let (validate, validate_fields_impl) = match validate_fields {
+        None => (None, None),
+        Some(validate_fields) => (
+            Some(quote! {
+                .and_then(|this: Self| this.__validate_fields().map(|()| this).map_err(::ohkami::Rejection::validation))
+            }),
+            Some(quote! {
+                impl<#generics_params> #name<#generics_params>
+                    #generics_where
+                {
+                    #[inline]
+                    fn __validate_fields(&self) -> ::std::result::Result<(), ::ohkami::typed::validate::ValidationErrors> {
+                        #validate_fields
+                    }
+                }
+            })
+        )
+    };
+
     let set_fields = target.fields.iter()
         .map(|Field { ident, .. }| quote!{
             #ident: self.#ident,
@@ -83,6 +105,8 @@ pub(super) fn Query(target: TokenStream) -> Result<TokenStream> {
             #[derive(::ohkami::serde::Deserialize)]
             #target_cloned
 
+            #validate_fields_impl
+
             impl<#generics_params> Into<#name<#generics_params>> for #cloned_name<#generics_params>
             where
                 #generics_where
@@ -102,13 +126,13 @@ pub(super) fn Query(target: TokenStream) -> Result<TokenStream> {
             where
                 #generics_where
             {
-                type Error = ::ohkami::Response;
+                type Error = ::ohkami::Rejection;
 
                 #[inline]
                 fn from_request(req: &#from_request_lifetime ::ohkami::Request) -> ::std::option::Option<::std::result::Result<Self, Self::Error>> {
-                    req.query.as_ref().map(|q| q.parse::<#cloned_name<#generics_params>>()
+                    req.__query::<#cloned_name<#generics_params>>().map(|result| result
                         .map(Into::into)
-                        .map_err(|e| ::ohkami::Response::BadRequest().with_text(::std::format!("Unexpected query parameters: {e}")))
+                        #validate
                     )
                 }
             }
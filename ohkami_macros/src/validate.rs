@@ -0,0 +1,184 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse::Parse, punctuated::Punctuated, token, Error, Expr, Field, Fields, Ident, Lit, LitStr, Meta, NestedMeta, Path, Result};
+use crate::headers::option_inner;
+
+
+/// A rule in `#[validate(...)]`
+enum Rule {
+    Length { min: TokenStream, max: TokenStream },
+    Email,
+    Range(TokenStream),
+    Regex(LitStr),
+    Custom(Path),
+}
+
+/// `min = {expr}` or `max = {expr}`
+struct Bound {
+    name:  Ident,
+    value: Expr,
+} impl Parse for Bound {
+    fn parse(input: syn::parse::ParseStream) -> Result<Self> {
+        let name = input.parse::<Ident>()?;
+        if name != "min" && name != "max" {
+            return Err(Error::new(name.span(), "expected `min` or `max`"))
+        }
+        input.parse::<token::Eq>()?;
+        Ok(Self { name, value: input.parse()? })
+    }
+}
+
+fn parse_bounds(input: syn::parse::ParseStream) -> Result<(Option<Expr>, Option<Expr>)> {
+    let (mut min, mut max) = (None, None);
+    for Bound { name, value } in Punctuated::<Bound, token::Comma>::parse_terminated(input)? {
+        if name == "min" {min = Some(value)} else {max = Some(value)}
+    }
+    if min.is_none() && max.is_none() {
+        return Err(input.error("expected `min = _` and/or `max = _`"))
+    }
+    Ok((min, max))
+}
+
+impl Parse for Rule {
+    fn parse(input: syn::parse::ParseStream) -> Result<Self> {
+        let name = input.parse::<Ident>()?;
+        match &*name.to_string() {
+            "length" => {
+                let content; syn::parenthesized!(content in input);
+                let (min, max) = parse_bounds(&content)?;
+                Ok(Self::Length { min: wrap_some(min), max: wrap_some(max) })
+            }
+            "email" => Ok(Self::Email),
+            "range" => {
+                let content; syn::parenthesized!(content in input);
+                if content.peek(Ident) && content.peek2(token::Eq) {
+                    Ok(Self::Range(match parse_bounds(&content)? {
+                        (Some(min), Some(max)) => quote! { (#min)..=(#max) },
+                        (Some(min), None)      => quote! { (#min).. },
+                        (None, Some(max))      => quote! { ..=(#max) },
+                        (None, None)           => unreachable!(),
+                    }))
+                } else {
+                    let range = content.parse::<Expr>()?;
+                    Ok(Self::Range(quote! { #range }))
+                }
+            }
+            "regex" => {
+                input.parse::<token::Eq>()?;
+                let pattern = input.parse::<LitStr>()?;
+                /* an invalid pattern is a compile error, not a panic at the first request */
+                if let Err(e) = ohkami_lib::regex::Regex::new(&pattern.value()) {
+                    return Err(Error::new(pattern.span(), e))
+                }
+                Ok(Self::Regex(pattern))
+            }
+            "custom" => {
+                input.parse::<token::Eq>()?;
+                Ok(Self::Custom(input.parse()?))
+            }
+            _ => Err(Error::new(name.span(), "unknown validation, expected one of `length`, `email`, `range`, `regex`, `custom`"))
+        }
+    }
+}
+
+/// Body of `fn(&self) -> Result<(), ValidationErrors>` validating the fields
+/// by their `#[validate(...)]` attributes, or `None` if there's no such attribute
+pub(super) fn validate_fields(fields: &Fields) -> Result<Option<TokenStream>> {
+    let mut validations = Vec::new();
+
+    for field in fields {
+        let rules = field.attrs.iter()
+            .filter(|a| a.path.is_ident("validate"))
+            .map(|a| a.parse_args_with(Punctuated::<Rule, token::Comma>::parse_terminated))
+            .collect::<Result<Vec<_>>>()?;
+        if rules.is_empty() {continue}
+
+        let ident = field.ident.as_ref().ok_or_else(|| Error::new_spanned(field, "#[validate] is only available for named fields"))?;
+        let name  = field_name(field);
+
+        let checks = rules.into_iter().flatten().map(|rule| match rule {
+            Rule::Length { min, max } => quote! {
+                if let ::std::result::Result::Err(e) = ::ohkami::typed::validate::length(__value, #min, #max) {
+                    __errors.push(#name, "length", e)
+                }
+            },
+            Rule::Email => quote! {
+                if let ::std::result::Result::Err(e) = ::ohkami::typed::validate::email(__value) {
+                    __errors.push(#name, "email", e)
+                }
+            },
+            Rule::Range(range) => quote! {
+                if let ::std::result::Result::Err(e) = ::ohkami::typed::validate::range(__value, #range) {
+                    __errors.push(#name, "range", e)
+                }
+            },
+            Rule::Regex(pattern) => quote! {
+                {
+                    static REGEX: ::std::sync::OnceLock<::ohkami::typed::validate::Regex> = ::std::sync::OnceLock::new();
+                    let regex = REGEX.get_or_init(|| ::ohkami::typed::validate::Regex::new(#pattern)
+                        .expect("already checked at compile time"));
+                    if let ::std::result::Result::Err(e) = ::ohkami::typed::validate::regex(__value, regex) {
+                        __errors.push(#name, "regex", e)
+                    }
+                }
+            },
+            Rule::Custom(function) => quote! {
+                if let ::std::result::Result::Err(e) = #function(__value) {
+                    __errors.push(#name, "custom", e)
+                }
+            },
+        });
+
+        validations.push(if option_inner(&field.ty).is_some() {quote! {
+            if let ::std::option::Option::Some(__value) = &self.#ident {
+                #( #checks )*
+            }
+        }} else {quote! {
+            {
+                let __value = &self.#ident;
+                #( #checks )*
+            }
+        }});
+    }
+
+    Ok((!validations.is_empty()).then(|| quote! {
+        {
+            let mut __errors = ::ohkami::typed::validate::ValidationErrors::new();
+            #( #validations )*
+            __errors.into_result()
+        }
+    }))
+}
+
+/// Remove `#[validate(...)]` attributes, which are not real attributes
+pub(super) fn strip_validate_attrs(fields: &mut Fields) {
+    for field in fields {
+        field.attrs.retain(|a| !a.path.is_ident("validate"));
+    }
+}
+
+fn wrap_some(bound: Option<Expr>) -> TokenStream {
+    match bound {
+        Some(bound) => quote! { ::std::option::Option::Some(#bound) },
+        None        => quote! { ::std::option::Option::None },
+    }
+}
+
+/// `#[serde(rename = "...")]` or `#[query(rename = "...")]`, or else the field name
+fn field_name(field: &Field) -> String {
+    field.attrs.iter()
+        .filter(|a| a.path.is_ident("serde") || a.path.is_ident("query"))
+        .filter_map(|a| match a.parse_meta().ok()? {
+            Meta::List(list) => Some(list.nested),
+            _ => None
+        })
+        .flatten()
+        .find_map(|nested| match nested {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rename") => match nv.lit {
+                Lit::Str(s) => Some(s.value()),
+                _ => None
+            },
+            _ => None
+        })
+        .unwrap_or_else(|| field.ident.as_ref().unwrap().to_string().trim_start_matches("r#").to_owned())
+}
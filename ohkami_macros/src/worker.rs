@@ -26,8 +26,10 @@ pub fn worker(ohkami_fn: TokenStream) -> Result<TokenStream> {
             env: ::worker::Env,
             ctx: ::worker::Context,
         ) -> ::worker::Result<::worker::Response> {
-            let ohkami: ::ohkami::Ohkami = #gen_ohkami;
-            Ok(ohkami.__worker__(req, env, ctx).await)
+            /* built at the first request and reused in this isolate */
+            static OHKAMI: ::ohkami::__internal__::WorkerRouter = ::ohkami::__internal__::WorkerRouter::new();
+            OHKAMI.build(|| async {#gen_ohkami}).await;
+            Ok(OHKAMI.handle(req, env, ctx).await)
         }
     })
 }
@@ -0,0 +1,66 @@
+use ohkami::prelude::*;
+use ohkami::testing::*;
+use ohkami::typed::Headers;
+
+
+#[Headers]
+struct TenantHeaders<'h> {
+    #[header(rename = "X-Tenant")]
+    tenant:        &'h str,
+    authorization: Option<&'h str>,
+    x_page_size:   Option<usize>,
+    #[header(serde)]
+    x_features:    Option<Vec<String>>,
+}
+
+async fn list_items(h: TenantHeaders<'_>) -> String {
+    format!("{} {} {:?} {:?}",
+        h.tenant,
+        h.authorization.unwrap_or("-"),
+        h.x_page_size,
+        h.x_features,
+    )
+}
+
+#[tokio::test]
+async fn typed_headers() {
+    let t = Ohkami::new((
+        "/items".GET(list_items),
+    )).test(); {
+        let req = TestRequest::GET("/items")
+            .header("x-tenant", "acme")
+            .header("Authorization", "Bearer xyz")
+            .header("X-Page-Size", " 50 ")
+            .header("X-Features", r#"["beta","dark"]"#);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some(r#"acme Bearer xyz Some(50) Some(["beta", "dark"])"#));
+    } {
+        let req = TestRequest::GET("/items")
+            .header("X-Tenant", "acme");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("acme - None None"));
+    } {
+        /* missing required header */
+        let req = TestRequest::GET("/items")
+            .header("Tenant", "acme");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert_eq!(res.text(), Some("Missing header `X-Tenant`"));
+    } {
+        /* `FromStr` failure */
+        let req = TestRequest::GET("/items")
+            .header("X-Tenant", "acme")
+            .header("X-Page-Size", "many");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert_eq!(res.text(), Some("Invalid header `X-Page-Size`: invalid digit found in string"));
+    } {
+        /* `serde` failure */
+        let req = TestRequest::GET("/items")
+            .header("X-Tenant", "acme")
+            .header("X-Features", "beta");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert!(res.text().unwrap().starts_with("Invalid header `X-Features`: "));
+    }
+}